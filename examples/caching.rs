@@ -95,6 +95,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Show how often each cache was hit
+    println!("\nCache statistics:");
+    for stats in client.cache_manager().stats() {
+        println!(
+            "  {}: {} entries, {} hits, {} misses, {} evictions",
+            stats.name, stats.entries, stats.hits, stats.misses, stats.evictions
+        );
+    }
+
     // Logout from Webull
     println!("\nLogging out...");
     match client.logout().await {
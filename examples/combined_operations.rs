@@ -202,8 +202,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 historical_data.insert(symbol.to_string(), bars);
                 println!(
                     "Retrieved {} days of historical data for {}",
-                    bar_count,
-                    symbol
+                    bar_count, symbol
                 );
             }
             Err(WebullError::InvalidRequest(msg)) => {
@@ -30,7 +30,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Some(credentials) => {
                     println!("Stored credentials:");
                     println!("  Username: {}", credentials.username);
-                    println!("  Password: {}", "*".repeat(credentials.password.len()));
+                    println!(
+                        "  Password: {}",
+                        "*".repeat(credentials.password.expose_secret().len())
+                    );
                 }
                 None => {
                     println!("No credentials stored");
@@ -0,0 +1,28 @@
+use webull_rs::client::WebullClientBuilder;
+use webull_rs::config::WebullConfig;
+use webull_rs::utils::credential_provider::CredentialProviderChain;
+
+/// Print a diagnostic report and exit with status 1 if it finds problems.
+///
+/// The configuration is read from `WEBULL_*` environment variables and the
+/// credentials from the default provider chain, so the same command can be
+/// run on any host the bot runs on.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let client = WebullClientBuilder::from_config(WebullConfig::from_env()?)
+        .with_credential_provider(CredentialProviderChain::default_chain())
+        .build()?;
+
+    // Diagnose a logged-out client if the login fails; the report says why
+    if let Err(e) = client.login_with_provider().await {
+        eprintln!("Login failed: {}", e);
+    }
+
+    let report = client.diagnose().await;
+    println!("{}", report);
+
+    if !report.is_healthy() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
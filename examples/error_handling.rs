@@ -62,7 +62,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(_) => {
             println!("Order placed successfully (unexpected)");
         }
-        Err(WebullError::ApiError { code, message }) => {
+        Err(WebullError::ApiError { code, message, .. }) => {
             println!("✓ Correctly handled API error: {} - {}", code, message);
         }
         Err(WebullError::InvalidRequest(msg)) => {
@@ -0,0 +1,866 @@
+//! Mockable traits for the endpoint groups.
+//!
+//! [`AccountApi`], [`OrderApi`], [`MarketDataApi`] and [`WatchlistApi`] mirror
+//! the endpoint groups and are implemented by both the endpoint structs and
+//! [`WebullClient`]. [`WebullApi`] combines all four. Application code that
+//! takes one of these traits instead of a concrete client can be tested
+//! against [`FakeWebull`] or a hand-written mock, without any HTTP:
+//!
+//! ```
+//! use webull_rs::api::{MarketDataApi, OrderApi};
+//! use webull_rs::models::order::{OrderRequest, OrderResponse, OrderSide};
+//! use webull_rs::WebullResult;
+//!
+//! /// Buy one share if the price is below a limit.
+//! async fn buy_the_dip(
+//!     api: &(impl MarketDataApi + OrderApi),
+//!     symbol: &str,
+//!     limit: f64,
+//! ) -> WebullResult<Option<OrderResponse>> {
+//!     let quote = api.get_quote(symbol).await?;
+//!     if quote.last_price > limit {
+//!         return Ok(None);
+//!     }
+//!
+//!     let order = OrderRequest::market()
+//!         .symbol(symbol)
+//!         .quantity(1.0)
+//!         .side(OrderSide::Buy);
+//!     api.place_order(&order).await.map(Some)
+//! }
+//! ```
+//!
+//! Convenience methods that only build parameters for another method, such as
+//! [`MarketDataApi::get_daily_bars`], have default implementations.
+//!
+//! [`FakeWebull`]: crate::fake::FakeWebull
+
+use crate::endpoints::watchlists::{
+    CreateWatchlistRequest, ModifyWatchlistRequest, Watchlist, WatchlistEndpoints,
+};
+use crate::endpoints::{
+    account::AccountEndpoints, market_data::MarketDataEndpoints, orders::OrderEndpoints,
+};
+use crate::error::WebullResult;
+use crate::models::account::{
+    Account, AccountBalance, AccountProfile, BalanceParams, PaperAccountResetRequest,
+    PaperFundingRequest, Position, PositionParams, TradeHistory,
+};
+use crate::models::market::{
+    Bar, BarQueryParams, CorpActionEventType, CorpActionParams, EodBarsParams, Instrument,
+    InstrumentParams, NewsArticle, NewsQueryParams, OptionChain, OptionChainQueryParams, Quote,
+    SnapshotParams, TimeFrame,
+};
+use crate::models::order::{
+    OptionOrderPreviewRequest, OptionOrderPreviewResponse, OptionOrderRequest, Order,
+    OrderQueryParams, OrderRequest, OrderResponse,
+};
+use crate::WebullClient;
+use async_trait::async_trait;
+
+/// All endpoint groups.
+///
+/// Implemented for every type that implements the four group traits, so
+/// `Arc<dyn WebullApi>` can hold either a [`WebullClient`] or a fake.
+pub trait WebullApi: AccountApi + OrderApi + MarketDataApi + WatchlistApi {}
+
+impl<T> WebullApi for T where T: AccountApi + OrderApi + MarketDataApi + WatchlistApi {}
+
+/// Account operations, mirroring [`AccountEndpoints`].
+#[async_trait]
+pub trait AccountApi: Send + Sync {
+    /// Get a list of accounts.
+    async fn get_accounts(&self) -> WebullResult<Vec<Account>>;
+
+    /// Get account details.
+    async fn get_account(&self, account_id: &str) -> WebullResult<Account>;
+
+    /// Get account balance.
+    async fn get_account_balance(&self, account_id: &str) -> WebullResult<AccountBalance>;
+
+    /// Get account positions.
+    async fn get_positions(&self, account_id: &str) -> WebullResult<Vec<Position>>;
+
+    /// Get account position by symbol.
+    async fn get_position(&self, account_id: &str, symbol: &str) -> WebullResult<Position>;
+
+    /// Get account trade history.
+    async fn get_trade_history(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>>;
+
+    /// Get account trade history with pagination.
+    async fn get_trade_history_paged(
+        &self,
+        account_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> WebullResult<Vec<TradeHistory>>;
+
+    /// Get account profile information.
+    async fn get_account_profile(&self, account_id: &str) -> WebullResult<AccountProfile>;
+
+    /// Get account balance with parameters.
+    async fn get_balance(&self, params: &BalanceParams) -> WebullResult<AccountBalance>;
+
+    /// Get account balance in the region's default currency.
+    async fn get_balance_with_default_currency(
+        &self,
+        account_id: &str,
+    ) -> WebullResult<AccountBalance>;
+
+    /// Get account positions with pagination.
+    async fn get_positions_with_params(
+        &self,
+        params: &PositionParams,
+    ) -> WebullResult<Vec<Position>>;
+
+    /// Get a page of account positions after an instrument.
+    async fn get_positions_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_instrument_id: Option<&str>,
+    ) -> WebullResult<Vec<Position>> {
+        let mut params = PositionParams::new(account_id, page_size);
+        if let Some(last_id) = last_instrument_id {
+            params = params.last_instrument_id(last_id);
+        }
+        self.get_positions_with_params(&params).await
+    }
+
+    /// Reset a paper trading account to a fresh starting balance.
+    async fn reset_paper_account(
+        &self,
+        request: &PaperAccountResetRequest,
+    ) -> WebullResult<AccountBalance>;
+
+    /// Add virtual funds to a paper trading account.
+    async fn fund_paper_account(
+        &self,
+        request: &PaperFundingRequest,
+    ) -> WebullResult<AccountBalance>;
+}
+
+/// Order operations, mirroring [`OrderEndpoints`].
+#[async_trait]
+pub trait OrderApi: Send + Sync {
+    /// Place an order.
+    async fn place_order(&self, order: &OrderRequest) -> WebullResult<OrderResponse>;
+
+    /// Cancel an order.
+    async fn cancel_order(&self, order_id: &str) -> WebullResult<()>;
+
+    /// Get an order by ID.
+    async fn get_order(&self, order_id: &str) -> WebullResult<Order>;
+
+    /// Get orders based on query parameters.
+    async fn get_orders(&self, params: &OrderQueryParams) -> WebullResult<Vec<Order>>;
+
+    /// Get active orders.
+    async fn get_active_orders(&self) -> WebullResult<Vec<Order>>;
+
+    /// Get filled orders.
+    async fn get_filled_orders(&self) -> WebullResult<Vec<Order>>;
+
+    /// Modify an existing order.
+    async fn modify_order(
+        &self,
+        order_id: &str,
+        order: &OrderRequest,
+    ) -> WebullResult<OrderResponse>;
+
+    /// Get open orders for an account.
+    async fn get_open_orders(&self, account_id: &str) -> WebullResult<Vec<Order>>;
+
+    /// Get open orders for an account with pagination.
+    async fn get_open_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>>;
+
+    /// Get today's orders for an account.
+    async fn get_today_orders(&self, account_id: &str) -> WebullResult<Vec<Order>>;
+
+    /// Get today's orders for an account with pagination.
+    async fn get_today_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>>;
+
+    /// Preview an option order.
+    async fn preview_option_order(
+        &self,
+        preview_request: &OptionOrderPreviewRequest,
+    ) -> WebullResult<OptionOrderPreviewResponse>;
+
+    /// Place an option order.
+    async fn place_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>>;
+
+    /// Replace an option order.
+    async fn replace_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>>;
+
+    /// Cancel an option order.
+    async fn cancel_option_order(
+        &self,
+        account_id: &str,
+        client_order_id: &str,
+    ) -> WebullResult<()>;
+}
+
+/// Market data operations, mirroring [`MarketDataEndpoints`].
+#[async_trait]
+pub trait MarketDataApi: Send + Sync {
+    /// Get a real-time quote for a symbol.
+    async fn get_quote(&self, symbol: &str) -> WebullResult<Quote>;
+
+    /// Get real-time quotes for multiple symbols.
+    async fn get_quotes(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>>;
+
+    /// Get snapshot data for symbols.
+    async fn get_snapshot(&self, params: &SnapshotParams) -> WebullResult<Vec<Quote>>;
+
+    /// Get a snapshot for a single stock symbol.
+    async fn get_stock_snapshot(&self, symbol: &str) -> WebullResult<Vec<Quote>> {
+        self.get_snapshot(&SnapshotParams::new_stock(symbol)).await
+    }
+
+    /// Get snapshots for multiple stock symbols.
+    async fn get_stock_snapshots(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>> {
+        self.get_snapshot(&SnapshotParams::new_stocks(symbols))
+            .await
+    }
+
+    /// Get historical bar data for a symbol.
+    async fn get_history_bar(&self, params: &BarQueryParams) -> WebullResult<Vec<Bar>>;
+
+    /// Get option chain for a symbol.
+    async fn get_option_chain(&self, params: &OptionChainQueryParams) -> WebullResult<OptionChain>;
+
+    /// Get market news.
+    async fn get_news(&self, params: &NewsQueryParams) -> WebullResult<Vec<NewsArticle>>;
+
+    /// Get the market calendar for the region's home market.
+    async fn get_market_calendar(&self) -> WebullResult<Vec<String>>;
+
+    /// Get instrument information.
+    async fn get_instrument(&self, params: &InstrumentParams) -> WebullResult<Vec<Instrument>>;
+
+    /// Get instrument information for a single stock symbol.
+    async fn get_stock_instrument(&self, symbol: &str) -> WebullResult<Vec<Instrument>> {
+        self.get_instrument(&InstrumentParams::new_stock(symbol))
+            .await
+    }
+
+    /// Get instrument information for multiple stock symbols.
+    async fn get_stock_instruments(&self, symbols: &[&str]) -> WebullResult<Vec<Instrument>> {
+        self.get_instrument(&InstrumentParams::new_stocks(symbols))
+            .await
+    }
+
+    /// Get end-of-day bars for instruments.
+    async fn get_eod_bar(&self, params: &EodBarsParams) -> WebullResult<Vec<Bar>>;
+
+    /// Get end-of-day bars for an instrument.
+    async fn get_instrument_eod_bars(
+        &self,
+        instrument_id: &str,
+        count: u32,
+    ) -> WebullResult<Vec<Bar>> {
+        self.get_eod_bar(&EodBarsParams::new(instrument_id, count))
+            .await
+    }
+
+    /// Get end-of-day bars for an instrument up to a date.
+    async fn get_instrument_eod_bars_with_date(
+        &self,
+        instrument_id: &str,
+        date: &str,
+        count: u32,
+    ) -> WebullResult<Vec<Bar>> {
+        self.get_eod_bar(&EodBarsParams::new(instrument_id, count).date(date))
+            .await
+    }
+
+    /// Get corporate actions for instruments.
+    async fn get_corp_action(&self, params: &CorpActionParams) -> WebullResult<Vec<Instrument>>;
+
+    /// Get stock split corporate actions for an instrument.
+    async fn get_stock_splits(&self, instrument_id: &str) -> WebullResult<Vec<Instrument>> {
+        let params = CorpActionParams::new(instrument_id, vec![CorpActionEventType::Split]);
+        self.get_corp_action(&params).await
+    }
+
+    /// Get reverse stock split corporate actions for an instrument.
+    async fn get_reverse_stock_splits(&self, instrument_id: &str) -> WebullResult<Vec<Instrument>> {
+        let params = CorpActionParams::new(instrument_id, vec![CorpActionEventType::ReverseSplit]);
+        self.get_corp_action(&params).await
+    }
+
+    /// Get all corporate actions for an instrument.
+    async fn get_all_corp_actions(&self, instrument_id: &str) -> WebullResult<Vec<Instrument>> {
+        let params = CorpActionParams::new(
+            instrument_id,
+            vec![
+                CorpActionEventType::Split,
+                CorpActionEventType::ReverseSplit,
+            ],
+        );
+        self.get_corp_action(&params).await
+    }
+
+    /// Get daily bars for a symbol.
+    async fn get_daily_bars(&self, symbol: &str, count: Option<u32>) -> WebullResult<Vec<Bar>> {
+        self.get_intraday_bars(symbol, TimeFrame::Day1, count).await
+    }
+
+    /// Get intraday bars for a symbol.
+    async fn get_intraday_bars(
+        &self,
+        symbol: &str,
+        time_frame: TimeFrame,
+        count: Option<u32>,
+    ) -> WebullResult<Vec<Bar>> {
+        let params = match count {
+            Some(count) => BarQueryParams::new(symbol, "STK", time_frame, count),
+            None => BarQueryParams::new_stock(symbol, time_frame),
+        };
+        self.get_history_bar(&params).await
+    }
+}
+
+/// Watchlist operations, mirroring [`WatchlistEndpoints`].
+#[async_trait]
+pub trait WatchlistApi: Send + Sync {
+    /// Get all watchlists.
+    async fn get_watchlists(&self) -> WebullResult<Vec<Watchlist>>;
+
+    /// Get a watchlist by ID.
+    async fn get_watchlist(&self, watchlist_id: &str) -> WebullResult<Watchlist>;
+
+    /// Create a new watchlist.
+    async fn create_watchlist(&self, request: &CreateWatchlistRequest) -> WebullResult<Watchlist>;
+
+    /// Modify a watchlist.
+    async fn modify_watchlist(&self, request: &ModifyWatchlistRequest) -> WebullResult<Watchlist>;
+
+    /// Delete a watchlist.
+    async fn delete_watchlist(&self, watchlist_id: &str) -> WebullResult<()>;
+
+    /// Add symbols to a watchlist.
+    async fn add_symbols(&self, watchlist_id: &str, symbols: &[String]) -> WebullResult<Watchlist> {
+        let request = ModifyWatchlistRequest {
+            id: watchlist_id.to_string(),
+            name: None,
+            add_symbols: Some(symbols.to_vec()),
+            remove_symbols: None,
+        };
+        self.modify_watchlist(&request).await
+    }
+
+    /// Remove symbols from a watchlist.
+    async fn remove_symbols(
+        &self,
+        watchlist_id: &str,
+        symbols: &[String],
+    ) -> WebullResult<Watchlist> {
+        let request = ModifyWatchlistRequest {
+            id: watchlist_id.to_string(),
+            name: None,
+            add_symbols: None,
+            remove_symbols: Some(symbols.to_vec()),
+        };
+        self.modify_watchlist(&request).await
+    }
+
+    /// Rename a watchlist.
+    async fn rename_watchlist(&self, watchlist_id: &str, name: &str) -> WebullResult<Watchlist> {
+        let request = ModifyWatchlistRequest {
+            id: watchlist_id.to_string(),
+            name: Some(name.to_string()),
+            add_symbols: None,
+            remove_symbols: None,
+        };
+        self.modify_watchlist(&request).await
+    }
+}
+
+#[async_trait]
+impl AccountApi for AccountEndpoints {
+    async fn get_accounts(&self) -> WebullResult<Vec<Account>> {
+        AccountEndpoints::get_accounts(self).await
+    }
+
+    async fn get_account(&self, account_id: &str) -> WebullResult<Account> {
+        AccountEndpoints::get_account(self, account_id).await
+    }
+
+    async fn get_account_balance(&self, account_id: &str) -> WebullResult<AccountBalance> {
+        AccountEndpoints::get_account_balance(self, account_id).await
+    }
+
+    async fn get_positions(&self, account_id: &str) -> WebullResult<Vec<Position>> {
+        AccountEndpoints::get_positions(self, account_id).await
+    }
+
+    async fn get_position(&self, account_id: &str, symbol: &str) -> WebullResult<Position> {
+        AccountEndpoints::get_position(self, account_id, symbol).await
+    }
+
+    async fn get_trade_history(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>> {
+        AccountEndpoints::get_trade_history(self, account_id).await
+    }
+
+    async fn get_trade_history_paged(
+        &self,
+        account_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> WebullResult<Vec<TradeHistory>> {
+        AccountEndpoints::get_trade_history_paged(self, account_id, page, page_size).await
+    }
+
+    async fn get_account_profile(&self, account_id: &str) -> WebullResult<AccountProfile> {
+        AccountEndpoints::get_account_profile(self, account_id).await
+    }
+
+    async fn get_balance(&self, params: &BalanceParams) -> WebullResult<AccountBalance> {
+        AccountEndpoints::get_balance(self, params).await
+    }
+
+    async fn get_balance_with_default_currency(
+        &self,
+        account_id: &str,
+    ) -> WebullResult<AccountBalance> {
+        AccountEndpoints::get_balance_with_default_currency(self, account_id).await
+    }
+
+    async fn get_positions_with_params(
+        &self,
+        params: &PositionParams,
+    ) -> WebullResult<Vec<Position>> {
+        AccountEndpoints::get_positions_with_params(self, params).await
+    }
+
+    async fn reset_paper_account(
+        &self,
+        request: &PaperAccountResetRequest,
+    ) -> WebullResult<AccountBalance> {
+        AccountEndpoints::reset_paper_account(self, request).await
+    }
+
+    async fn fund_paper_account(
+        &self,
+        request: &PaperFundingRequest,
+    ) -> WebullResult<AccountBalance> {
+        AccountEndpoints::fund_paper_account(self, request).await
+    }
+}
+
+#[async_trait]
+impl AccountApi for WebullClient {
+    async fn get_accounts(&self) -> WebullResult<Vec<Account>> {
+        self.accounts().get_accounts().await
+    }
+
+    async fn get_account(&self, account_id: &str) -> WebullResult<Account> {
+        self.accounts().get_account(account_id).await
+    }
+
+    async fn get_account_balance(&self, account_id: &str) -> WebullResult<AccountBalance> {
+        self.accounts().get_account_balance(account_id).await
+    }
+
+    async fn get_positions(&self, account_id: &str) -> WebullResult<Vec<Position>> {
+        self.accounts().get_positions(account_id).await
+    }
+
+    async fn get_position(&self, account_id: &str, symbol: &str) -> WebullResult<Position> {
+        self.accounts().get_position(account_id, symbol).await
+    }
+
+    async fn get_trade_history(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>> {
+        self.accounts().get_trade_history(account_id).await
+    }
+
+    async fn get_trade_history_paged(
+        &self,
+        account_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> WebullResult<Vec<TradeHistory>> {
+        self.accounts()
+            .get_trade_history_paged(account_id, page, page_size)
+            .await
+    }
+
+    async fn get_account_profile(&self, account_id: &str) -> WebullResult<AccountProfile> {
+        self.accounts().get_account_profile(account_id).await
+    }
+
+    async fn get_balance(&self, params: &BalanceParams) -> WebullResult<AccountBalance> {
+        self.accounts().get_balance(params).await
+    }
+
+    async fn get_balance_with_default_currency(
+        &self,
+        account_id: &str,
+    ) -> WebullResult<AccountBalance> {
+        self.accounts()
+            .get_balance_with_default_currency(account_id)
+            .await
+    }
+
+    async fn get_positions_with_params(
+        &self,
+        params: &PositionParams,
+    ) -> WebullResult<Vec<Position>> {
+        self.accounts().get_positions_with_params(params).await
+    }
+
+    async fn reset_paper_account(
+        &self,
+        request: &PaperAccountResetRequest,
+    ) -> WebullResult<AccountBalance> {
+        self.accounts().reset_paper_account(request).await
+    }
+
+    async fn fund_paper_account(
+        &self,
+        request: &PaperFundingRequest,
+    ) -> WebullResult<AccountBalance> {
+        self.accounts().fund_paper_account(request).await
+    }
+}
+
+#[async_trait]
+impl OrderApi for OrderEndpoints {
+    async fn place_order(&self, order: &OrderRequest) -> WebullResult<OrderResponse> {
+        OrderEndpoints::place_order(self, order).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> WebullResult<()> {
+        OrderEndpoints::cancel_order(self, order_id).await
+    }
+
+    async fn get_order(&self, order_id: &str) -> WebullResult<Order> {
+        OrderEndpoints::get_order(self, order_id).await
+    }
+
+    async fn get_orders(&self, params: &OrderQueryParams) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_orders(self, params).await
+    }
+
+    async fn get_active_orders(&self) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_active_orders(self).await
+    }
+
+    async fn get_filled_orders(&self) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_filled_orders(self).await
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: &str,
+        order: &OrderRequest,
+    ) -> WebullResult<OrderResponse> {
+        OrderEndpoints::modify_order(self, order_id, order).await
+    }
+
+    async fn get_open_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_open_orders(self, account_id).await
+    }
+
+    async fn get_open_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_open_orders_paged(self, account_id, page_size, last_order_id).await
+    }
+
+    async fn get_today_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_today_orders(self, account_id).await
+    }
+
+    async fn get_today_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        OrderEndpoints::get_today_orders_paged(self, account_id, page_size, last_order_id).await
+    }
+
+    async fn preview_option_order(
+        &self,
+        preview_request: &OptionOrderPreviewRequest,
+    ) -> WebullResult<OptionOrderPreviewResponse> {
+        OrderEndpoints::preview_option_order(self, preview_request).await
+    }
+
+    async fn place_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        OrderEndpoints::place_option_order(self, account_id, orders).await
+    }
+
+    async fn replace_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        OrderEndpoints::replace_option_order(self, account_id, orders).await
+    }
+
+    async fn cancel_option_order(
+        &self,
+        account_id: &str,
+        client_order_id: &str,
+    ) -> WebullResult<()> {
+        OrderEndpoints::cancel_option_order(self, account_id, client_order_id).await
+    }
+}
+
+#[async_trait]
+impl OrderApi for WebullClient {
+    async fn place_order(&self, order: &OrderRequest) -> WebullResult<OrderResponse> {
+        self.orders().place_order(order).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> WebullResult<()> {
+        self.orders().cancel_order(order_id).await
+    }
+
+    async fn get_order(&self, order_id: &str) -> WebullResult<Order> {
+        self.orders().get_order(order_id).await
+    }
+
+    async fn get_orders(&self, params: &OrderQueryParams) -> WebullResult<Vec<Order>> {
+        self.orders().get_orders(params).await
+    }
+
+    async fn get_active_orders(&self) -> WebullResult<Vec<Order>> {
+        self.orders().get_active_orders().await
+    }
+
+    async fn get_filled_orders(&self) -> WebullResult<Vec<Order>> {
+        self.orders().get_filled_orders().await
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: &str,
+        order: &OrderRequest,
+    ) -> WebullResult<OrderResponse> {
+        self.orders().modify_order(order_id, order).await
+    }
+
+    async fn get_open_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        self.orders().get_open_orders(account_id).await
+    }
+
+    async fn get_open_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        self.orders()
+            .get_open_orders_paged(account_id, page_size, last_order_id)
+            .await
+    }
+
+    async fn get_today_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        self.orders().get_today_orders(account_id).await
+    }
+
+    async fn get_today_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        self.orders()
+            .get_today_orders_paged(account_id, page_size, last_order_id)
+            .await
+    }
+
+    async fn preview_option_order(
+        &self,
+        preview_request: &OptionOrderPreviewRequest,
+    ) -> WebullResult<OptionOrderPreviewResponse> {
+        self.orders().preview_option_order(preview_request).await
+    }
+
+    async fn place_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        self.orders().place_option_order(account_id, orders).await
+    }
+
+    async fn replace_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        self.orders().replace_option_order(account_id, orders).await
+    }
+
+    async fn cancel_option_order(
+        &self,
+        account_id: &str,
+        client_order_id: &str,
+    ) -> WebullResult<()> {
+        self.orders()
+            .cancel_option_order(account_id, client_order_id)
+            .await
+    }
+}
+
+#[async_trait]
+impl MarketDataApi for MarketDataEndpoints {
+    async fn get_quote(&self, symbol: &str) -> WebullResult<Quote> {
+        MarketDataEndpoints::get_quote(self, symbol).await
+    }
+
+    async fn get_quotes(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>> {
+        MarketDataEndpoints::get_quotes(self, symbols).await
+    }
+
+    async fn get_snapshot(&self, params: &SnapshotParams) -> WebullResult<Vec<Quote>> {
+        MarketDataEndpoints::get_snapshot(self, params).await
+    }
+
+    async fn get_history_bar(&self, params: &BarQueryParams) -> WebullResult<Vec<Bar>> {
+        MarketDataEndpoints::get_history_bar(self, params).await
+    }
+
+    async fn get_option_chain(&self, params: &OptionChainQueryParams) -> WebullResult<OptionChain> {
+        MarketDataEndpoints::get_option_chain(self, params).await
+    }
+
+    async fn get_news(&self, params: &NewsQueryParams) -> WebullResult<Vec<NewsArticle>> {
+        MarketDataEndpoints::get_news(self, params).await
+    }
+
+    async fn get_market_calendar(&self) -> WebullResult<Vec<String>> {
+        MarketDataEndpoints::get_market_calendar(self).await
+    }
+
+    async fn get_instrument(&self, params: &InstrumentParams) -> WebullResult<Vec<Instrument>> {
+        MarketDataEndpoints::get_instrument(self, params).await
+    }
+
+    async fn get_eod_bar(&self, params: &EodBarsParams) -> WebullResult<Vec<Bar>> {
+        MarketDataEndpoints::get_eod_bar(self, params).await
+    }
+
+    async fn get_corp_action(&self, params: &CorpActionParams) -> WebullResult<Vec<Instrument>> {
+        MarketDataEndpoints::get_corp_action(self, params).await
+    }
+}
+
+#[async_trait]
+impl MarketDataApi for WebullClient {
+    async fn get_quote(&self, symbol: &str) -> WebullResult<Quote> {
+        self.market_data().get_quote(symbol).await
+    }
+
+    async fn get_quotes(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>> {
+        self.market_data().get_quotes(symbols).await
+    }
+
+    async fn get_snapshot(&self, params: &SnapshotParams) -> WebullResult<Vec<Quote>> {
+        self.market_data().get_snapshot(params).await
+    }
+
+    async fn get_history_bar(&self, params: &BarQueryParams) -> WebullResult<Vec<Bar>> {
+        self.market_data().get_history_bar(params).await
+    }
+
+    async fn get_option_chain(&self, params: &OptionChainQueryParams) -> WebullResult<OptionChain> {
+        self.market_data().get_option_chain(params).await
+    }
+
+    async fn get_news(&self, params: &NewsQueryParams) -> WebullResult<Vec<NewsArticle>> {
+        self.market_data().get_news(params).await
+    }
+
+    async fn get_market_calendar(&self) -> WebullResult<Vec<String>> {
+        self.market_data().get_market_calendar().await
+    }
+
+    async fn get_instrument(&self, params: &InstrumentParams) -> WebullResult<Vec<Instrument>> {
+        self.market_data().get_instrument(params).await
+    }
+
+    async fn get_eod_bar(&self, params: &EodBarsParams) -> WebullResult<Vec<Bar>> {
+        self.market_data().get_eod_bar(params).await
+    }
+
+    async fn get_corp_action(&self, params: &CorpActionParams) -> WebullResult<Vec<Instrument>> {
+        self.market_data().get_corp_action(params).await
+    }
+}
+
+#[async_trait]
+impl WatchlistApi for WatchlistEndpoints {
+    async fn get_watchlists(&self) -> WebullResult<Vec<Watchlist>> {
+        WatchlistEndpoints::get_watchlists(self).await
+    }
+
+    async fn get_watchlist(&self, watchlist_id: &str) -> WebullResult<Watchlist> {
+        WatchlistEndpoints::get_watchlist(self, watchlist_id).await
+    }
+
+    async fn create_watchlist(&self, request: &CreateWatchlistRequest) -> WebullResult<Watchlist> {
+        WatchlistEndpoints::create_watchlist(self, request).await
+    }
+
+    async fn modify_watchlist(&self, request: &ModifyWatchlistRequest) -> WebullResult<Watchlist> {
+        WatchlistEndpoints::modify_watchlist(self, request).await
+    }
+
+    async fn delete_watchlist(&self, watchlist_id: &str) -> WebullResult<()> {
+        WatchlistEndpoints::delete_watchlist(self, watchlist_id).await
+    }
+}
+
+#[async_trait]
+impl WatchlistApi for WebullClient {
+    async fn get_watchlists(&self) -> WebullResult<Vec<Watchlist>> {
+        self.watchlists().get_watchlists().await
+    }
+
+    async fn get_watchlist(&self, watchlist_id: &str) -> WebullResult<Watchlist> {
+        self.watchlists().get_watchlist(watchlist_id).await
+    }
+
+    async fn create_watchlist(&self, request: &CreateWatchlistRequest) -> WebullResult<Watchlist> {
+        self.watchlists().create_watchlist(request).await
+    }
+
+    async fn modify_watchlist(&self, request: &ModifyWatchlistRequest) -> WebullResult<Watchlist> {
+        self.watchlists().modify_watchlist(request).await
+    }
+
+    async fn delete_watchlist(&self, watchlist_id: &str) -> WebullResult<()> {
+        self.watchlists().delete_watchlist(watchlist_id).await
+    }
+}
@@ -1,34 +1,75 @@
+use crate::cassette::{Cassette, CassetteRequest};
 use crate::config::WebullConfig;
 use crate::error::{WebullError, WebullResult};
-use crate::utils::crypto::{encrypt_password, generate_signature, generate_timestamp};
+use crate::telemetry::{self, Metrics, NoopMetrics};
+use crate::transport::{HttpRequest, HttpTransport};
+use crate::utils::clock::Clock;
+use crate::utils::crypto::{encrypt_password, generate_signature};
+use crate::utils::failover::Failover;
+use crate::utils::rate_limit::RateLimiter;
+use crate::utils::secret::SecretString;
 use crate::utils::serialization::{from_json, to_json};
 use chrono::{DateTime, Utc};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
-use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::sync::Mutex;
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::Instrument;
+use url::Url;
 
 /// Credentials for authentication.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The password is redacted from `Debug` output and zeroed on drop.
+#[derive(Debug, Clone, Deserialize)]
 pub struct Credentials {
     /// Username for authentication
     pub username: String,
 
     /// Password for authentication
-    pub password: String,
+    pub password: SecretString,
+}
+
+impl Credentials {
+    /// Create new credentials.
+    pub fn new(username: impl Into<String>, password: impl Into<SecretString>) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
 }
 
 /// Access token for API requests.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The access and refresh tokens are redacted from `Debug` output and zeroed on drop.
+#[derive(Debug, Clone, Deserialize)]
 pub struct AccessToken {
     /// The access token
-    pub token: String,
+    pub token: SecretString,
 
     /// When the token expires
     pub expires_at: DateTime<Utc>,
 
     /// The refresh token
-    pub refresh_token: Option<String>,
+    pub refresh_token: Option<SecretString>,
+}
+
+impl AccessToken {
+    /// Build the value of the `Authorization` header for this token.
+    ///
+    /// The returned header value is marked as sensitive so that it is not
+    /// printed by `Debug` implementations of requests or header maps.
+    pub fn bearer_header(&self) -> WebullResult<HeaderValue> {
+        let mut value = HeaderValue::from_str(&format!("Bearer {}", self.token.expose_secret()))
+            .map_err(|_| {
+                WebullError::InvalidRequest("Invalid characters in access token".to_string())
+            })?;
+        value.set_sensitive(true);
+        Ok(value)
+    }
 }
 
 /// Interface for storing and retrieving tokens.
@@ -66,9 +107,17 @@ impl TokenStore for MemoryTokenStore {
 }
 
 /// Manager for authentication.
+///
+/// All methods take `&self`, so one manager can be shared by every endpoint
+/// group and clone of a client. The credentials and token are kept behind
+/// locks, and token refreshes are serialized so concurrent callers do not
+/// spend the same refresh token twice.
 pub struct AuthManager {
     /// Credentials for authentication
-    credentials: Option<Credentials>,
+    credentials: Mutex<Option<Credentials>>,
+
+    /// Held while the token is being refreshed
+    refresh_lock: tokio::sync::Mutex<()>,
 
     /// Token store
     pub token_store: Box<dyn TokenStore>,
@@ -76,8 +125,29 @@ pub struct AuthManager {
     /// Configuration
     config: WebullConfig,
 
-    /// HTTP client
-    client: reqwest::Client,
+    /// HTTP transport
+    transport: Arc<dyn HttpTransport>,
+
+    /// Metrics sink for authentication calls
+    metrics: Arc<dyn Metrics>,
+
+    /// Cassette that records or replays authentication calls
+    cassette: Option<Arc<Cassette>>,
+
+    /// Hosts to fail over across, if there are fallback base URLs
+    failover: Option<Arc<Failover>>,
+
+    /// Clock for request timestamps
+    clock: Arc<Clock>,
+
+    /// Rate limiter that passport calls take budget from
+    rate_limiter: Arc<RateLimiter>,
+
+    /// API key, from the configuration or resolved at login
+    api_key: Mutex<Option<String>>,
+
+    /// API secret, from the configuration or resolved at login
+    api_secret: Mutex<Option<SecretString>>,
 }
 
 impl AuthManager {
@@ -85,33 +155,85 @@ impl AuthManager {
     pub fn new(
         config: WebullConfig,
         token_store: Box<dyn TokenStore>,
-        client: reqwest::Client,
+        transport: Arc<dyn HttpTransport>,
     ) -> Self {
         Self {
-            credentials: None,
+            credentials: Mutex::new(None),
+            refresh_lock: tokio::sync::Mutex::new(()),
             token_store,
+            transport,
+            metrics: Arc::new(NoopMetrics),
+            cassette: None,
+            failover: None,
+            clock: Arc::new(Clock::from_config(&config.clock_skew)),
+            rate_limiter: Arc::new(RateLimiter::from_config(&config.rate_limit)),
+            api_key: Mutex::new(config.api_key.clone()),
+            api_secret: Mutex::new(config.api_secret.clone()),
             config,
-            client,
+        }
+    }
+
+    /// Report authentication calls to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Record authentication calls to, or replay them from, a cassette.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Fail over across a list of base URLs.
+    ///
+    /// Authentication calls are not idempotent, so they only move to the next
+    /// host when the connection to a host could not be made.
+    pub fn with_failover(mut self, failover: Arc<Failover>) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    /// Take request timestamps from a clock, to share its skew measurements.
+    pub fn with_clock(mut self, clock: Arc<Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Take budget for authentication calls from a rate limiter, to share it
+    /// with the rest of the client.
+    ///
+    /// Passport calls count against the `auth` category and the global cap.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Get the clock that request timestamps are taken from.
+    pub fn clock(&self) -> &Arc<Clock> {
+        &self.clock
+    }
+
+    /// Set the API key and secret, where the configuration has none.
+    pub fn fill_api_credentials(&self, api_key: Option<String>, api_secret: Option<SecretString>) {
+        let mut current_key = self.api_key.lock().unwrap();
+        if current_key.is_none() {
+            *current_key = api_key;
+        }
+        let mut current_secret = self.api_secret.lock().unwrap();
+        if current_secret.is_none() {
+            *current_secret = api_secret;
         }
     }
 
     /// Authenticate with username and password.
-    pub async fn authenticate(
-        &mut self,
-        username: &str,
-        password: &str,
-    ) -> WebullResult<AccessToken> {
+    pub async fn authenticate(&self, username: &str, password: &str) -> WebullResult<AccessToken> {
         // Store credentials for potential token refresh
-        self.credentials = Some(Credentials {
-            username: username.to_string(),
-            password: password.to_string(),
-        });
+        *self.credentials.lock().unwrap() = Some(Credentials::new(username, password));
 
         // Encrypt the password
-        let encrypted_password = encrypt_password(
-            password,
-            &self.config.api_secret.clone().unwrap_or_default(),
-        )?;
+        let api_secret = self.api_secret.lock().unwrap().clone().unwrap_or_default();
+        let encrypted_password = encrypt_password(password, api_secret.expose_secret())?;
 
         // Create the request body
         let body = json!({
@@ -122,72 +244,21 @@ impl AuthManager {
             "deviceType": "Web",
         });
 
-        // Create headers
-        let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-        // Add API key if available
-        if let Some(api_key) = &self.config.api_key {
-            headers.insert("api-key", HeaderValue::from_str(api_key).unwrap());
-        }
-
-        // Generate timestamp and signature
-        let timestamp = generate_timestamp();
-        let signature = if let Some(api_secret) = &self.config.api_secret {
-            let message = format!("{}{}", timestamp, to_json(&body)?);
-            generate_signature(api_secret, &message)?
-        } else {
-            String::new()
-        };
-
-        // Add timestamp and signature to headers
-        headers.insert("timestamp", HeaderValue::from_str(&timestamp).unwrap());
-        headers.insert("signature", HeaderValue::from_str(&signature).unwrap());
-
         // Send the request
-        let response = self
-            .client
-            .post(format!(
-                "{}/api/passport/login/v5/account",
-                self.config.base_url
-            ))
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
+        let (status, response_text) = self
+            .send_signed("login", "/api/passport/login/v5/account", &body, None)
+            .await?;
 
         // Check for errors
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            if status.as_u16() == 401 {
-                return Err(WebullError::Unauthorized);
-            } else if status.as_u16() == 429 {
-                return Err(WebullError::RateLimitExceeded);
-            } else {
-                return Err(WebullError::ApiError {
-                    code: status.as_u16().to_string(),
-                    message: text,
-                });
-            }
+        if !status.is_success() {
+            return Err(status_error(status, &response_text));
         }
 
         // Parse the response
-        let response_text = response
-            .text()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
-
         #[derive(Debug, Deserialize)]
         struct LoginResponse {
-            access_token: String,
-            refresh_token: String,
-            token_type: String,
+            access_token: SecretString,
+            refresh_token: SecretString,
             expires_in: i64,
         }
 
@@ -207,85 +278,39 @@ impl AuthManager {
     }
 
     /// Handle multi-factor authentication.
-    pub async fn multi_factor_auth(&mut self, mfa_code: &str) -> WebullResult<AccessToken> {
+    pub async fn multi_factor_auth(&self, mfa_code: &str) -> WebullResult<AccessToken> {
         // Check if we have credentials
-        let credentials = self.credentials.as_ref().ok_or_else(|| {
-            WebullError::InvalidRequest("No credentials available for MFA".to_string())
-        })?;
+        let username = match &*self.credentials.lock().unwrap() {
+            Some(credentials) => credentials.username.clone(),
+            None => {
+                return Err(WebullError::InvalidRequest(
+                    "No credentials available for MFA".to_string(),
+                ))
+            }
+        };
 
         // Create the request body
         let body = json!({
-            "username": credentials.username,
+            "username": username,
             "verificationCode": mfa_code,
             "deviceId": self.config.device_id.clone().unwrap_or_default(),
         });
 
-        // Create headers
-        let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-        // Add API key if available
-        if let Some(api_key) = &self.config.api_key {
-            headers.insert("api-key", HeaderValue::from_str(api_key).unwrap());
-        }
-
-        // Generate timestamp and signature
-        let timestamp = generate_timestamp();
-        let signature = if let Some(api_secret) = &self.config.api_secret {
-            let message = format!("{}{}", timestamp, to_json(&body)?);
-            generate_signature(api_secret, &message)?
-        } else {
-            String::new()
-        };
-
-        // Add timestamp and signature to headers
-        headers.insert("timestamp", HeaderValue::from_str(&timestamp).unwrap());
-        headers.insert("signature", HeaderValue::from_str(&signature).unwrap());
-
         // Send the request
-        let response = self
-            .client
-            .post(format!(
-                "{}/api/passport/verificationCode/verify",
-                self.config.base_url
-            ))
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
+        let (status, response_text) = self
+            .send_signed("mfa", "/api/passport/verificationCode/verify", &body, None)
+            .await?;
 
         // Check for errors
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            if status.as_u16() == 401 {
-                return Err(WebullError::Unauthorized);
-            } else if status.as_u16() == 429 {
-                return Err(WebullError::RateLimitExceeded);
-            } else {
-                return Err(WebullError::ApiError {
-                    code: status.as_u16().to_string(),
-                    message: text,
-                });
-            }
+        if !status.is_success() {
+            return Err(status_error(status, &response_text));
         }
 
         // Parse the response
-        let response_text = response
-            .text()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
-
         #[derive(Debug, Deserialize)]
         struct MfaResponse {
-            access_token: String,
-            refresh_token: String,
-            token_type: String,
+            access_token: SecretString,
+            refresh_token: SecretString,
             expires_in: i64,
         }
 
@@ -305,12 +330,27 @@ impl AuthManager {
     }
 
     /// Refresh the access token.
-    pub async fn refresh_token(&mut self) -> WebullResult<AccessToken> {
+    ///
+    /// Concurrent callers share one refresh: whoever gets there first refreshes
+    /// the token, and the others return the token it stored.
+    pub async fn refresh_token(&self) -> WebullResult<AccessToken> {
+        let seen = self.token_store.get_token()?;
+
+        // Wait for any refresh in progress, then use the token it stored
+        let _refreshing = self.refresh_lock.lock().await;
+
         // Get the current token
         let current_token = self.token_store.get_token()?.ok_or_else(|| {
             WebullError::InvalidRequest("No token available for refresh".to_string())
         })?;
 
+        // Another caller refreshed the token while we were waiting
+        let refreshed = seen
+            .is_none_or(|seen| seen.token.expose_secret() != current_token.token.expose_secret());
+        if refreshed && current_token.expires_at > Utc::now() {
+            return Ok(current_token);
+        }
+
         // Check if we have a refresh token
         let refresh_token = current_token
             .refresh_token
@@ -318,76 +358,25 @@ impl AuthManager {
 
         // Create the request body
         let body = json!({
-            "refreshToken": refresh_token,
+            "refreshToken": refresh_token.expose_secret(),
             "deviceId": self.config.device_id.clone().unwrap_or_default(),
         });
 
-        // Create headers
-        let mut headers = HeaderMap::new();
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-
-        // Add API key if available
-        if let Some(api_key) = &self.config.api_key {
-            headers.insert("api-key", HeaderValue::from_str(api_key).unwrap());
-        }
-
-        // Generate timestamp and signature
-        let timestamp = generate_timestamp();
-        let signature = if let Some(api_secret) = &self.config.api_secret {
-            let message = format!("{}{}", timestamp, to_json(&body)?);
-            generate_signature(api_secret, &message)?
-        } else {
-            String::new()
-        };
-
-        // Add timestamp and signature to headers
-        headers.insert("timestamp", HeaderValue::from_str(&timestamp).unwrap());
-        headers.insert("signature", HeaderValue::from_str(&signature).unwrap());
-
         // Send the request
-        let response = self
-            .client
-            .post(format!(
-                "{}/api/passport/refreshToken",
-                self.config.base_url
-            ))
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
+        let (status, response_text) = self
+            .send_signed("refresh", "/api/passport/refreshToken", &body, None)
+            .await?;
 
         // Check for errors
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            if status.as_u16() == 401 {
-                return Err(WebullError::Unauthorized);
-            } else if status.as_u16() == 429 {
-                return Err(WebullError::RateLimitExceeded);
-            } else {
-                return Err(WebullError::ApiError {
-                    code: status.as_u16().to_string(),
-                    message: text,
-                });
-            }
+        if !status.is_success() {
+            return Err(status_error(status, &response_text));
         }
 
         // Parse the response
-        let response_text = response
-            .text()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
-
         #[derive(Debug, Deserialize)]
         struct RefreshResponse {
-            access_token: String,
-            refresh_token: String,
-            token_type: String,
+            access_token: SecretString,
+            refresh_token: SecretString,
             expires_in: i64,
         }
 
@@ -421,83 +410,278 @@ impl AuthManager {
     }
 
     /// Revoke the current token.
-    pub async fn revoke_token(&mut self) -> WebullResult<()> {
+    pub async fn revoke_token(&self) -> WebullResult<()> {
         // Get the current token
         let current_token = match self.token_store.get_token()? {
             Some(token) => token,
             None => {
                 // No token to revoke
-                self.credentials = None;
+                *self.credentials.lock().unwrap() = None;
                 return Ok(());
             }
         };
 
         // Create the request body
         let body = json!({
-            "accessToken": current_token.token,
+            "accessToken": current_token.token.expose_secret(),
             "deviceId": self.config.device_id.clone().unwrap_or_default(),
         });
 
+        // Send the request
+        let (status, response_text) = self
+            .send_signed(
+                "logout",
+                "/api/passport/logout",
+                &body,
+                Some(&current_token),
+            )
+            .await?;
+
+        // Check for errors; a 401 means the token is already invalid, so we
+        // can just clear it
+        if !status.is_success() && status != StatusCode::UNAUTHORIZED {
+            return Err(status_error(status, &response_text));
+        }
+
+        // Clear the token and credentials
+        self.token_store.clear_token()?;
+        *self.credentials.lock().unwrap() = None;
+
+        Ok(())
+    }
+
+    /// Send a signed request to a passport endpoint and read the response.
+    ///
+    /// The call runs in a `webull.auth` span. The API key, signature and
+    /// token headers are marked as sensitive, so they are never printed.
+    async fn send_signed(
+        &self,
+        operation: &'static str,
+        path: &str,
+        body: &Value,
+        token: Option<&AccessToken>,
+    ) -> WebullResult<(StatusCode, String)> {
+        let span =
+            tracing::info_span!("webull.auth", operation, status = Empty, latency_ms = Empty);
+        let started = Instant::now();
+        let result = self
+            .send_signed_request(path, body, token)
+            .instrument(span.clone())
+            .await;
+        let latency = started.elapsed();
+
+        let status = match &result {
+            Ok((status, _)) => status.as_str().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        span.record("status", status.as_str());
+        span.record("latency_ms", latency.as_millis() as u64);
+
+        self.metrics.record_histogram(
+            telemetry::AUTH_DURATION_SECONDS,
+            latency.as_secs_f64(),
+            &[("operation", operation)],
+        );
+        self.metrics.increment_counter(
+            telemetry::AUTH_REQUESTS_TOTAL,
+            &[("operation", operation), ("status", status.as_str())],
+        );
+
+        result
+    }
+
+    /// Build, sign and send a request to a passport endpoint.
+    async fn send_signed_request(
+        &self,
+        path: &str,
+        body: &Value,
+        token: Option<&AccessToken>,
+    ) -> WebullResult<(StatusCode, String)> {
+        let body = to_json(body)?;
+
         // Create headers
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", current_token.token)).unwrap(),
-        );
+        if let Some(token) = token {
+            headers.insert(AUTHORIZATION, token.bearer_header()?);
+        }
 
         // Add API key if available
-        if let Some(api_key) = &self.config.api_key {
-            headers.insert("api-key", HeaderValue::from_str(api_key).unwrap());
+        if let Some(api_key) = self.api_key.lock().unwrap().as_deref() {
+            headers.insert("api-key", sensitive_header(api_key)?);
         }
 
         // Generate timestamp and signature
-        let timestamp = generate_timestamp();
-        let signature = if let Some(api_secret) = &self.config.api_secret {
-            let message = format!("{}{}", timestamp, to_json(&body)?);
-            generate_signature(api_secret, &message)?
+        let timestamp = self.clock.timestamp()?;
+        let api_secret = self.api_secret.lock().unwrap().clone();
+        let signature = if let Some(api_secret) = &api_secret {
+            let message = format!("{}{}", timestamp, body);
+            generate_signature(api_secret.expose_secret(), &message)?
         } else {
             String::new()
         };
 
         // Add timestamp and signature to headers
         headers.insert("timestamp", HeaderValue::from_str(&timestamp).unwrap());
-        headers.insert("signature", HeaderValue::from_str(&signature).unwrap());
+        headers.insert("signature", sensitive_header(&signature)?);
+
+        // Send the request, through the cassette if there is one
+        let url = format!("{}{}", self.config.base_url, path);
+        let url = Url::parse(&url)
+            .map_err(|e| WebullError::InvalidRequest(format!("Invalid URL {}: {}", url, e)))?;
+        let recorded = CassetteRequest::new(&Method::POST, path, None, Some(body.clone()));
+        let request = HttpRequest {
+            method: Method::POST,
+            url: url.clone(),
+            headers,
+            body: Some(body),
+        };
 
-        // Send the request
-        let response = self
-            .client
-            .post(format!("{}/api/passport/logout", self.config.base_url))
-            .headers(headers)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| WebullError::NetworkError(e))?;
+        let send = |url: Url| {
+            let request = HttpRequest {
+                url,
+                ..request.clone()
+            };
+            let recorded = recorded.clone();
+            async move {
+                match &self.cassette {
+                    Some(cassette) => {
+                        cassette
+                            .exchange(recorded, self.transport.send(request))
+                            .await
+                    }
+                    None => {
+                        let sent_at = Utc::now();
+                        let response = self.transport.send(request).await?;
+                        self.clock.observe(&response.headers, sent_at, Utc::now());
+                        Ok(response)
+                    }
+                }
+            }
+        };
+        self.rate_limiter.wait(path).await;
+        let response = match &self.failover {
+            Some(failover) => failover.send(&url, false, send).await?,
+            None => send(url).await?,
+        };
 
-        // Check for errors
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            if status.as_u16() == 401 {
-                // Token is already invalid, so we can just clear it
-            } else if status.as_u16() == 429 {
-                return Err(WebullError::RateLimitExceeded);
-            } else {
-                return Err(WebullError::ApiError {
-                    code: status.as_u16().to_string(),
-                    message: text,
-                });
+        self.rate_limiter
+            .update_from_headers(path, &response.headers);
+        if response.status == StatusCode::TOO_MANY_REQUESTS {
+            if let Some(retry_after) = response.retry_after() {
+                self.rate_limiter.pause(path, retry_after);
             }
         }
 
-        // Clear the token and credentials
-        self.token_store.clear_token()?;
-        self.credentials = None;
+        Ok((response.status, response.body))
+    }
+}
 
-        Ok(())
+/// Build a header value that is never printed by `Debug` implementations.
+fn sensitive_header(value: &str) -> WebullResult<HeaderValue> {
+    let mut value = HeaderValue::from_str(value)
+        .map_err(|_| WebullError::InvalidRequest("Invalid characters in header".to_string()))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Get the error for an unsuccessful authentication response.
+fn status_error(status: StatusCode, body: &str) -> WebullError {
+    match status {
+        StatusCode::UNAUTHORIZED => WebullError::Unauthorized,
+        StatusCode::TOO_MANY_REQUESTS => WebullError::RateLimitExceeded,
+        status => WebullError::from_response(status.as_u16(), body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::RateLimitConfig;
+    use crate::middleware::HttpResponse;
+    use crate::transport::testing::FakeTransport;
+    use crate::transport::{HttpRequest, HttpTransport};
+    use crate::utils::rate_limit::RateCategory;
+    use crate::{WebullClient, WebullResult};
+    use reqwest::StatusCode;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn logins_take_auth_budget() {
+        let client = WebullClient::builder()
+            .with_transport(FakeTransport::new())
+            .with_rate_limit_config(RateLimitConfig {
+                auth_per_minute: 1,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+
+        let started = tokio::time::Instant::now();
+        client.login("user", "password").await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        client.login("user", "password").await.unwrap();
+        assert!(started.elapsed() >= Duration::from_secs(59));
+
+        let global = client.rate_limiter().global_headroom();
+        assert!(global.available < global.limit);
+        assert!(client
+            .rate_limiter()
+            .headroom(RateCategory::Auth)
+            .unwrap()
+            .is_exhausted());
+    }
+
+    #[tokio::test]
+    async fn concurrent_refreshes_share_one_request() {
+        /// Answers slowly, so that refreshes overlap.
+        struct SlowTransport(FakeTransport);
+
+        #[async_trait::async_trait]
+        impl HttpTransport for SlowTransport {
+            async fn send(&self, request: HttpRequest) -> WebullResult<HttpResponse> {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.0.send(request).await
+            }
+        }
+
+        let issued = AtomicUsize::new(0);
+        let fake = FakeTransport::with_handler(move |request| {
+            (request.url.path() == "/api/passport/refreshToken").then(|| {
+                let n = issued.fetch_add(1, Ordering::SeqCst);
+                HttpResponse::new(
+                    StatusCode::OK,
+                    format!(
+                        r#"{{"access_token":"a{}","refresh_token":"r{}","expires_in":3600}}"#,
+                        n, n
+                    ),
+                )
+            })
+        });
+        let client = WebullClient::builder()
+            .with_transport(SlowTransport(fake.clone()))
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+
+        let tasks: Vec<_> = (0..5)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.refresh_token().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let refreshes = fake
+            .requests()
+            .iter()
+            .filter(|request| request.ends_with("/api/passport/refreshToken"))
+            .count();
+        assert_eq!(refreshes, 1);
+        let token = client.auth_manager().get_token().await.unwrap();
+        assert_eq!(token.token.expose_secret(), "a0");
     }
 }
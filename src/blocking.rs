@@ -0,0 +1,666 @@
+//! Blocking client.
+//!
+//! [`WebullClient`] wraps the async [`crate::WebullClient`] and runs every
+//! call to completion on an internal tokio runtime, like `reqwest::blocking`,
+//! so synchronous programs can use the API without managing a runtime. The
+//! endpoint groups mirror the async ones and return the same models and
+//! [`WebullResult`] errors:
+//!
+//! ```no_run
+//! # fn main() -> webull_rs::WebullResult<()> {
+//! let client = webull_rs::blocking::WebullClient::builder()
+//!     .with_api_key("key")
+//!     .with_api_secret("secret")
+//!     .build_blocking()?;
+//!
+//! client.login("username", "password")?;
+//! let quote = client.market_data().get_quote("AAPL")?;
+//! println!("AAPL: {}", quote.last_price);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The blocking client must not be used or dropped from within an async
+//! runtime; blocking calls panic there, as `Runtime::block_on` does. Async
+//! programs should use [`crate::WebullClient`] directly. Streaming is only
+//! available on the async client.
+//!
+//! Requires the `blocking` feature.
+
+use crate::auth::Credentials;
+use crate::config::WebullConfig;
+use crate::diagnostics::DiagnosticReport;
+use crate::endpoints;
+use crate::endpoints::watchlists::{CreateWatchlistRequest, ModifyWatchlistRequest, Watchlist};
+use crate::error::{WebullError, WebullResult};
+use crate::models::account::{
+    Account, AccountBalance, AccountProfile, BalanceParams, PaperAccountResetRequest,
+    PaperFundingRequest, Position, PositionParams, TradeHistory,
+};
+use crate::models::market::{
+    Bar, BarQueryParams, CorpActionParams, EodBarsParams, Instrument, InstrumentParams,
+    NewsArticle, NewsQueryParams, OptionChain, OptionChainQueryParams, Quote, SnapshotParams,
+    TimeFrame,
+};
+use crate::models::order::{
+    OptionOrderPreviewRequest, OptionOrderPreviewResponse, OptionOrderRequest, Order,
+    OrderQueryParams, OrderRequest, OrderResponse,
+};
+use crate::region::Region;
+use crate::utils::cache::CacheManager;
+use crate::utils::circuit_breaker::CircuitBreaker;
+use crate::utils::clock::Clock;
+use crate::utils::credential_provider::ResolvedCredentials;
+use crate::utils::failover::Failover;
+use crate::utils::rate_limit::RateLimiter;
+use crate::utils::retry::RetryPolicy;
+use crate::WebullClientBuilder;
+use chrono::TimeDelta;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Blocking client for the Webull API.
+///
+/// Cloning the client is cheap; clones share the async client and the
+/// runtime, and can be used from several threads at once.
+#[derive(Clone)]
+pub struct WebullClient {
+    inner: crate::WebullClient,
+    runtime: Arc<Runtime>,
+}
+
+impl WebullClient {
+    /// Create a new builder for configuring the client.
+    ///
+    /// Finish it with [`WebullClientBuilder::build_blocking`].
+    pub fn builder() -> WebullClientBuilder {
+        WebullClientBuilder::new()
+    }
+
+    /// Create a blocking client around an async client.
+    pub fn new(inner: crate::WebullClient) -> WebullResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("webull-blocking")
+            .enable_all()
+            .build()
+            .map_err(|e| WebullError::Unknown(format!("Failed to start runtime: {}", e)))?;
+
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Get the async client this client wraps.
+    pub fn async_client(&self) -> &crate::WebullClient {
+        &self.inner
+    }
+
+    /// Login to Webull.
+    pub fn login(&self, username: &str, password: &str) -> WebullResult<()> {
+        self.runtime.block_on(self.inner.login(username, password))
+    }
+
+    /// Complete a login that requires multi-factor authentication.
+    pub fn verify_mfa(&self, mfa_code: &str) -> WebullResult<()> {
+        self.runtime.block_on(self.inner.verify_mfa(mfa_code))
+    }
+
+    /// Resolve credentials from the configured provider chain.
+    pub fn resolve_credentials(&self) -> WebullResult<ResolvedCredentials> {
+        self.inner.resolve_credentials()
+    }
+
+    /// Login to Webull with a username and password resolved from the provider chain.
+    pub fn login_with_provider(&self) -> WebullResult<()> {
+        self.runtime.block_on(self.inner.login_with_provider())
+    }
+
+    /// Logout from Webull.
+    pub fn logout(&self) -> WebullResult<()> {
+        self.runtime.block_on(self.inner.logout())
+    }
+
+    /// Refresh the authentication token.
+    pub fn refresh_token(&self) -> WebullResult<()> {
+        self.runtime.block_on(self.inner.refresh_token())
+    }
+
+    /// Get account endpoints.
+    pub fn accounts(&self) -> AccountEndpoints {
+        AccountEndpoints {
+            inner: self.inner.accounts(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get market data endpoints.
+    pub fn market_data(&self) -> MarketDataEndpoints {
+        MarketDataEndpoints {
+            inner: self.inner.market_data(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get order endpoints.
+    pub fn orders(&self) -> OrderEndpoints {
+        OrderEndpoints {
+            inner: self.inner.orders(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get watchlist endpoints.
+    pub fn watchlists(&self) -> WatchlistEndpoints {
+        WatchlistEndpoints {
+            inner: self.inner.watchlists(),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Get the rate limiter shared by all endpoints.
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        self.inner.rate_limiter()
+    }
+
+    /// Get the response cache shared by all endpoints.
+    pub fn cache_manager(&self) -> &Arc<CacheManager> {
+        self.inner.cache_manager()
+    }
+
+    /// Get the retry policy shared by all endpoints.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        self.inner.retry_policy()
+    }
+
+    /// Get the circuit breaker shared by all endpoints, if enabled.
+    pub fn circuit_breaker(&self) -> Option<&Arc<CircuitBreaker>> {
+        self.inner.circuit_breaker()
+    }
+
+    /// Get the hosts requests fail over across, if there are fallback base URLs.
+    pub fn failover(&self) -> Option<&Arc<Failover>> {
+        self.inner.failover()
+    }
+
+    /// Get the clock that request timestamps are taken from.
+    pub fn clock(&self) -> &Arc<Clock> {
+        self.inner.clock()
+    }
+
+    /// Measure the skew between the local and server clocks now.
+    pub fn sync_clock(&self) -> WebullResult<TimeDelta> {
+        self.runtime.block_on(self.inner.sync_clock())
+    }
+
+    /// Check connectivity and the health of the client.
+    pub fn diagnose(&self) -> DiagnosticReport {
+        self.runtime.block_on(self.inner.diagnose())
+    }
+
+    /// Get the client configuration.
+    pub fn config(&self) -> &WebullConfig {
+        self.inner.config()
+    }
+
+    /// Get the stored credentials.
+    pub fn get_credentials(&self) -> WebullResult<Option<Credentials>> {
+        self.inner.get_credentials()
+    }
+
+    /// Get the Webull region the client is configured for.
+    pub fn region(&self) -> Region {
+        self.inner.region()
+    }
+
+    /// Check if the client is configured for paper trading.
+    pub fn is_paper_trading(&self) -> bool {
+        self.inner.is_paper_trading()
+    }
+
+    /// Get a client for paper trading.
+    ///
+    /// The paper client shares this client's runtime.
+    pub fn paper_trading(&self) -> WebullResult<Self> {
+        Ok(Self {
+            inner: self.inner.paper_trading()?,
+            runtime: self.runtime.clone(),
+        })
+    }
+}
+
+/// Blocking account operations, mirroring [`AccountEndpoints`](endpoints::account::AccountEndpoints).
+pub struct AccountEndpoints {
+    inner: endpoints::account::AccountEndpoints,
+    runtime: Arc<Runtime>,
+}
+
+impl AccountEndpoints {
+    /// Get a list of accounts.
+    pub fn get_accounts(&self) -> WebullResult<Vec<Account>> {
+        self.runtime.block_on(self.inner.get_accounts())
+    }
+
+    /// Get account details.
+    pub fn get_account(&self, account_id: &str) -> WebullResult<Account> {
+        self.runtime.block_on(self.inner.get_account(account_id))
+    }
+
+    /// Get account balance.
+    pub fn get_account_balance(&self, account_id: &str) -> WebullResult<AccountBalance> {
+        self.runtime
+            .block_on(self.inner.get_account_balance(account_id))
+    }
+
+    /// Get account positions.
+    pub fn get_positions(&self, account_id: &str) -> WebullResult<Vec<Position>> {
+        self.runtime.block_on(self.inner.get_positions(account_id))
+    }
+
+    /// Get account position by symbol.
+    pub fn get_position(&self, account_id: &str, symbol: &str) -> WebullResult<Position> {
+        self.runtime
+            .block_on(self.inner.get_position(account_id, symbol))
+    }
+
+    /// Get account trade history.
+    pub fn get_trade_history(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>> {
+        self.runtime
+            .block_on(self.inner.get_trade_history(account_id))
+    }
+
+    /// Get account trade history with pagination.
+    pub fn get_trade_history_paged(
+        &self,
+        account_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> WebullResult<Vec<TradeHistory>> {
+        self.runtime.block_on(
+            self.inner
+                .get_trade_history_paged(account_id, page, page_size),
+        )
+    }
+
+    /// Get account profile information.
+    pub fn get_account_profile(&self, account_id: &str) -> WebullResult<AccountProfile> {
+        self.runtime
+            .block_on(self.inner.get_account_profile(account_id))
+    }
+
+    /// Get account balance with parameters.
+    pub fn get_balance(&self, params: &BalanceParams) -> WebullResult<AccountBalance> {
+        self.runtime.block_on(self.inner.get_balance(params))
+    }
+
+    /// Get account balance in the region's default currency.
+    pub fn get_balance_with_default_currency(
+        &self,
+        account_id: &str,
+    ) -> WebullResult<AccountBalance> {
+        self.runtime
+            .block_on(self.inner.get_balance_with_default_currency(account_id))
+    }
+
+    /// Get account positions with pagination.
+    pub fn get_positions_with_params(
+        &self,
+        params: &PositionParams,
+    ) -> WebullResult<Vec<Position>> {
+        self.runtime
+            .block_on(self.inner.get_positions_with_params(params))
+    }
+
+    /// Get a page of account positions after an instrument.
+    pub fn get_positions_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_instrument_id: Option<&str>,
+    ) -> WebullResult<Vec<Position>> {
+        self.runtime.block_on(self.inner.get_positions_paged(
+            account_id,
+            page_size,
+            last_instrument_id,
+        ))
+    }
+
+    /// Reset a paper trading account to a fresh starting balance.
+    pub fn reset_paper_account(
+        &self,
+        request: &PaperAccountResetRequest,
+    ) -> WebullResult<AccountBalance> {
+        self.runtime
+            .block_on(self.inner.reset_paper_account(request))
+    }
+
+    /// Add virtual funds to a paper trading account.
+    pub fn fund_paper_account(
+        &self,
+        request: &PaperFundingRequest,
+    ) -> WebullResult<AccountBalance> {
+        self.runtime
+            .block_on(self.inner.fund_paper_account(request))
+    }
+}
+
+/// Blocking order operations, mirroring [`OrderEndpoints`](endpoints::orders::OrderEndpoints).
+pub struct OrderEndpoints {
+    inner: endpoints::orders::OrderEndpoints,
+    runtime: Arc<Runtime>,
+}
+
+impl OrderEndpoints {
+    /// Place an order.
+    pub fn place_order(&self, order: &OrderRequest) -> WebullResult<OrderResponse> {
+        self.runtime.block_on(self.inner.place_order(order))
+    }
+
+    /// Cancel an order.
+    pub fn cancel_order(&self, order_id: &str) -> WebullResult<()> {
+        self.runtime.block_on(self.inner.cancel_order(order_id))
+    }
+
+    /// Get an order by ID.
+    pub fn get_order(&self, order_id: &str) -> WebullResult<Order> {
+        self.runtime.block_on(self.inner.get_order(order_id))
+    }
+
+    /// Get orders based on query parameters.
+    pub fn get_orders(&self, params: &OrderQueryParams) -> WebullResult<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_orders(params))
+    }
+
+    /// Get active orders.
+    pub fn get_active_orders(&self) -> WebullResult<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_active_orders())
+    }
+
+    /// Get filled orders.
+    pub fn get_filled_orders(&self) -> WebullResult<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_filled_orders())
+    }
+
+    /// Modify an existing order.
+    pub fn modify_order(
+        &self,
+        order_id: &str,
+        order: &OrderRequest,
+    ) -> WebullResult<OrderResponse> {
+        self.runtime
+            .block_on(self.inner.modify_order(order_id, order))
+    }
+
+    /// Get open orders for an account.
+    pub fn get_open_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        self.runtime
+            .block_on(self.inner.get_open_orders(account_id))
+    }
+
+    /// Get open orders for an account with pagination.
+    pub fn get_open_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_open_orders_paged(
+            account_id,
+            page_size,
+            last_order_id,
+        ))
+    }
+
+    /// Get today's orders for an account.
+    pub fn get_today_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        self.runtime
+            .block_on(self.inner.get_today_orders(account_id))
+    }
+
+    /// Get today's orders for an account with pagination.
+    pub fn get_today_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_today_orders_paged(
+            account_id,
+            page_size,
+            last_order_id,
+        ))
+    }
+
+    /// Preview an option order.
+    pub fn preview_option_order(
+        &self,
+        preview_request: &OptionOrderPreviewRequest,
+    ) -> WebullResult<OptionOrderPreviewResponse> {
+        self.runtime
+            .block_on(self.inner.preview_option_order(preview_request))
+    }
+
+    /// Place an option order.
+    pub fn place_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        self.runtime
+            .block_on(self.inner.place_option_order(account_id, orders))
+    }
+
+    /// Replace an option order.
+    pub fn replace_option_order(
+        &self,
+        account_id: &str,
+        orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        self.runtime
+            .block_on(self.inner.replace_option_order(account_id, orders))
+    }
+
+    /// Cancel an option order.
+    pub fn cancel_option_order(&self, account_id: &str, client_order_id: &str) -> WebullResult<()> {
+        self.runtime
+            .block_on(self.inner.cancel_option_order(account_id, client_order_id))
+    }
+}
+
+/// Blocking market data operations, mirroring [`MarketDataEndpoints`](endpoints::market_data::MarketDataEndpoints).
+pub struct MarketDataEndpoints {
+    inner: endpoints::market_data::MarketDataEndpoints,
+    runtime: Arc<Runtime>,
+}
+
+impl MarketDataEndpoints {
+    /// Get a real-time quote for a symbol.
+    pub fn get_quote(&self, symbol: &str) -> WebullResult<Quote> {
+        self.runtime.block_on(self.inner.get_quote(symbol))
+    }
+
+    /// Get real-time quotes for multiple symbols.
+    pub fn get_quotes(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>> {
+        self.runtime.block_on(self.inner.get_quotes(symbols))
+    }
+
+    /// Get snapshot data for symbols.
+    pub fn get_snapshot(&self, params: &SnapshotParams) -> WebullResult<Vec<Quote>> {
+        self.runtime.block_on(self.inner.get_snapshot(params))
+    }
+
+    /// Get a snapshot for a single stock symbol.
+    pub fn get_stock_snapshot(&self, symbol: &str) -> WebullResult<Vec<Quote>> {
+        self.runtime.block_on(self.inner.get_stock_snapshot(symbol))
+    }
+
+    /// Get snapshots for multiple stock symbols.
+    pub fn get_stock_snapshots(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>> {
+        self.runtime
+            .block_on(self.inner.get_stock_snapshots(symbols))
+    }
+
+    /// Get historical bar data for a symbol.
+    pub fn get_history_bar(&self, params: &BarQueryParams) -> WebullResult<Vec<Bar>> {
+        self.runtime.block_on(self.inner.get_history_bar(params))
+    }
+
+    /// Get option chain for a symbol.
+    pub fn get_option_chain(&self, params: &OptionChainQueryParams) -> WebullResult<OptionChain> {
+        self.runtime.block_on(self.inner.get_option_chain(params))
+    }
+
+    /// Get market news.
+    pub fn get_news(&self, params: &NewsQueryParams) -> WebullResult<Vec<NewsArticle>> {
+        self.runtime.block_on(self.inner.get_news(params))
+    }
+
+    /// Get the market calendar for the region's home market.
+    pub fn get_market_calendar(&self) -> WebullResult<Vec<String>> {
+        self.runtime.block_on(self.inner.get_market_calendar())
+    }
+
+    /// Get instrument information.
+    pub fn get_instrument(&self, params: &InstrumentParams) -> WebullResult<Vec<Instrument>> {
+        self.runtime.block_on(self.inner.get_instrument(params))
+    }
+
+    /// Get instrument information for a single stock symbol.
+    pub fn get_stock_instrument(&self, symbol: &str) -> WebullResult<Vec<Instrument>> {
+        self.runtime
+            .block_on(self.inner.get_stock_instrument(symbol))
+    }
+
+    /// Get instrument information for multiple stock symbols.
+    pub fn get_stock_instruments(&self, symbols: &[&str]) -> WebullResult<Vec<Instrument>> {
+        self.runtime
+            .block_on(self.inner.get_stock_instruments(symbols))
+    }
+
+    /// Get end-of-day bars for instruments.
+    pub fn get_eod_bar(&self, params: &EodBarsParams) -> WebullResult<Vec<Bar>> {
+        self.runtime.block_on(self.inner.get_eod_bar(params))
+    }
+
+    /// Get end-of-day bars for an instrument.
+    pub fn get_instrument_eod_bars(
+        &self,
+        instrument_id: &str,
+        count: u32,
+    ) -> WebullResult<Vec<Bar>> {
+        self.runtime
+            .block_on(self.inner.get_instrument_eod_bars(instrument_id, count))
+    }
+
+    /// Get end-of-day bars for an instrument up to a date.
+    pub fn get_instrument_eod_bars_with_date(
+        &self,
+        instrument_id: &str,
+        date: &str,
+        count: u32,
+    ) -> WebullResult<Vec<Bar>> {
+        self.runtime
+            .block_on(
+                self.inner
+                    .get_instrument_eod_bars_with_date(instrument_id, date, count),
+            )
+    }
+
+    /// Get corporate actions for instruments.
+    pub fn get_corp_action(&self, params: &CorpActionParams) -> WebullResult<Vec<Instrument>> {
+        self.runtime.block_on(self.inner.get_corp_action(params))
+    }
+
+    /// Get stock split corporate actions for an instrument.
+    pub fn get_stock_splits(&self, instrument_id: &str) -> WebullResult<Vec<Instrument>> {
+        self.runtime
+            .block_on(self.inner.get_stock_splits(instrument_id))
+    }
+
+    /// Get reverse stock split corporate actions for an instrument.
+    pub fn get_reverse_stock_splits(&self, instrument_id: &str) -> WebullResult<Vec<Instrument>> {
+        self.runtime
+            .block_on(self.inner.get_reverse_stock_splits(instrument_id))
+    }
+
+    /// Get all corporate actions for an instrument.
+    pub fn get_all_corp_actions(&self, instrument_id: &str) -> WebullResult<Vec<Instrument>> {
+        self.runtime
+            .block_on(self.inner.get_all_corp_actions(instrument_id))
+    }
+
+    /// Get daily bars for a symbol.
+    pub fn get_daily_bars(&self, symbol: &str, count: Option<u32>) -> WebullResult<Vec<Bar>> {
+        self.runtime
+            .block_on(self.inner.get_daily_bars(symbol, count))
+    }
+
+    /// Get intraday bars for a symbol.
+    pub fn get_intraday_bars(
+        &self,
+        symbol: &str,
+        time_frame: TimeFrame,
+        count: Option<u32>,
+    ) -> WebullResult<Vec<Bar>> {
+        self.runtime
+            .block_on(self.inner.get_intraday_bars(symbol, time_frame, count))
+    }
+}
+
+/// Blocking watchlist operations, mirroring [`WatchlistEndpoints`](endpoints::watchlists::WatchlistEndpoints).
+pub struct WatchlistEndpoints {
+    inner: endpoints::watchlists::WatchlistEndpoints,
+    runtime: Arc<Runtime>,
+}
+
+impl WatchlistEndpoints {
+    /// Get all watchlists.
+    pub fn get_watchlists(&self) -> WebullResult<Vec<Watchlist>> {
+        self.runtime.block_on(self.inner.get_watchlists())
+    }
+
+    /// Get a watchlist by ID.
+    pub fn get_watchlist(&self, watchlist_id: &str) -> WebullResult<Watchlist> {
+        self.runtime
+            .block_on(self.inner.get_watchlist(watchlist_id))
+    }
+
+    /// Create a new watchlist.
+    pub fn create_watchlist(&self, request: &CreateWatchlistRequest) -> WebullResult<Watchlist> {
+        self.runtime.block_on(self.inner.create_watchlist(request))
+    }
+
+    /// Modify a watchlist.
+    pub fn modify_watchlist(&self, request: &ModifyWatchlistRequest) -> WebullResult<Watchlist> {
+        self.runtime.block_on(self.inner.modify_watchlist(request))
+    }
+
+    /// Delete a watchlist.
+    pub fn delete_watchlist(&self, watchlist_id: &str) -> WebullResult<()> {
+        self.runtime
+            .block_on(self.inner.delete_watchlist(watchlist_id))
+    }
+
+    /// Add symbols to a watchlist.
+    pub fn add_symbols(&self, watchlist_id: &str, symbols: &[String]) -> WebullResult<Watchlist> {
+        self.runtime
+            .block_on(self.inner.add_symbols(watchlist_id, symbols))
+    }
+
+    /// Remove symbols from a watchlist.
+    pub fn remove_symbols(
+        &self,
+        watchlist_id: &str,
+        symbols: &[String],
+    ) -> WebullResult<Watchlist> {
+        self.runtime
+            .block_on(self.inner.remove_symbols(watchlist_id, symbols))
+    }
+
+    /// Rename a watchlist.
+    pub fn rename_watchlist(&self, watchlist_id: &str, name: &str) -> WebullResult<Watchlist> {
+        self.runtime
+            .block_on(self.inner.rename_watchlist(watchlist_id, name))
+    }
+}
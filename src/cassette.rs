@@ -0,0 +1,724 @@
+//! HTTP record and replay.
+//!
+//! A [`Cassette`] in record mode captures every request sent by the endpoint
+//! groups and the authentication calls, together with the response, and can
+//! save them to a JSON file. In replay mode it answers requests from such a
+//! file without touching the network, matching them by method, path,
+//! canonical query string and JSON body.
+//!
+//! Recordings are scrubbed when they are read or saved: tokens, passwords,
+//! secrets, device IDs and login names are replaced with [`REDACTED`], and
+//! account IDs and numbers with stable placeholders such as `ACCOUNT-1`,
+//! wherever they appear in paths, query strings or bodies. Account numbers
+//! are learned from every interaction first, so an ID is replaced even in
+//! requests made before the response that lists it. Sensitive response
+//! headers are dropped and request headers are never recorded.
+//!
+//! ```no_run
+//! # async fn example() -> webull_rs::WebullResult<()> {
+//! use std::sync::Arc;
+//! use webull_rs::cassette::Cassette;
+//! use webull_rs::WebullClient;
+//!
+//! // Capture real responses once...
+//! let cassette = Arc::new(Cassette::record("tests/cassettes/positions.json"));
+//! let client = WebullClient::builder()
+//!     .with_cassette(cassette.clone())
+//!     .build()?;
+//! client.login("username", "password").await?;
+//! let accounts = client.accounts().get_accounts().await?;
+//! client.accounts().get_positions(&accounts[0].id).await?;
+//! cassette.save()?;
+//!
+//! // ...then replay them offline
+//! let cassette = Arc::new(Cassette::replay("tests/cassettes/positions.json")?);
+//! let client = WebullClient::builder().with_cassette(cassette).build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`REDACTED`]: crate::telemetry::REDACTED
+
+use crate::error::{WebullError, WebullResult};
+use crate::middleware::HttpResponse;
+use crate::telemetry::{self, REDACTED};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Prefix of the placeholders that replace account IDs and numbers.
+const ACCOUNT_PLACEHOLDER: &str = "ACCOUNT-";
+
+/// Shortest account number that is replaced in paths and free text.
+const MIN_FREE_TEXT_ACCOUNT_LEN: usize = 4;
+
+/// Field name fragments whose values are always redacted.
+const SECRET_FIELDS: [&str; 8] = [
+    "token",
+    "password",
+    "secret",
+    "signature",
+    "deviceid",
+    "username",
+    "email",
+    "phone",
+];
+
+/// Whether a cassette records or replays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests over the network and record them
+    Record,
+
+    /// Answer requests from recorded interactions
+    Replay,
+}
+
+/// A recorded request, after scrubbing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CassetteRequest {
+    /// HTTP method
+    pub method: String,
+
+    /// API path
+    pub path: String,
+
+    /// Canonical query string, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// JSON request body, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl CassetteRequest {
+    /// Create a request.
+    pub fn new(
+        method: &Method,
+        path: impl Into<String>,
+        query: Option<String>,
+        body: Option<String>,
+    ) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.into(),
+            query,
+            body,
+        }
+    }
+
+    /// Check if another request has the same method, path, query parameters
+    /// and body.
+    ///
+    /// Query parameters are compared regardless of order, and JSON bodies
+    /// regardless of formatting and key order.
+    fn matches(&self, other: &CassetteRequest) -> bool {
+        self.method == other.method
+            && self.path == other.path
+            && canonical_query(self.query.as_deref()) == canonical_query(other.query.as_deref())
+            && canonical_body(self.body.as_deref()) == canonical_body(other.body.as_deref())
+    }
+}
+
+/// A recorded response, after scrubbing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CassetteResponse {
+    /// HTTP status
+    pub status: u16,
+
+    /// Response headers, without sensitive ones
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+
+    /// Response body
+    pub body: String,
+}
+
+impl CassetteResponse {
+    /// Convert to a response for the middleware chain.
+    fn to_http_response(&self) -> WebullResult<HttpResponse> {
+        let status = StatusCode::from_u16(self.status).map_err(|_| {
+            WebullError::InvalidRequest(format!("Invalid status {} in cassette", self.status))
+        })?;
+
+        let mut response = HttpResponse::new(status, self.body.clone());
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                response.headers.append(name, value);
+            }
+        }
+
+        Ok(response)
+    }
+}
+
+/// A request and the response it received.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request
+    pub request: CassetteRequest,
+
+    /// The response
+    pub response: CassetteResponse,
+}
+
+/// On-disk format of a cassette.
+#[derive(Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// Recorded HTTP interactions.
+///
+/// See the [module documentation](self) for an example.
+pub struct Cassette {
+    /// Whether the cassette records or replays
+    mode: CassetteMode,
+
+    /// File the cassette is saved to
+    path: Option<PathBuf>,
+
+    /// Interactions and scrubbing state
+    state: Mutex<CassetteState>,
+}
+
+/// Mutable state of a cassette.
+#[derive(Default)]
+struct CassetteState {
+    /// Interactions in order, as sent and received when recording and as
+    /// loaded when replaying
+    interactions: Vec<Interaction>,
+
+    /// Whether each interaction has been replayed
+    replayed: Vec<bool>,
+
+    /// Scrubber for secrets and account numbers in replayed requests
+    scrubber: Scrubber,
+}
+
+impl Cassette {
+    /// Create an empty cassette that records, to be saved to a file.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: CassetteMode::Record,
+            path: Some(path.into()),
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// Load a cassette file for replay.
+    pub fn replay(path: impl AsRef<Path>) -> WebullResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            WebullError::InvalidRequest(format!(
+                "Failed to read cassette {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let mut cassette = Self::from_json(&contents)?;
+        cassette.path = Some(path.to_path_buf());
+        Ok(cassette)
+    }
+
+    /// Parse a cassette for replay, e.g. one embedded with `include_str!`.
+    pub fn from_json(json: &str) -> WebullResult<Self> {
+        let file: CassetteFile = serde_json::from_str(json)?;
+        let replayed = vec![false; file.interactions.len()];
+
+        Ok(Self {
+            mode: CassetteMode::Replay,
+            path: None,
+            state: Mutex::new(CassetteState {
+                interactions: file.interactions,
+                replayed,
+                scrubber: Scrubber::default(),
+            }),
+        })
+    }
+
+    /// Also redact the values of a field, in addition to tokens, passwords,
+    /// secrets, device IDs and login names.
+    ///
+    /// Field names are compared ignoring case and separators, so
+    /// `trade_pin` also matches `tradePin`.
+    pub fn with_scrubbed_field(mut self, name: &str) -> Self {
+        let state = self.state.get_mut().unwrap();
+        state.scrubber.fields.push(normalize(name));
+        self
+    }
+
+    /// Get whether the cassette records or replays.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Get the interactions recorded or loaded so far, scrubbed.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        let state = self.state.lock().unwrap();
+        match self.mode {
+            CassetteMode::Record => state.scrubbed(),
+            CassetteMode::Replay => state.interactions.clone(),
+        }
+    }
+
+    /// Serialize the interactions as pretty-printed JSON.
+    pub fn to_json(&self) -> WebullResult<String> {
+        let file = CassetteFile {
+            interactions: self.interactions(),
+        };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Write the interactions to the cassette file.
+    pub fn save(&self) -> WebullResult<()> {
+        let path = self.path.as_ref().ok_or_else(|| {
+            WebullError::InvalidRequest("Cassette has no file to save to".to_string())
+        })?;
+
+        std::fs::write(path, self.to_json()?).map_err(|e| {
+            WebullError::InvalidRequest(format!(
+                "Failed to write cassette {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Send a request through the cassette.
+    ///
+    /// In record mode `send` is awaited and its response recorded; in replay
+    /// mode the recorded response is returned instead. Identical requests
+    /// are answered by their recordings in order, and by the last one once
+    /// all have been used.
+    pub(crate) async fn exchange(
+        &self,
+        request: CassetteRequest,
+        send: impl Future<Output = WebullResult<HttpResponse>>,
+    ) -> WebullResult<HttpResponse> {
+        match self.mode {
+            CassetteMode::Record => {
+                let response = send.await?;
+                self.state
+                    .lock()
+                    .unwrap()
+                    .record(request, CassetteResponse::from(&response));
+                Ok(response)
+            }
+            CassetteMode::Replay => {
+                let mut state = self.state.lock().unwrap();
+                let request = state.scrubber.scrub_request(request);
+                state.replay(&request)?.to_http_response()
+            }
+        }
+    }
+}
+
+impl CassetteState {
+    /// Store an interaction as it was sent and received.
+    fn record(&mut self, request: CassetteRequest, response: CassetteResponse) {
+        self.interactions.push(Interaction { request, response });
+        self.replayed.push(false);
+    }
+
+    /// Scrub the recorded interactions.
+    ///
+    /// Account numbers are learned from all of them before any is scrubbed,
+    /// so an ID is replaced everywhere, whichever interaction revealed it.
+    fn scrubbed(&self) -> Vec<Interaction> {
+        let mut scrubber = Scrubber {
+            fields: self.scrubber.fields.clone(),
+            ..Scrubber::default()
+        };
+        for interaction in &self.interactions {
+            scrubber.learn_request(&interaction.request);
+            scrubber.learn_body(Some(&interaction.response.body));
+        }
+
+        self.interactions
+            .iter()
+            .map(|interaction| Interaction {
+                request: scrubber.scrub_request(interaction.request.clone()),
+                response: CassetteResponse {
+                    body: scrubber.scrub_body(&interaction.response.body),
+                    ..interaction.response.clone()
+                },
+            })
+            .collect()
+    }
+
+    /// Find the recorded response for a scrubbed request.
+    fn replay(&mut self, request: &CassetteRequest) -> WebullResult<&CassetteResponse> {
+        let matching: Vec<usize> = self
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.request.matches(request))
+            .map(|(index, _)| index)
+            .collect();
+
+        let index = matching
+            .iter()
+            .copied()
+            .find(|index| !self.replayed[*index])
+            .or_else(|| matching.last().copied())
+            .ok_or_else(|| {
+                WebullError::InvalidRequest(format!(
+                    "No recorded response for {} {}",
+                    request.method, request.path
+                ))
+            })?;
+
+        self.replayed[index] = true;
+        Ok(&self.interactions[index].response)
+    }
+}
+
+impl From<&HttpResponse> for CassetteResponse {
+    fn from(response: &HttpResponse) -> Self {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| !telemetry::is_sensitive_header(name.as_str()))
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+
+        Self {
+            status: response.status.as_u16(),
+            headers,
+            body: response.body.clone(),
+        }
+    }
+}
+
+/// Kind of sensitive field.
+enum Sensitive {
+    /// Token, password or other secret, replaced with [`REDACTED`]
+    Secret,
+
+    /// Account ID or number, replaced with a placeholder
+    Account,
+}
+
+/// Replaces secrets and account numbers in recorded interactions.
+#[derive(Default)]
+struct Scrubber {
+    /// Extra normalized field names to redact
+    fields: Vec<String>,
+
+    /// Placeholders for the account numbers seen so far
+    accounts: HashMap<String, String>,
+}
+
+impl Scrubber {
+    /// Classify a JSON field or query parameter by name.
+    fn classify(&self, name: &str) -> Option<Sensitive> {
+        let name = normalize(name);
+        if name.contains("account")
+            && (name.ends_with("id") || name.ends_with("no") || name.contains("number"))
+        {
+            Some(Sensitive::Account)
+        } else if SECRET_FIELDS.iter().any(|field| name.contains(field))
+            || self.fields.contains(&name)
+        {
+            Some(Sensitive::Secret)
+        } else {
+            None
+        }
+    }
+
+    /// Get the placeholder for an account number, assigning one if needed.
+    fn placeholder(&mut self, account: &str) -> String {
+        if is_placeholder(account) {
+            return account.to_string();
+        }
+
+        let next = self.accounts.len() + 1;
+        self.accounts
+            .entry(account.to_string())
+            .or_insert_with(|| format!("{}{}", ACCOUNT_PLACEHOLDER, next))
+            .clone()
+    }
+
+    /// Assign placeholders to the account numbers in the path, query and body
+    /// of a request.
+    fn learn_request(&mut self, request: &CassetteRequest) {
+        if let Some(account) = telemetry::path_account_id(&request.path) {
+            self.placeholder(&account);
+        }
+
+        if let Some(query) = &request.query {
+            for (name, value) in url::form_urlencoded::parse(query.as_bytes()) {
+                if matches!(self.classify(&name), Some(Sensitive::Account)) {
+                    self.placeholder(&value);
+                }
+            }
+        }
+
+        self.learn_body(request.body.as_deref());
+    }
+
+    /// Assign placeholders to the account numbers in a JSON body.
+    fn learn_body(&mut self, body: Option<&str>) {
+        if let Some(Ok(value)) = body.map(serde_json::from_str::<Value>) {
+            self.learn(&value);
+        }
+    }
+
+    fn learn(&mut self, value: &Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    match value {
+                        Value::String(s) if self.is_account_field(map, key) => {
+                            self.placeholder(s);
+                        }
+                        Value::Number(n) if self.is_account_field(map, key) => {
+                            self.placeholder(&n.to_string());
+                        }
+                        value => self.learn(value),
+                    }
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| self.learn(value)),
+            _ => {}
+        }
+    }
+
+    /// Check if a field of a JSON object holds an account number.
+    ///
+    /// Besides fields named like one, the `id` of an object that has an
+    /// account number is taken to be an account ID.
+    fn is_account_field(&self, object: &Map<String, Value>, key: &str) -> bool {
+        let is_account = |key: &str| matches!(self.classify(key), Some(Sensitive::Account));
+        is_account(key) || (key == "id" && object.keys().any(|key| is_account(key)))
+    }
+
+    /// Scrub the path, query and body of a request.
+    fn scrub_request(&mut self, request: CassetteRequest) -> CassetteRequest {
+        self.learn_request(&request);
+
+        let query = request.query.as_deref().map(|query| {
+            let pairs: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .map(|(name, value)| {
+                    let value = match self.classify(&name) {
+                        Some(Sensitive::Secret) => REDACTED.to_string(),
+                        Some(Sensitive::Account) => self.placeholder(&value),
+                        None => self.replace_accounts(&value),
+                    };
+                    (name, value)
+                })
+                .collect();
+            url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs)
+                .finish()
+        });
+
+        CassetteRequest {
+            path: self.replace_accounts(&request.path),
+            query,
+            body: request.body.as_deref().map(|body| self.scrub_body(body)),
+            ..request
+        }
+    }
+
+    /// Scrub a body, which is left as it is apart from account numbers if it
+    /// is not JSON.
+    fn scrub_body(&self, body: &str) -> String {
+        match serde_json::from_str::<Value>(body) {
+            Ok(mut value) => {
+                self.scrub(&mut value);
+                value.to_string()
+            }
+            Err(_) => self.replace_accounts(body),
+        }
+    }
+
+    fn scrub(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                let accounts: Vec<String> = map
+                    .keys()
+                    .filter(|key| self.is_account_field(map, key))
+                    .cloned()
+                    .collect();
+                for (key, value) in map.iter_mut() {
+                    match value {
+                        Value::String(s) if accounts.contains(key) => {
+                            if let Some(placeholder) = self.accounts.get(s.as_str()) {
+                                *s = placeholder.clone();
+                            }
+                        }
+                        value @ Value::Number(_) if accounts.contains(key) => {
+                            if let Some(placeholder) = self.accounts.get(&value.to_string()) {
+                                *value = Value::String(placeholder.clone());
+                            }
+                        }
+                        value @ (Value::String(_) | Value::Number(_))
+                            if matches!(self.classify(key), Some(Sensitive::Secret)) =>
+                        {
+                            *value = Value::String(REDACTED.to_string());
+                        }
+                        value => self.scrub(value),
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.scrub(value)),
+            Value::String(s) => *s = self.replace_accounts(s),
+            Value::Number(n) => {
+                let number = n.to_string();
+                if number.len() >= MIN_FREE_TEXT_ACCOUNT_LEN {
+                    if let Some(placeholder) = self.accounts.get(&number) {
+                        *value = Value::String(placeholder.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace known account numbers in free text.
+    ///
+    /// Longer numbers are replaced first, and very short ones not at all, so
+    /// that unrelated text is left alone.
+    fn replace_accounts(&self, text: &str) -> String {
+        let mut accounts: Vec<(&String, &String)> = self
+            .accounts
+            .iter()
+            .filter(|(account, _)| account.len() >= MIN_FREE_TEXT_ACCOUNT_LEN)
+            .collect();
+        accounts.sort_by_key(|(account, _)| std::cmp::Reverse(account.len()));
+
+        accounts
+            .into_iter()
+            .fold(text.to_string(), |text, (account, placeholder)| {
+                text.replace(account.as_str(), placeholder)
+            })
+    }
+}
+
+/// Check if a value is already an account placeholder.
+fn is_placeholder(value: &str) -> bool {
+    value
+        .strip_prefix(ACCOUNT_PLACEHOLDER)
+        .is_some_and(|n| n.parse::<u32>().is_ok())
+}
+
+/// Lowercase a field name and drop separators.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Parse a query string into sorted pairs.
+fn canonical_query(query: Option<&str>) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = query
+        .map(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+    pairs.sort();
+    pairs
+}
+
+/// Parse a body as JSON, falling back to the raw text.
+fn canonical_body(body: Option<&str>) -> Option<Value> {
+    body.map(|body| serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::testing::FakeTransport;
+    use crate::WebullClient;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn cassettes_scrub_recordings_and_replay_them() {
+        let fake = FakeTransport::with_handler(|request| match request.url.path() {
+            "/api/passport/login/v5/account" => Some(HttpResponse::new(
+                StatusCode::OK,
+                r#"{"access_token":"secret-access","refresh_token":"secret-refresh","expires_in":3600}"#,
+            )),
+            "/api/account/getSecAccountList" => Some(HttpResponse::new(
+                StatusCode::OK,
+                r#"{"success":true,"data":[{"id":"5550001","account_number":"5550001","account_type":"MARGIN","status":"ACTIVE","created_at":"2024-01-02T00:00:00Z","currency":"USD","paper_trading":false,"secAccountId":5550002}]}"#,
+            )),
+            _ => None,
+        });
+
+        let cassette = Arc::new(Cassette::record("unused.json"));
+        let client = WebullClient::builder()
+            .with_transport(fake)
+            .with_cassette(cassette.clone())
+            .without_cache()
+            .build()
+            .unwrap();
+        client.login("user@example.com", "hunter2").await.unwrap();
+        // A known ID used before the account list has been fetched
+        client.accounts().get_positions("5550001").await.unwrap();
+        let accounts = client.accounts().get_accounts().await.unwrap();
+        client
+            .accounts()
+            .get_positions(&accounts[0].id)
+            .await
+            .unwrap();
+
+        let json = cassette.to_json().unwrap();
+        for secret in [
+            "secret-access",
+            "secret-refresh",
+            "hunter2",
+            "user@example.com",
+            "5550001",
+            "5550002",
+        ] {
+            assert!(!json.contains(secret), "{} leaked into {}", secret, json);
+        }
+        assert!(json.contains("/api/position/getUserPositions/ACCOUNT-1"));
+        assert!(json.contains(r#"\"secAccountId\":\"ACCOUNT-2\""#));
+
+        // Every response is replayed, so nothing reaches the transport
+        let replay_transport = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(replay_transport.clone())
+            .with_cassette(Arc::new(Cassette::from_json(&json).unwrap()))
+            .without_cache()
+            .build()
+            .unwrap();
+        client
+            .login("someone-else", "other-password")
+            .await
+            .unwrap();
+        client.accounts().get_positions("ACCOUNT-1").await.unwrap();
+        let accounts = client.accounts().get_accounts().await.unwrap();
+        assert_eq!(accounts[0].id, "ACCOUNT-1");
+        let positions = client
+            .accounts()
+            .get_positions(&accounts[0].id)
+            .await
+            .unwrap();
+        assert!(positions.is_empty());
+
+        let err = client.market_data().get_quote("AAPL").await.unwrap_err();
+        assert!(err.to_string().contains("No recorded response"));
+        assert!(replay_transport.requests().is_empty());
+    }
+}
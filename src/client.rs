@@ -1,82 +1,237 @@
 use crate::auth::{AuthManager, MemoryTokenStore, TokenStore};
-use crate::config::WebullConfig;
+use crate::cassette::Cassette;
+use crate::config::{
+    CacheConfig, CircuitBreakerConfig, ClockSkewConfig, RateLimitConfig, StreamingConfig,
+    WebullConfig,
+};
+use crate::diagnostics::{self, DiagnosticReport};
+use crate::endpoints::base::BaseEndpoint;
 use crate::endpoints::{
     account::AccountEndpoints, market_data::MarketDataEndpoints, orders::OrderEndpoints,
     watchlists::WatchlistEndpoints,
 };
 use crate::error::{WebullError, WebullResult};
-use crate::streaming::client::WebSocketClient;
+use crate::middleware::{Middleware, SingleFlightLayer};
+use crate::region::Region;
+use crate::streaming::client::{StreamMonitor, StreamStatus, WebSocketClient};
+use crate::telemetry::{Metrics, NoopMetrics};
+use crate::transport::{HttpTransport, ReqwestTransport};
+use crate::utils::cache::CacheManager;
+use crate::utils::circuit_breaker::CircuitBreaker;
+use crate::utils::clock::Clock;
+use crate::utils::credential_provider::{CredentialProviderChain, ResolvedCredentials};
 use crate::utils::credentials::{CredentialStore, MemoryCredentialStore};
-use std::sync::Arc;
+use crate::utils::failover::Failover;
+use crate::utils::rate_limit::RateLimiter;
+use crate::utils::retry::RetryPolicy;
+use crate::utils::secret::SecretString;
+use chrono::{TimeDelta, Utc};
+use reqwest::Method;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 use uuid::Uuid;
 
 /// Builder for creating a WebullClient.
 pub struct WebullClientBuilder {
-    api_key: Option<String>,
-    api_secret: Option<String>,
-    device_id: Option<String>,
-    timeout: Duration,
-    base_url: String,
-    paper_trading: bool,
+    config: WebullConfig,
     token_store: Option<Box<dyn TokenStore>>,
     credential_store: Option<Box<dyn CredentialStore>>,
+    credential_provider: Option<CredentialProviderChain>,
+    retry_policy: RetryPolicy,
+    middleware: Vec<Arc<dyn Middleware>>,
+    metrics: Arc<dyn Metrics>,
+    cassette: Option<Arc<Cassette>>,
+    transport: Option<Arc<dyn HttpTransport>>,
 }
 
 impl WebullClientBuilder {
     /// Create a new builder with default values.
     pub fn new() -> Self {
+        Self::from_config(WebullConfig::default())
+    }
+
+    /// Create a new builder starting from an existing configuration.
+    ///
+    /// Use this with [`WebullConfig::from_env`], [`WebullConfig::from_file`] or
+    /// [`WebullConfig::profile`] to build a client from loaded settings.
+    pub fn from_config(config: WebullConfig) -> Self {
         Self {
-            api_key: None,
-            api_secret: None,
-            device_id: None,
-            timeout: Duration::from_secs(30),
-            base_url: "https://api.webull.com".to_string(),
-            paper_trading: false,
+            config,
             token_store: None,
             credential_store: None,
+            credential_provider: None,
+            retry_policy: RetryPolicy::default(),
+            middleware: Vec::new(),
+            metrics: Arc::new(NoopMetrics),
+            cassette: None,
+            transport: None,
         }
     }
 
     /// Set the API key.
     pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
-        self.api_key = Some(api_key.into());
+        self.config.api_key = Some(api_key.into());
         self
     }
 
     /// Set the API secret.
-    pub fn with_api_secret(mut self, api_secret: impl Into<String>) -> Self {
-        self.api_secret = Some(api_secret.into());
+    pub fn with_api_secret(mut self, api_secret: impl Into<SecretString>) -> Self {
+        self.config.api_secret = Some(api_secret.into());
         self
     }
 
     /// Set the device ID.
     pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
-        self.device_id = Some(device_id.into());
+        self.config.device_id = Some(device_id.into());
         self
     }
 
     /// Set the timeout for API requests.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = timeout;
+        self.config.timeout = timeout;
         self
     }
 
     /// Set a custom base URL.
     pub fn with_custom_url(mut self, url: impl Into<String>) -> Self {
-        self.base_url = url.into();
+        self.config.base_url = url.into();
+        self
+    }
+
+    /// Set base URLs to fail over to, in order, when the base URL is
+    /// unavailable, e.g. other regional gateways.
+    ///
+    /// See [`Failover`] for when requests move to another host.
+    pub fn with_fallback_urls<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.fallback_urls = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the Webull region.
+    ///
+    /// This also switches the base URL to the region's default host, so call
+    /// [`WebullClientBuilder::with_custom_url`] afterwards to override it.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.config.set_region(region);
         self
     }
 
     /// Set whether to use paper trading.
     pub fn with_paper_trading(mut self, paper_trading: bool) -> Self {
-        self.paper_trading = paper_trading;
+        self.config.paper_trading = paper_trading;
         self
     }
 
     /// Enable paper trading.
     pub fn paper_trading(mut self) -> Self {
-        self.paper_trading = true;
+        self.config.paper_trading = true;
+        self
+    }
+
+    /// Set the maximum number of requests per minute shared by all endpoints.
+    pub fn with_rate_limit(mut self, requests_per_minute: u32) -> Self {
+        self.config.rate_limit.requests_per_minute = requests_per_minute;
+        self
+    }
+
+    /// Set the rate limiting settings.
+    pub fn with_rate_limit_config(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.config.rate_limit = rate_limit;
+        self
+    }
+
+    /// Set the policy for retrying failed requests.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Add a middleware layer to every API request.
+    ///
+    /// Layers run in the order they are added, after the built-in cache,
+    /// retry, rate limit and authentication layers, once per attempt.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Report request, authentication and streaming metrics to a sink.
+    ///
+    /// See [`crate::telemetry`] for the metrics reported.
+    pub fn with_metrics(mut self, metrics: impl Metrics + 'static) -> Self {
+        self.metrics = Arc::new(metrics);
+        self
+    }
+
+    /// Send requests with a preconfigured HTTP client, e.g. one with a proxy
+    /// or custom TLS roots.
+    ///
+    /// The configured timeout is not applied to the client.
+    pub fn with_http_client(self, client: reqwest::Client) -> Self {
+        self.with_transport(ReqwestTransport::new(client))
+    }
+
+    /// Send requests through a custom transport.
+    ///
+    /// See [`crate::transport`] for an example. The configured timeout is not
+    /// applied to the transport.
+    pub fn with_transport(mut self, transport: impl HttpTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Record API and authentication requests to, or replay them from, a
+    /// cassette.
+    ///
+    /// See [`crate::cassette`] for how recordings are scrubbed and matched.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Set the circuit breaker settings.
+    pub fn with_circuit_breaker_config(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.config.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Disable the circuit breaker.
+    pub fn without_circuit_breaker(mut self) -> Self {
+        self.config.circuit_breaker.enabled = false;
+        self
+    }
+
+    /// Set the clock skew settings.
+    pub fn with_clock_skew_config(mut self, clock_skew: ClockSkewConfig) -> Self {
+        self.config.clock_skew = clock_skew;
+        self
+    }
+
+    /// Set the default time-to-live for cached responses.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.config.cache.default_ttl = ttl;
+        self
+    }
+
+    /// Set the response cache settings.
+    pub fn with_cache_config(mut self, cache: CacheConfig) -> Self {
+        self.config.cache = cache;
+        self
+    }
+
+    /// Disable response caching.
+    pub fn without_cache(mut self) -> Self {
+        self.config.cache.enabled = false;
+        self
+    }
+
+    /// Set the streaming settings.
+    pub fn with_streaming_config(mut self, streaming: StreamingConfig) -> Self {
+        self.config.streaming = streaming;
         self
     }
 
@@ -92,28 +247,31 @@ impl WebullClientBuilder {
         self
     }
 
+    /// Set a credential provider chain.
+    ///
+    /// The chain is only resolved by [`WebullClient::login_with_provider`] and
+    /// [`WebullClient::resolve_credentials`], never when the client is built.
+    pub fn with_credential_provider(mut self, provider: CredentialProviderChain) -> Self {
+        self.credential_provider = Some(provider);
+        self
+    }
+
     /// Build the WebullClient.
     pub fn build(self) -> WebullResult<WebullClient> {
+        let mut config = self.config;
+
         // Generate a random device ID if not provided
-        let device_id = self
-            .device_id
-            .unwrap_or_else(|| Uuid::new_v4().to_hyphenated().to_string());
-
-        // Create the configuration
-        let config = WebullConfig {
-            api_key: self.api_key,
-            api_secret: self.api_secret,
-            device_id: Some(device_id),
-            timeout: self.timeout,
-            base_url: self.base_url,
-            paper_trading: self.paper_trading,
-        };
+        if config.device_id.is_none() {
+            config.device_id = Some(Uuid::new_v4().to_hyphenated().to_string());
+        }
 
-        // Create the HTTP client
-        let client = reqwest::Client::builder()
-            .timeout(config.timeout)
-            .build()
-            .map_err(|e| WebullError::NetworkError(e))?;
+        config.validate()?;
+
+        // Create the HTTP transport
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => Arc::new(ReqwestTransport::with_timeout(config.timeout)?),
+        };
 
         // Create the token store
         let token_store = self
@@ -125,26 +283,93 @@ impl WebullClientBuilder {
             .credential_store
             .unwrap_or_else(|| Box::new(MemoryCredentialStore::default()));
 
+        // Create the failover list, if there are hosts to fail over to
+        let failover = if config.fallback_urls.is_empty() {
+            None
+        } else {
+            let failover = Failover::new(
+                &config.base_url,
+                &config.fallback_urls,
+                config.circuit_breaker.open_duration,
+            )?;
+            Some(Arc::new(failover.with_metrics(self.metrics.clone())))
+        };
+
+        // Create the clock that request timestamps are taken from
+        let clock =
+            Arc::new(Clock::from_config(&config.clock_skew).with_metrics(self.metrics.clone()));
+
+        // Create the rate limiter shared by all endpoints and authentication calls
+        let rate_limiter = Arc::new(RateLimiter::from_config(&config.rate_limit));
+
         // Create the auth manager
-        let auth_manager = Arc::new(AuthManager::new(
+        let auth_manager = Arc::new(new_auth_manager(
             config.clone(),
             token_store,
-            client.clone(),
+            transport.clone(),
+            &self.metrics,
+            &self.cassette,
+            &failover,
+            &clock,
+            &rate_limiter,
         ));
 
-        Ok(WebullClient {
-            inner: client,
+        // Create the cache and circuit breaker shared by all endpoints
+        let cache_manager = Arc::new(CacheManager::from_config(&config.cache));
+        let circuit_breaker = config.circuit_breaker.enabled.then(|| {
+            Arc::new(
+                CircuitBreaker::from_config(&config.circuit_breaker)
+                    .with_metrics(self.metrics.clone()),
+            )
+        });
+
+        Ok(WebullClient::from_parts(ClientParts {
+            transport,
             config,
             auth_manager,
+            rate_limiter,
+            cache_manager,
+            circuit_breaker,
+            failover,
+            retry_policy: Arc::new(self.retry_policy),
+            middleware: self.middleware,
+            metrics: self.metrics,
+            cassette: self.cassette,
             credential_store: Arc::new(credential_store),
-        })
+            credential_provider: self.credential_provider.map(Arc::new),
+        }))
+    }
+
+    /// Build a blocking client.
+    ///
+    /// See [`crate::blocking`]. Requires the `blocking` feature.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> WebullResult<crate::blocking::WebullClient> {
+        crate::blocking::WebullClient::new(self.build()?)
+    }
+}
+
+impl Default for WebullClientBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 /// Client for interacting with the Webull API.
+///
+/// The client is a handle to shared state, so it is cheap to clone and can be
+/// stored in application state and used from many tasks at once. Clones share
+/// the HTTP transport, authentication state, rate limiter and response cache;
+/// logging in through one clone logs in all of them.
+#[derive(Clone)]
 pub struct WebullClient {
-    /// HTTP client
-    inner: reqwest::Client,
+    inner: Arc<ClientInner>,
+}
+
+/// State shared by all clones of a client.
+struct ClientInner {
+    /// HTTP transport
+    transport: Arc<dyn HttpTransport>,
 
     /// Configuration
     config: WebullConfig,
@@ -152,8 +377,46 @@ pub struct WebullClient {
     /// Authentication manager
     auth_manager: Arc<AuthManager>,
 
+    /// Base endpoint shared by all endpoint groups
+    base: BaseEndpoint,
+
+    /// Custom middleware layers
+    middleware: Vec<Arc<dyn Middleware>>,
+
+    /// Metrics sink
+    metrics: Arc<dyn Metrics>,
+
+    /// Cassette that records or replays requests
+    cassette: Option<Arc<Cassette>>,
+
     /// Credential store
     credential_store: Arc<Box<dyn CredentialStore>>,
+
+    /// Credential provider chain
+    credential_provider: Option<Arc<CredentialProviderChain>>,
+
+    /// Paper trading client, created on first use
+    paper: OnceLock<WebullClient>,
+
+    /// Streaming clients created by this client, for diagnostics
+    streams: Mutex<Vec<StreamMonitor>>,
+}
+
+/// Everything needed to assemble a client.
+struct ClientParts {
+    transport: Arc<dyn HttpTransport>,
+    config: WebullConfig,
+    auth_manager: Arc<AuthManager>,
+    rate_limiter: Arc<RateLimiter>,
+    cache_manager: Arc<CacheManager>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    failover: Option<Arc<Failover>>,
+    retry_policy: Arc<RetryPolicy>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    metrics: Arc<dyn Metrics>,
+    cassette: Option<Arc<Cassette>>,
+    credential_store: Arc<Box<dyn CredentialStore>>,
+    credential_provider: Option<Arc<CredentialProviderChain>>,
 }
 
 impl WebullClient {
@@ -162,178 +425,458 @@ impl WebullClient {
         WebullClientBuilder::new()
     }
 
-    /// Login to Webull.
-    pub async fn login(&self, username: &str, password: &str) -> WebullResult<()> {
-        // Create a new AuthManager with the same configuration
-        let mut auth_manager = AuthManager::new(
-            self.config.clone(),
-            Box::new(MemoryTokenStore::default()),
-            self.inner.clone(),
-        );
+    /// Assemble a client, building the base endpoint its endpoint groups share.
+    fn from_parts(parts: ClientParts) -> Self {
+        let base = BaseEndpoint::new(
+            parts.transport.clone(),
+            parts.config.base_url.clone(),
+            parts.auth_manager.clone(),
+        )
+        .with_rate_limiter(parts.rate_limiter)
+        .with_cache_manager(parts.cache_manager)
+        .with_retry_policy(parts.retry_policy)
+        .with_single_flight(Arc::new(SingleFlightLayer::new()))
+        .with_metrics(parts.metrics.clone())
+        .with_region(parts.config.region)
+        .with_paper_trading(parts.config.paper_trading);
+        let base = match &parts.cassette {
+            Some(cassette) => base.with_cassette(cassette.clone()),
+            None => base,
+        };
+        let base = match parts.circuit_breaker {
+            Some(circuit_breaker) => base.with_circuit_breaker(circuit_breaker),
+            None => base,
+        };
+        let base = match parts.failover {
+            Some(failover) => base.with_failover(failover),
+            None => base,
+        };
+        let base = parts.middleware.iter().fold(base, |base, middleware| {
+            base.with_middleware(middleware.clone())
+        });
 
-        // Authenticate
-        let token = auth_manager.authenticate(username, password).await?;
+        Self {
+            inner: Arc::new(ClientInner {
+                transport: parts.transport,
+                config: parts.config,
+                auth_manager: parts.auth_manager,
+                base,
+                middleware: parts.middleware,
+                metrics: parts.metrics,
+                cassette: parts.cassette,
+                credential_store: parts.credential_store,
+                credential_provider: parts.credential_provider,
+                paper: OnceLock::new(),
+                streams: Mutex::new(Vec::new()),
+            }),
+        }
+    }
 
-        // Store the token in the original auth_manager
-        let token_store = self.auth_manager.token_store.as_ref();
-        token_store.store_token(token)?;
+    /// Login to Webull.
+    pub async fn login(&self, username: &str, password: &str) -> WebullResult<()> {
+        self.inner
+            .auth_manager
+            .authenticate(username, password)
+            .await?;
 
         // Store the credentials
-        let credentials = crate::auth::Credentials {
-            username: username.to_string(),
-            password: password.to_string(),
-        };
-        self.credential_store.store_credentials(credentials)?;
+        let credentials = crate::auth::Credentials::new(username, password);
+        self.inner.credential_store.store_credentials(credentials)?;
+
+        // Cache keys do not include the user, so drop responses cached for a
+        // previous login
+        self.cache_manager().clear_all();
 
         Ok(())
     }
 
-    /// Logout from Webull.
-    pub async fn logout(&self) -> WebullResult<()> {
-        // Create a new AuthManager with the same configuration
-        let mut auth_manager = AuthManager::new(
-            self.config.clone(),
-            Box::new(MemoryTokenStore::default()),
-            self.inner.clone(),
-        );
-
-        // Get the current token from the original auth_manager
-        let token = match self.auth_manager.token_store.get_token()? {
-            Some(token) => token,
-            None => {
-                // No token to revoke
-                return Ok(());
-            }
-        };
+    /// Complete a login that requires multi-factor authentication.
+    pub async fn verify_mfa(&self, mfa_code: &str) -> WebullResult<()> {
+        self.inner.auth_manager.multi_factor_auth(mfa_code).await?;
+        self.cache_manager().clear_all();
+        Ok(())
+    }
 
-        // Store the token in the new auth_manager
-        auth_manager.token_store.store_token(token)?;
+    /// Resolve credentials from the configured provider chain.
+    pub fn resolve_credentials(&self) -> WebullResult<ResolvedCredentials> {
+        match &self.inner.credential_provider {
+            Some(provider) => provider.resolve(),
+            None => Err(WebullError::InvalidRequest(
+                "No credential provider configured".to_string(),
+            )),
+        }
+    }
 
-        // Revoke the token
-        auth_manager.revoke_token().await?;
+    /// Login to Webull with a username and password resolved from the provider chain.
+    ///
+    /// An API key and secret resolved by the chain are used if none were
+    /// configured.
+    ///
+    /// Providers such as credential helper commands block, so the chain is
+    /// resolved on the blocking thread pool.
+    pub async fn login_with_provider(&self) -> WebullResult<()> {
+        let client = self.clone();
+        let resolved = tokio::task::spawn_blocking(move || client.resolve_credentials())
+            .await
+            .map_err(|e| {
+                WebullError::InvalidRequest(format!("Credential providers panicked: {}", e))
+            })??;
+        let credentials = resolved.credentials().ok_or_else(|| {
+            WebullError::InvalidRequest(
+                "Credential providers did not supply a username and password".to_string(),
+            )
+        })?;
+        self.inner
+            .auth_manager
+            .fill_api_credentials(resolved.api_key, resolved.api_secret);
+
+        self.login(&credentials.username, credentials.password.expose_secret())
+            .await
+    }
 
-        // Clear the token in the original auth_manager
-        self.auth_manager.token_store.clear_token()?;
+    /// Logout from Webull.
+    pub async fn logout(&self) -> WebullResult<()> {
+        // Revoke the token, if there is one
+        self.inner.auth_manager.revoke_token().await?;
 
         // Clear the credentials
-        self.credential_store.clear_credentials()?;
+        self.inner.credential_store.clear_credentials()?;
+
+        // Clear responses cached for this login
+        self.cache_manager().clear_all();
 
         Ok(())
     }
 
     /// Refresh the authentication token.
     pub async fn refresh_token(&self) -> WebullResult<()> {
-        // Create a new AuthManager with the same configuration
-        let mut auth_manager = AuthManager::new(
-            self.config.clone(),
-            Box::new(MemoryTokenStore::default()),
-            self.inner.clone(),
-        );
-
-        // Get the current token from the original auth_manager
-        let token = match self.auth_manager.token_store.get_token()? {
-            Some(token) => token,
-            None => {
-                return Err(WebullError::InvalidRequest(
-                    "No token available for refresh".to_string(),
-                ));
-            }
-        };
-
-        // Store the token in the new auth_manager
-        auth_manager.token_store.store_token(token)?;
-
-        // Refresh the token
-        let new_token = auth_manager.refresh_token().await?;
-
-        // Store the new token in the original auth_manager
-        self.auth_manager.token_store.store_token(new_token)?;
-
+        self.inner.auth_manager.refresh_token().await?;
         Ok(())
     }
 
     /// Get account endpoints.
     pub fn accounts(&self) -> AccountEndpoints {
-        AccountEndpoints::new(
-            self.inner.clone(),
-            self.config.base_url.clone(),
-            self.auth_manager.clone(),
-        )
+        AccountEndpoints::from_base(self.inner.base.clone())
     }
 
     /// Get market data endpoints.
     pub fn market_data(&self) -> MarketDataEndpoints {
-        MarketDataEndpoints::new(
-            self.inner.clone(),
-            self.config.base_url.clone(),
-            self.auth_manager.clone(),
-        )
+        MarketDataEndpoints::from_base(self.inner.base.clone())
     }
 
     /// Get order endpoints.
     pub fn orders(&self) -> OrderEndpoints {
-        OrderEndpoints::new(
-            self.inner.clone(),
-            self.config.base_url.clone(),
-            self.auth_manager.clone(),
-        )
+        OrderEndpoints::from_base(self.inner.base.clone())
     }
 
     /// Get watchlist endpoints.
     pub fn watchlists(&self) -> WatchlistEndpoints {
-        WatchlistEndpoints::new(
-            self.inner.clone(),
-            self.config.base_url.clone(),
-            self.auth_manager.clone(),
-        )
+        WatchlistEndpoints::from_base(self.inner.base.clone())
+    }
+
+    /// Get the authentication manager shared by all endpoints.
+    pub fn auth_manager(&self) -> &Arc<AuthManager> {
+        &self.inner.auth_manager
+    }
+
+    /// Get the rate limiter shared by all endpoints.
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        self.inner.base.rate_limiter()
+    }
+
+    /// Get the response cache shared by all endpoints.
+    pub fn cache_manager(&self) -> &Arc<CacheManager> {
+        self.inner.base.cache_manager()
+    }
+
+    /// Get the retry policy shared by all endpoints.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        self.inner.base.retry_policy()
+    }
+
+    /// Get the circuit breaker shared by all endpoints, if enabled.
+    pub fn circuit_breaker(&self) -> Option<&Arc<CircuitBreaker>> {
+        self.inner.base.circuit_breaker()
+    }
+
+    /// Get the hosts requests fail over across, if there are fallback base URLs.
+    pub fn failover(&self) -> Option<&Arc<Failover>> {
+        self.inner.base.failover()
+    }
+
+    /// Get the clock that request timestamps are taken from.
+    ///
+    /// [`Clock::skew`] is the skew between the local and server clocks, as
+    /// last measured from a response.
+    pub fn clock(&self) -> &Arc<Clock> {
+        self.inner.auth_manager.clock()
+    }
+
+    /// Measure the skew between the local and server clocks now.
+    ///
+    /// Sends an unauthenticated request to the base URL and reads the `Date`
+    /// header of the response, whatever its status. The skew is also measured
+    /// from every other response, so this is only needed to correct the
+    /// clock before the first login.
+    pub async fn sync_clock(&self) -> WebullResult<TimeDelta> {
+        let request = self.inner.base.request(Method::GET, "/");
+        let sent_at = Utc::now();
+        let response = self.inner.transport.send(request).await?;
+        self.clock()
+            .observe(&response.headers, sent_at, Utc::now())
+            .ok_or_else(|| {
+                WebullError::Unknown("The server response has no valid Date header".to_string())
+            })
     }
 
     /// Create a WebSocket client for streaming data.
     pub fn streaming(&self) -> WebSocketClient {
-        let ws_base_url = self.config.base_url.clone().replace("http", "ws");
-        WebSocketClient::new(ws_base_url, self.auth_manager.clone())
+        let config = &self.inner.config;
+        let stream = WebSocketClient::new(self.streaming_url(), self.inner.auth_manager.clone())
+            .with_config(&config.streaming)
+            .with_metrics(self.inner.metrics.clone());
+
+        let mut streams = self.inner.streams.lock().unwrap();
+        streams.retain(|monitor| monitor.status().is_some());
+        streams.push(stream.monitor());
+        stream
+    }
+
+    /// Get the base URL of streaming connections.
+    ///
+    /// See [`WebullConfig::streaming_url`].
+    pub fn streaming_url(&self) -> String {
+        self.inner.config.streaming_url()
+    }
+
+    /// Get the status of every streaming client created by this client that
+    /// is still alive.
+    pub fn streams(&self) -> Vec<StreamStatus> {
+        self.inner
+            .streams
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(StreamMonitor::status)
+            .collect()
+    }
+
+    /// Check connectivity and the health of the client.
+    ///
+    /// Resolves and sends a request to every REST and streaming host, and
+    /// collects the state of the login, trade token, clock, rate limits,
+    /// circuit breakers, caches and streaming connections. Problems are
+    /// reported in the returned [`DiagnosticReport`] rather than as errors.
+    pub async fn diagnose(&self) -> DiagnosticReport {
+        diagnostics::diagnose(self).await
+    }
+
+    /// Get the HTTP transport.
+    pub(crate) fn transport(&self) -> &Arc<dyn HttpTransport> {
+        &self.inner.transport
+    }
+
+    /// Get the base endpoint shared by all endpoint groups.
+    pub(crate) fn base(&self) -> &BaseEndpoint {
+        &self.inner.base
+    }
+
+    /// Get the client configuration.
+    pub fn config(&self) -> &WebullConfig {
+        &self.inner.config
     }
 
     /// Get the stored credentials.
     pub fn get_credentials(&self) -> WebullResult<Option<crate::auth::Credentials>> {
-        self.credential_store.get_credentials()
+        self.inner.credential_store.get_credentials()
     }
 
     /// Get the credential store.
     pub fn credential_store(&self) -> &Arc<Box<dyn CredentialStore>> {
-        &self.credential_store
+        &self.inner.credential_store
+    }
+
+    /// Get the Webull region the client is configured for.
+    pub fn region(&self) -> Region {
+        self.inner.config.region
     }
 
     /// Check if the client is configured for paper trading.
     pub fn is_paper_trading(&self) -> bool {
-        self.config.paper_trading
+        self.inner.config.paper_trading
     }
 
-    /// Create a new client for paper trading.
+    /// Get a client for paper trading.
+    ///
+    /// The paper client is created on first use and then shared, so every
+    /// call returns a handle to the same paper login. A client that is
+    /// already in paper trading mode returns itself.
     pub fn paper_trading(&self) -> WebullResult<Self> {
-        let mut config = self.config.clone();
+        if self.is_paper_trading() {
+            return Ok(self.clone());
+        }
+        if let Some(paper) = self.inner.paper.get() {
+            return Ok(paper.clone());
+        }
+
+        let mut config = self.inner.config.clone();
         config.paper_trading = true;
+        config.validate()?;
+
+        // Paper trading uses its own login and in-memory cache, since logging
+        // in or out clears the cache, but shares the HTTP transport, rate
+        // limiter, disk cache, circuit breaker, failover and clock with the
+        // live client
+        let failover = self.failover().cloned();
+        let auth_manager = Arc::new(new_auth_manager(
+            config.clone(),
+            Box::new(MemoryTokenStore::default()),
+            self.inner.transport.clone(),
+            &self.inner.metrics,
+            &self.inner.cassette,
+            &failover,
+            self.clock(),
+            self.rate_limiter(),
+        ));
+        let paper = Self::from_parts(ClientParts {
+            transport: self.inner.transport.clone(),
+            config,
+            auth_manager,
+            rate_limiter: self.rate_limiter().clone(),
+            cache_manager: Arc::new(self.cache_manager().sibling()),
+            circuit_breaker: self.circuit_breaker().cloned(),
+            failover,
+            retry_policy: self.inner.base.retry_policy().clone(),
+            middleware: self.inner.middleware.clone(),
+            metrics: self.inner.metrics.clone(),
+            cassette: self.inner.cassette.clone(),
+            credential_store: Arc::new(Box::new(MemoryCredentialStore::default())),
+            credential_provider: self.inner.credential_provider.clone(),
+        });
+
+        // Another task may have created the paper client first
+        Ok(self.inner.paper.get_or_init(|| paper).clone())
+    }
+}
 
-        // Create a new client with the same settings but for paper trading
-        let client = reqwest::ClientBuilder::new()
-            .timeout(config.timeout)
-            .build()
-            .map_err(|e| WebullError::NetworkError(e))?;
+/// Create an authentication manager that reports to the client's metrics
+/// sink and cassette, fails over across the client's hosts, takes request
+/// timestamps from the client's clock and budget from its rate limiter.
+#[allow(clippy::too_many_arguments)]
+fn new_auth_manager(
+    config: WebullConfig,
+    token_store: Box<dyn TokenStore>,
+    transport: Arc<dyn HttpTransport>,
+    metrics: &Arc<dyn Metrics>,
+    cassette: &Option<Arc<Cassette>>,
+    failover: &Option<Arc<Failover>>,
+    clock: &Arc<Clock>,
+    rate_limiter: &Arc<RateLimiter>,
+) -> AuthManager {
+    let auth_manager = AuthManager::new(config, token_store, transport)
+        .with_metrics(metrics.clone())
+        .with_clock(clock.clone())
+        .with_rate_limiter(rate_limiter.clone());
+    let auth_manager = match cassette {
+        Some(cassette) => auth_manager.with_cassette(cassette.clone()),
+        None => auth_manager,
+    };
+    match failover {
+        Some(failover) => auth_manager.with_failover(failover.clone()),
+        None => auth_manager,
+    }
+}
 
-        let token_store = Box::new(MemoryTokenStore::default());
-        let credential_store = Box::new(MemoryCredentialStore::default());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::testing::FakeTransport;
 
-        let auth_manager = Arc::new(AuthManager::new(
-            config.clone(),
-            token_store,
-            client.clone(),
+    #[tokio::test]
+    async fn client_clones_share_one_login() {
+        fn assert_handle<T: Clone + Send + Sync + 'static>() {}
+        assert_handle::<WebullClient>();
+
+        let client = WebullClient::builder()
+            .with_transport(FakeTransport::new())
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        clone.login("user", "password").await.unwrap();
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.accounts().get_accounts().await })
+            })
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap().unwrap().is_empty());
+        }
+
+        let paper = client.paper_trading().unwrap();
+        assert!(Arc::ptr_eq(
+            paper.auth_manager(),
+            clone.paper_trading().unwrap().auth_manager()
         ));
+        assert!(!Arc::ptr_eq(paper.auth_manager(), client.auth_manager()));
+        assert!(paper.auth_manager().get_token().await.is_err());
 
-        Ok(Self {
-            inner: client,
-            config,
-            auth_manager,
-            credential_store: Arc::new(credential_store),
-        })
+        client.logout().await.unwrap();
+        assert!(clone.auth_manager().get_token().await.is_err());
+    }
+
+    #[test]
+    fn clones_share_rate_limiter_and_cache() {
+        let client = WebullClient::builder()
+            .with_transport(FakeTransport::new())
+            .with_rate_limit(120)
+            .build()
+            .unwrap();
+        let clone = client.clone();
+
+        assert!(Arc::ptr_eq(client.cache_manager(), clone.cache_manager()));
+        assert!(Arc::ptr_eq(client.rate_limiter(), clone.rate_limiter()));
+        assert_eq!(clone.rate_limiter().requests_per_minute(), 120);
+    }
+
+    #[tokio::test]
+    async fn paper_logins_leave_the_live_cache_alone() {
+        let fake = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+        client.accounts().get_accounts().await.unwrap();
+
+        let paper = client.paper_trading().unwrap();
+        assert!(!Arc::ptr_eq(paper.cache_manager(), client.cache_manager()));
+        paper.login("user", "password").await.unwrap();
+        paper.logout().await.unwrap();
+
+        client.accounts().get_accounts().await.unwrap();
+        let listed = fake
+            .requests()
+            .iter()
+            .filter(|request| request.ends_with("getSecAccountList"))
+            .count();
+        assert_eq!(listed, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn credential_provider_is_only_resolved_at_login() {
+        use crate::utils::credential_provider::CommandCredentialProvider;
+
+        let failing = CredentialProviderChain::new()
+            .with_provider(CommandCredentialProvider::new("cat >/dev/null; exit 1;"));
+        let client = WebullClient::builder()
+            .with_credential_provider(failing)
+            .build()
+            .unwrap();
+
+        let error = client.login_with_provider().await.unwrap_err();
+        assert!(error.to_string().contains("failed"));
     }
 }
@@ -1,5 +1,14 @@
+use crate::error::{WebullError, WebullResult};
+use crate::region::{Capability, Region};
+use crate::utils::credential_provider::DEFAULT_ENV_PREFIX;
+use crate::utils::secret::SecretString;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+/// Names of the built-in configuration profiles.
+pub const BUILTIN_PROFILES: &[&str] = &["live-us", "paper-us", "live-hk", "jp"];
+
 /// Configuration for the Webull API client.
 #[derive(Debug, Clone)]
 pub struct WebullConfig {
@@ -7,7 +16,7 @@ pub struct WebullConfig {
     pub api_key: Option<String>,
 
     /// API secret for authentication
-    pub api_secret: Option<String>,
+    pub api_secret: Option<SecretString>,
 
     /// Device ID for authentication
     pub device_id: Option<String>,
@@ -18,8 +27,197 @@ pub struct WebullConfig {
     /// Base URL for API requests
     pub base_url: String,
 
+    /// Base URLs to fail over to, in order, when the base URL is unavailable
+    pub fallback_urls: Vec<String>,
+
+    /// Webull region
+    pub region: Region,
+
     /// Whether to use paper trading
     pub paper_trading: bool,
+
+    /// Rate limiting settings
+    pub rate_limit: RateLimitConfig,
+
+    /// Response cache settings
+    pub cache: CacheConfig,
+
+    /// Circuit breaker settings
+    pub circuit_breaker: CircuitBreakerConfig,
+
+    /// Clock skew settings
+    pub clock_skew: ClockSkewConfig,
+
+    /// Streaming settings
+    pub streaming: StreamingConfig,
+}
+
+/// Rate limiting settings.
+///
+/// By default every budget allows 60 requests per minute, so only the global
+/// cap limits a client until a category is tightened explicitly or by rate
+/// limit response headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Maximum number of requests per minute across all endpoints
+    pub requests_per_minute: u32,
+
+    /// Maximum number of trading requests per minute
+    pub trade_per_minute: u32,
+
+    /// Maximum number of market data requests per minute
+    pub quote_per_minute: u32,
+
+    /// Maximum number of account requests per minute
+    pub account_per_minute: u32,
+
+    /// Maximum number of authentication requests per minute
+    pub auth_per_minute: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 60,
+            trade_per_minute: 60,
+            quote_per_minute: 60,
+            account_per_minute: 60,
+            auth_per_minute: 60,
+        }
+    }
+}
+
+/// Response cache settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Whether responses are cached
+    pub enabled: bool,
+
+    /// Default time-to-live for cached responses
+    pub default_ttl: Duration,
+
+    /// Maximum number of entries per cache
+    pub max_entries: usize,
+
+    /// Directory of the disk tier that keeps past end-of-day bars and
+    /// corporate actions, and instrument metadata, across restarts; `None`
+    /// disables it
+    pub disk_dir: Option<PathBuf>,
+
+    /// How long files are kept in the disk tier
+    pub disk_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_ttl: Duration::from_secs(60),
+            max_entries: 1000,
+            disk_dir: None,
+            disk_ttl: Duration::from_secs(30 * 24 * 3600),
+        }
+    }
+}
+
+/// Circuit breaker settings.
+///
+/// Each endpoint category has its own circuit. A circuit opens when at least
+/// `failure_rate_percent` of the requests in a window fail with a network
+/// error, a timeout or a server error, provided there were at least
+/// `minimum_requests` of them. While open, requests in the category fail
+/// immediately. After `open_duration` the circuit is half-open and lets
+/// `half_open_requests` trial requests through; it closes if they all succeed
+/// and opens again if any fails. Hosts that fail are also skipped for
+/// `open_duration` when failing over between base URLs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Whether circuits can open
+    pub enabled: bool,
+
+    /// Percentage of failed requests in a window that opens the circuit
+    pub failure_rate_percent: u32,
+
+    /// Minimum number of requests in a window before the circuit can open
+    pub minimum_requests: u32,
+
+    /// Length of the window that failures are counted over
+    pub window: Duration,
+
+    /// How long the circuit stays open before trial requests are let through
+    pub open_duration: Duration,
+
+    /// Number of trial requests that must succeed to close the circuit
+    pub half_open_requests: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_rate_percent: 50,
+            minimum_requests: 10,
+            window: Duration::from_secs(60),
+            open_duration: Duration::from_secs(30),
+            half_open_requests: 1,
+        }
+    }
+}
+
+/// Clock skew settings.
+///
+/// Signed requests carry a timestamp that the server checks against its own
+/// clock. The skew between the local and server clocks is measured from the
+/// `Date` header of every response, and by [`crate::WebullClient::sync_clock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockSkewConfig {
+    /// Whether the measured skew is added to request timestamps
+    pub compensate: bool,
+
+    /// Skew above which a warning is logged
+    pub warn_threshold: Duration,
+
+    /// Skew above which signed requests fail with [`WebullError::ClockSkew`]
+    /// instead of being sent
+    pub max_skew: Option<Duration>,
+}
+
+impl Default for ClockSkewConfig {
+    fn default() -> Self {
+        Self {
+            compensate: true,
+            warn_threshold: Duration::from_secs(5),
+            max_skew: None,
+        }
+    }
+}
+
+/// Streaming settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamingConfig {
+    /// WebSocket URL; the region's streaming host when not set, or derived
+    /// from the base URL when that is not the region's default host
+    pub url: Option<String>,
+
+    /// Interval between heartbeats
+    pub heartbeat_interval: Duration,
+
+    /// Delay between reconnect attempts
+    pub reconnect_delay: Duration,
+
+    /// Maximum number of reconnect attempts
+    pub max_reconnect_attempts: u32,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            heartbeat_interval: Duration::from_secs(30),
+            reconnect_delay: Duration::from_secs(5),
+            max_reconnect_attempts: 5,
+        }
+    }
 }
 
 impl Default for WebullConfig {
@@ -29,8 +227,15 @@ impl Default for WebullConfig {
             api_secret: None,
             device_id: None,
             timeout: Duration::from_secs(30),
-            base_url: "https://api.webull.com".to_string(),
+            base_url: Region::Us.base_url().to_string(),
+            fallback_urls: Vec::new(),
+            region: Region::Us,
             paper_trading: false,
+            rate_limit: RateLimitConfig::default(),
+            cache: CacheConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            clock_skew: ClockSkewConfig::default(),
+            streaming: StreamingConfig::default(),
         }
     }
 }
@@ -40,4 +245,642 @@ impl WebullConfig {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Create a configuration for a region, using the region's default host.
+    pub fn for_region(region: Region) -> Self {
+        let mut config = Self::default();
+        config.set_region(region);
+        config
+    }
+
+    /// Set the region and switch the base URL to the region's default host.
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+        self.base_url = region.base_url().to_string();
+    }
+
+    /// Get the URL of streaming connections.
+    ///
+    /// This is the configured streaming URL if there is one, the region's
+    /// streaming host if the base URL is the region's default host, and
+    /// otherwise the base URL with its scheme switched to `ws` or `wss`.
+    pub fn streaming_url(&self) -> String {
+        if let Some(url) = &self.streaming.url {
+            return url.clone();
+        }
+        if self.base_url == self.region.base_url() {
+            return self.region.streaming_url().to_string();
+        }
+
+        match url::Url::parse(&self.base_url) {
+            Ok(mut url) => {
+                let scheme = if url.scheme() == "http" { "ws" } else { "wss" };
+                // Both schemes are special, so switching between them cannot fail
+                let _ = url.set_scheme(scheme);
+                url.as_str().trim_end_matches('/').to_string()
+            }
+            Err(_) => self.region.streaming_url().to_string(),
+        }
+    }
+
+    /// Create a configuration from a built-in profile.
+    ///
+    /// The built-in profiles are listed in [`BUILTIN_PROFILES`].
+    pub fn profile(name: &str) -> WebullResult<Self> {
+        let config = match name {
+            "live-us" => Self::for_region(Region::Us),
+            "paper-us" => {
+                let mut config = Self::for_region(Region::Us);
+                config.paper_trading = true;
+                config
+            }
+            "live-hk" => Self::for_region(Region::Hk),
+            "jp" => Self::for_region(Region::Jp),
+            _ => {
+                return Err(WebullError::InvalidConfig {
+                    key: "profile".to_string(),
+                    message: format!(
+                        "unknown profile '{}' (expected one of {} or a profile defined in a config file)",
+                        name,
+                        BUILTIN_PROFILES.join(", ")
+                    ),
+                })
+            }
+        };
+
+        Ok(config)
+    }
+
+    /// Create a configuration from environment variables.
+    ///
+    /// `WEBULL_PROFILE` selects a built-in profile to start from. The other
+    /// recognised variables are `WEBULL_REGION` (`US`, `HK` or `JP`), `WEBULL_BASE_URL`,
+    /// `WEBULL_FALLBACK_URLS` (comma-separated), `WEBULL_TIMEOUT` (seconds),
+    /// `WEBULL_PAPER_TRADING`, `WEBULL_DEVICE_ID`, `WEBULL_API_KEY`,
+    /// `WEBULL_API_SECRET`, `WEBULL_RATE_LIMIT` (requests per minute),
+    /// `WEBULL_RATE_LIMIT_TRADE`, `WEBULL_RATE_LIMIT_QUOTE`,
+    /// `WEBULL_RATE_LIMIT_ACCOUNT`, `WEBULL_RATE_LIMIT_AUTH`,
+    /// `WEBULL_CACHE_ENABLED`, `WEBULL_CACHE_TTL` (seconds),
+    /// `WEBULL_CACHE_MAX_ENTRIES`, `WEBULL_CACHE_DISK_DIR`,
+    /// `WEBULL_CACHE_DISK_TTL` (seconds), `WEBULL_CIRCUIT_BREAKER_ENABLED`,
+    /// `WEBULL_CIRCUIT_BREAKER_FAILURE_RATE` (percent),
+    /// `WEBULL_CIRCUIT_BREAKER_MINIMUM_REQUESTS`,
+    /// `WEBULL_CIRCUIT_BREAKER_WINDOW` (seconds),
+    /// `WEBULL_CIRCUIT_BREAKER_OPEN_DURATION` (seconds),
+    /// `WEBULL_CIRCUIT_BREAKER_HALF_OPEN_REQUESTS`,
+    /// `WEBULL_CLOCK_SKEW_COMPENSATE`, `WEBULL_CLOCK_SKEW_WARN_THRESHOLD`
+    /// (seconds), `WEBULL_CLOCK_SKEW_MAX` (seconds), `WEBULL_STREAMING_URL`,
+    /// `WEBULL_STREAMING_HEARTBEAT_INTERVAL` (seconds),
+    /// `WEBULL_STREAMING_RECONNECT_DELAY` (seconds) and
+    /// `WEBULL_STREAMING_MAX_RECONNECT_ATTEMPTS`.
+    pub fn from_env() -> WebullResult<Self> {
+        let mut config = match std::env::var(format!("{}PROFILE", DEFAULT_ENV_PREFIX)) {
+            Ok(profile) if !profile.is_empty() => Self::profile(&profile)?,
+            _ => Self::default(),
+        };
+
+        const VARS: &[(&str, &str)] = &[
+            ("REGION", "region"),
+            ("BASE_URL", "base_url"),
+            ("FALLBACK_URLS", "fallback_urls"),
+            ("TIMEOUT", "timeout"),
+            ("PAPER_TRADING", "paper_trading"),
+            ("DEVICE_ID", "device_id"),
+            ("API_KEY", "api_key"),
+            ("API_SECRET", "api_secret"),
+            ("RATE_LIMIT", "rate_limit.requests_per_minute"),
+            ("RATE_LIMIT_TRADE", "rate_limit.trade_per_minute"),
+            ("RATE_LIMIT_QUOTE", "rate_limit.quote_per_minute"),
+            ("RATE_LIMIT_ACCOUNT", "rate_limit.account_per_minute"),
+            ("RATE_LIMIT_AUTH", "rate_limit.auth_per_minute"),
+            ("CACHE_ENABLED", "cache.enabled"),
+            ("CACHE_TTL", "cache.ttl"),
+            ("CACHE_MAX_ENTRIES", "cache.max_entries"),
+            ("CACHE_DISK_DIR", "cache.disk_dir"),
+            ("CACHE_DISK_TTL", "cache.disk_ttl"),
+            ("CIRCUIT_BREAKER_ENABLED", "circuit_breaker.enabled"),
+            (
+                "CIRCUIT_BREAKER_FAILURE_RATE",
+                "circuit_breaker.failure_rate_percent",
+            ),
+            (
+                "CIRCUIT_BREAKER_MINIMUM_REQUESTS",
+                "circuit_breaker.minimum_requests",
+            ),
+            ("CIRCUIT_BREAKER_WINDOW", "circuit_breaker.window"),
+            (
+                "CIRCUIT_BREAKER_OPEN_DURATION",
+                "circuit_breaker.open_duration",
+            ),
+            (
+                "CIRCUIT_BREAKER_HALF_OPEN_REQUESTS",
+                "circuit_breaker.half_open_requests",
+            ),
+            ("CLOCK_SKEW_COMPENSATE", "clock_skew.compensate"),
+            ("CLOCK_SKEW_WARN_THRESHOLD", "clock_skew.warn_threshold"),
+            ("CLOCK_SKEW_MAX", "clock_skew.max_skew"),
+            ("STREAMING_URL", "streaming.url"),
+            (
+                "STREAMING_HEARTBEAT_INTERVAL",
+                "streaming.heartbeat_interval",
+            ),
+            ("STREAMING_RECONNECT_DELAY", "streaming.reconnect_delay"),
+            (
+                "STREAMING_MAX_RECONNECT_ATTEMPTS",
+                "streaming.max_reconnect_attempts",
+            ),
+        ];
+
+        for (suffix, key) in VARS {
+            let var = format!("{}{}", DEFAULT_ENV_PREFIX, suffix);
+            if let Ok(raw) = std::env::var(&var) {
+                config.apply_env(key, &var, &raw)?;
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load a configuration from a TOML or JSON file.
+    ///
+    /// Top-level keys apply to every profile. If the file has a top-level
+    /// `profile` key, that profile is applied on top; see
+    /// [`WebullConfig::from_file_with_profile`].
+    pub fn from_file(path: impl AsRef<Path>) -> WebullResult<Self> {
+        let root = load_file(path.as_ref())?;
+        let profile = match root.get("profile") {
+            Some(Value::String(profile)) => Some(profile.clone()),
+            Some(_) => return Err(invalid("profile", "expected a string")),
+            None => None,
+        };
+
+        Self::from_table(&root, profile.as_deref())
+    }
+
+    /// Load a named profile from a TOML or JSON file.
+    ///
+    /// Settings are layered in order: the built-in profile of the same name
+    /// (if any), the file's top-level keys, then the `[profiles.<name>]` table.
+    /// Files ending in `.json` are parsed as JSON; everything else is parsed as TOML.
+    /// Credentials (`username` and `password`) are only allowed
+    /// in profiles, where a [`ProfileCredentialProvider`] for the same file
+    /// reads them, and are an error at the top level.
+    ///
+    /// [`ProfileCredentialProvider`]: crate::utils::credential_provider::ProfileCredentialProvider
+    ///
+    /// ```toml
+    /// timeout = 15
+    ///
+    /// [profiles.paper-us]
+    /// paper_trading = true
+    ///
+    /// [profiles.paper-us.rate_limit]
+    /// requests_per_minute = 120
+    /// ```
+    pub fn from_file_with_profile(path: impl AsRef<Path>, profile: &str) -> WebullResult<Self> {
+        let root = load_file(path.as_ref())?;
+        Self::from_table(&root, Some(profile))
+    }
+
+    /// Build a configuration from a parsed file.
+    fn from_table(root: &Map<String, Value>, profile: Option<&str>) -> WebullResult<Self> {
+        let profiles = match root.get("profiles") {
+            Some(Value::Object(profiles)) => Some(profiles),
+            Some(_) => return Err(invalid("profiles", "expected a table")),
+            None => None,
+        };
+
+        let profile_table = match profile {
+            Some(name) => match profiles.and_then(|profiles| profiles.get(name)) {
+                Some(Value::Object(table)) => Some((name, table)),
+                Some(_) => return Err(invalid(&format!("profiles.{}", name), "expected a table")),
+                None if BUILTIN_PROFILES.contains(&name) => None,
+                None => {
+                    return Err(invalid(
+                        "profile",
+                        &format!("profile '{}' is not defined", name),
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        let mut config = match profile {
+            Some(name) if BUILTIN_PROFILES.contains(&name) => Self::profile(name)?,
+            _ => Self::default(),
+        };
+
+        config.apply_table(root, "")?;
+        if let Some((name, table)) = profile_table {
+            config.apply_table(table, &format!("profiles.{}.", name))?;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Apply the keys of a configuration table.
+    fn apply_table(&mut self, table: &Map<String, Value>, prefix: &str) -> WebullResult<()> {
+        // The region resets the base URL, so it is applied before any explicit `base_url`
+        if let Some(region) = table.get("region") {
+            self.apply_value("region", &format!("{}region", prefix), region)?;
+        }
+
+        for (key, value) in table {
+            let path = format!("{}{}", prefix, key);
+            match key.as_str() {
+                // Handled by the caller or above
+                "profile" | "profiles" | "region" => {}
+                // Credentials in a profile are read by `ProfileCredentialProvider`,
+                // which only looks at `profiles.<name>`
+                "username" | "password" if !prefix.is_empty() => {}
+                "username" | "password" => {
+                    return Err(invalid(
+                        &path,
+                        "credentials must be set under `profiles.<name>` to be read by ProfileCredentialProvider",
+                    ))
+                }
+                "rate_limit" | "cache" | "circuit_breaker" | "clock_skew" | "streaming" => {
+                    let nested = value
+                        .as_object()
+                        .ok_or_else(|| invalid(&path, "expected a table"))?;
+                    for (nested_key, nested_value) in nested {
+                        self.apply_value(
+                            &format!("{}.{}", key, nested_key),
+                            &format!("{}.{}", path, nested_key),
+                            nested_value,
+                        )?;
+                    }
+                }
+                _ => self.apply_value(key, &path, value)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply a single setting from an environment variable.
+    fn apply_env(&mut self, key: &str, var: &str, raw: &str) -> WebullResult<()> {
+        let value = match key {
+            "region" | "base_url" | "fallback_urls" | "device_id" | "api_key" | "api_secret"
+            | "cache.disk_dir" | "streaming.url" => Value::String(raw.to_string()),
+            "paper_trading"
+            | "cache.enabled"
+            | "circuit_breaker.enabled"
+            | "clock_skew.compensate" => match raw.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => Value::Bool(true),
+                "0" | "false" | "no" | "off" => Value::Bool(false),
+                _ => return Err(invalid(var, "expected a boolean")),
+            },
+            _ => serde_json::from_str::<serde_json::Number>(raw)
+                .map(Value::Number)
+                .map_err(|_| invalid(var, "expected a number"))?,
+        };
+
+        self.apply_value(key, var, &value)
+    }
+
+    /// Apply a single setting; `path` names the setting in error messages.
+    fn apply_value(&mut self, key: &str, path: &str, value: &Value) -> WebullResult<()> {
+        match key {
+            "region" => {
+                let region = as_string(path, value)?
+                    .parse()
+                    .map_err(|e: String| invalid(path, &e))?;
+                self.set_region(region);
+            }
+            "base_url" => self.base_url = as_string(path, value)?,
+            "fallback_urls" => self.fallback_urls = as_url_list(path, value)?,
+            "timeout" => self.timeout = as_duration(path, value)?,
+            "paper_trading" => self.paper_trading = as_bool(path, value)?,
+            "device_id" => self.device_id = Some(as_string(path, value)?),
+            "api_key" => self.api_key = Some(as_string(path, value)?),
+            "api_secret" => self.api_secret = Some(as_string(path, value)?.into()),
+            "rate_limit.requests_per_minute" => {
+                self.rate_limit.requests_per_minute = as_u32(path, value)?
+            }
+            "rate_limit.trade_per_minute" => {
+                self.rate_limit.trade_per_minute = as_u32(path, value)?
+            }
+            "rate_limit.quote_per_minute" => {
+                self.rate_limit.quote_per_minute = as_u32(path, value)?
+            }
+            "rate_limit.account_per_minute" => {
+                self.rate_limit.account_per_minute = as_u32(path, value)?
+            }
+            "rate_limit.auth_per_minute" => self.rate_limit.auth_per_minute = as_u32(path, value)?,
+            "cache.enabled" => self.cache.enabled = as_bool(path, value)?,
+            "cache.ttl" => self.cache.default_ttl = as_duration(path, value)?,
+            "cache.max_entries" => self.cache.max_entries = as_u32(path, value)? as usize,
+            "cache.disk_dir" => self.cache.disk_dir = Some(as_string(path, value)?.into()),
+            "cache.disk_ttl" => self.cache.disk_ttl = as_duration(path, value)?,
+            "circuit_breaker.enabled" => self.circuit_breaker.enabled = as_bool(path, value)?,
+            "circuit_breaker.failure_rate_percent" => {
+                self.circuit_breaker.failure_rate_percent = as_u32(path, value)?
+            }
+            "circuit_breaker.minimum_requests" => {
+                self.circuit_breaker.minimum_requests = as_u32(path, value)?
+            }
+            "circuit_breaker.window" => self.circuit_breaker.window = as_duration(path, value)?,
+            "circuit_breaker.open_duration" => {
+                self.circuit_breaker.open_duration = as_duration(path, value)?
+            }
+            "circuit_breaker.half_open_requests" => {
+                self.circuit_breaker.half_open_requests = as_u32(path, value)?
+            }
+            "clock_skew.compensate" => self.clock_skew.compensate = as_bool(path, value)?,
+            "clock_skew.warn_threshold" => {
+                self.clock_skew.warn_threshold = as_duration(path, value)?
+            }
+            "clock_skew.max_skew" => self.clock_skew.max_skew = Some(as_duration(path, value)?),
+            "streaming.url" => self.streaming.url = Some(as_string(path, value)?),
+            "streaming.heartbeat_interval" => {
+                self.streaming.heartbeat_interval = as_duration(path, value)?
+            }
+            "streaming.reconnect_delay" => {
+                self.streaming.reconnect_delay = as_duration(path, value)?
+            }
+            "streaming.max_reconnect_attempts" => {
+                self.streaming.max_reconnect_attempts = as_u32(path, value)?
+            }
+            _ => return Err(invalid(path, "unknown configuration key")),
+        }
+
+        Ok(())
+    }
+
+    /// Validate the configuration.
+    pub fn validate(&self) -> WebullResult<()> {
+        validate_http_url("base_url", &self.base_url)?;
+        for url in &self.fallback_urls {
+            validate_http_url("fallback_urls", url)?;
+        }
+
+        if let Some(streaming_url) = &self.streaming.url {
+            let url = url::Url::parse(streaming_url)
+                .map_err(|e| invalid("streaming.url", &format!("invalid URL: {}", e)))?;
+            if url.scheme() != "ws" && url.scheme() != "wss" {
+                return Err(invalid("streaming.url", "expected a ws or wss URL"));
+            }
+        }
+
+        if self.paper_trading && !self.region.supports(Capability::PaperTrading) {
+            return Err(invalid(
+                "paper_trading",
+                &format!(
+                    "paper trading is not available in the {} region",
+                    self.region
+                ),
+            ));
+        }
+
+        if self.timeout.is_zero() {
+            return Err(invalid("timeout", "must be greater than zero"));
+        }
+
+        let rate_limits = [
+            ("requests_per_minute", self.rate_limit.requests_per_minute),
+            ("trade_per_minute", self.rate_limit.trade_per_minute),
+            ("quote_per_minute", self.rate_limit.quote_per_minute),
+            ("account_per_minute", self.rate_limit.account_per_minute),
+            ("auth_per_minute", self.rate_limit.auth_per_minute),
+        ];
+        for (key, limit) in rate_limits {
+            if limit == 0 {
+                return Err(invalid(
+                    &format!("rate_limit.{}", key),
+                    "must be greater than zero",
+                ));
+            }
+        }
+
+        if self.cache.max_entries == 0 {
+            return Err(invalid("cache.max_entries", "must be greater than zero"));
+        }
+
+        let circuit_breaker = &self.circuit_breaker;
+        if !(1..=100).contains(&circuit_breaker.failure_rate_percent) {
+            return Err(invalid(
+                "circuit_breaker.failure_rate_percent",
+                "must be between 1 and 100",
+            ));
+        }
+        let circuit_breaker_minimums = [
+            ("minimum_requests", circuit_breaker.minimum_requests),
+            ("half_open_requests", circuit_breaker.half_open_requests),
+        ];
+        for (key, minimum) in circuit_breaker_minimums {
+            if minimum == 0 {
+                return Err(invalid(
+                    &format!("circuit_breaker.{}", key),
+                    "must be greater than zero",
+                ));
+            }
+        }
+        if circuit_breaker.window.is_zero() {
+            return Err(invalid(
+                "circuit_breaker.window",
+                "must be greater than zero",
+            ));
+        }
+
+        if self.streaming.heartbeat_interval.is_zero() {
+            return Err(invalid(
+                "streaming.heartbeat_interval",
+                "must be greater than zero",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Read and parse a configuration file into a table.
+fn load_file(path: &Path) -> WebullResult<Map<String, Value>> {
+    let file_error = |message: String| invalid("file", &format!("{}: {}", path.display(), message));
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| file_error(format!("failed to read config file: {}", e)))?;
+
+    let is_json = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let value: Value = if is_json {
+        serde_json::from_str(&contents).map_err(|e| file_error(format!("invalid JSON: {}", e)))?
+    } else {
+        toml::from_str(&contents).map_err(|e| file_error(format!("invalid TOML: {}", e)))?
+    };
+
+    match value {
+        Value::Object(table) => Ok(table),
+        _ => Err(file_error("expected a table at the top level".to_string())),
+    }
+}
+
+/// Check that a setting is an http or https URL.
+fn validate_http_url(key: &str, url: &str) -> WebullResult<()> {
+    let parsed = url::Url::parse(url).map_err(|e| invalid(key, &format!("invalid URL: {}", e)))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(invalid(key, "expected an http or https URL"));
+    }
+    Ok(())
+}
+
+/// Create a configuration error for a key.
+fn invalid(key: &str, message: &str) -> WebullError {
+    WebullError::InvalidConfig {
+        key: key.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn as_string(key: &str, value: &Value) -> WebullResult<String> {
+    value
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| invalid(key, "expected a string"))
+}
+
+fn as_bool(key: &str, value: &Value) -> WebullResult<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| invalid(key, "expected a boolean"))
+}
+
+fn as_u32(key: &str, value: &Value) -> WebullResult<u32> {
+    value
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .ok_or_else(|| invalid(key, "expected a non-negative integer"))
+}
+
+/// Parse a list of URLs given as an array or a comma-separated string.
+fn as_url_list(key: &str, value: &Value) -> WebullResult<Vec<String>> {
+    match value {
+        Value::String(list) => Ok(list
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect()),
+        Value::Array(urls) => urls.iter().map(|url| as_string(key, url)).collect(),
+        _ => Err(invalid(key, "expected a list of URLs")),
+    }
+}
+
+/// Parse a duration given in seconds.
+fn as_duration(key: &str, value: &Value) -> WebullResult<Duration> {
+    let secs = value
+        .as_f64()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .ok_or_else(|| invalid(key, "expected a non-negative number of seconds"))?;
+    Duration::try_from_secs_f64(secs).map_err(|_| invalid(key, "number of seconds is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Map<String, Value> {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn profile_overrides_top_level_keys() {
+        let root = parse(
+            r#"
+            timeout = 10
+
+            [profiles.paper-us]
+            timeout = 5
+
+            [profiles.paper-us.cache]
+            ttl = 2.5
+            disk_dir = "/var/cache/webull"
+            "#,
+        );
+
+        let config = WebullConfig::from_table(&root, Some("paper-us")).unwrap();
+        assert!(config.paper_trading);
+        assert_eq!(config.timeout, Duration::from_secs(5));
+        assert_eq!(config.cache.default_ttl, Duration::from_millis(2500));
+        assert_eq!(
+            config.cache.disk_dir.as_deref(),
+            Some(Path::new("/var/cache/webull"))
+        );
+    }
+
+    #[test]
+    fn out_of_range_durations_are_errors() {
+        let root = parse("timeout = 1e30");
+
+        match WebullConfig::from_table(&root, None) {
+            Err(WebullError::InvalidConfig { key, .. }) => assert_eq!(key, "timeout"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn errors_name_the_offending_key() {
+        let root = parse(
+            r#"
+            [profiles.jp.rate_limit]
+            requests_per_minute = "fast"
+            "#,
+        );
+
+        match WebullConfig::from_table(&root, Some("jp")) {
+            Err(WebullError::InvalidConfig { key, .. }) => {
+                assert_eq!(key, "profiles.jp.rate_limit.requests_per_minute")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn streaming_urls_follow_the_region() {
+        let config = WebullConfig::for_region(Region::Hk);
+        assert_eq!(config.streaming_url(), Region::Hk.streaming_url());
+
+        let config = WebullConfig {
+            base_url: "http://127.0.0.1:8080/http-proxy".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.streaming_url(), "ws://127.0.0.1:8080/http-proxy");
+    }
+
+    #[test]
+    fn unreadable_files_are_config_errors() {
+        let path =
+            std::env::temp_dir().join(format!("webull-config-{}.json", uuid::Uuid::new_v4()));
+        for contents in [None, Some("{ not json")] {
+            if let Some(contents) = contents {
+                std::fs::write(&path, contents).unwrap();
+            }
+            match WebullConfig::from_file(&path) {
+                Err(WebullError::InvalidConfig { key, .. }) => assert_eq!(key, "file"),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn top_level_credentials_are_rejected() {
+        let root = parse(
+            r#"
+            username = "trader@example.com"
+
+            [profiles.paper-us]
+            password = "..."
+            "#,
+        );
+        match WebullConfig::from_table(&root, Some("paper-us")) {
+            Err(WebullError::InvalidConfig { key, .. }) => assert_eq!(key, "username"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
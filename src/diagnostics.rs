@@ -0,0 +1,713 @@
+//! Connectivity and health diagnostics.
+//!
+//! [`WebullClient::diagnose`] answers "why is the bot not trading?" in one
+//! call: it checks that every REST and streaming host resolves and answers,
+//! and collects the state of the login, trade token, clock, rate limits,
+//! circuit breakers, caches and streaming connections into a
+//! [`DiagnosticReport`]. The report prints as a human-readable summary and
+//! lists anything that would stop requests from succeeding:
+//!
+//! ```no_run
+//! # async fn example(client: webull_rs::WebullClient) {
+//! let report = client.diagnose().await;
+//! println!("{}", report);
+//! for problem in report.problems() {
+//!     eprintln!("problem: {}", problem);
+//! }
+//! # }
+//! ```
+//!
+//! Diagnosing sends one unauthenticated `GET` to the root of each host,
+//! bypassing the rate limiter and circuit breakers, so it works even when
+//! they are holding back API requests.
+
+use crate::client::WebullClient;
+use crate::region::Region;
+use crate::streaming::client::StreamStatus;
+use crate::streaming::events::ConnectionState;
+use crate::telemetry::redact_url;
+use crate::transport::HttpRequest;
+use crate::utils::cache::CacheStats;
+use crate::utils::circuit_breaker::CircuitState;
+use crate::utils::rate_limit::{Headroom, RateCategory};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::future::join_all;
+use reqwest::{Method, StatusCode};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Role of a host checked by [`WebullClient::diagnose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostRole {
+    /// Primary REST API host
+    Rest,
+
+    /// Fallback REST API host
+    Fallback,
+
+    /// WebSocket streaming host
+    Streaming,
+}
+
+impl HostRole {
+    /// Get the role name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rest => "rest",
+            Self::Fallback => "fallback",
+            Self::Streaming => "streaming",
+        }
+    }
+}
+
+/// Result of checking one host.
+#[derive(Debug, Clone)]
+pub struct HostCheck {
+    /// Role of the host
+    pub role: HostRole,
+
+    /// URL checked, with credentials redacted
+    pub url: String,
+
+    /// Addresses the host name resolved to, or why it did not resolve
+    pub addresses: Result<Vec<IpAddr>, String>,
+
+    /// Status of the response from the host, or why there was none
+    ///
+    /// A status means the TCP connection and, for `https` hosts, the TLS
+    /// handshake succeeded.
+    pub response: Result<StatusCode, String>,
+
+    /// Time taken to get the response
+    pub latency: Duration,
+
+    /// Whether API requests are currently sent to this host
+    pub active: bool,
+}
+
+impl HostCheck {
+    /// Check if the host resolved and answered without a server error.
+    pub fn is_reachable(&self) -> bool {
+        self.addresses.is_ok() && matches!(&self.response, Ok(status) if !status.is_server_error())
+    }
+}
+
+/// State of the access token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthStatus {
+    /// There is no token; the client has not logged in
+    LoggedOut,
+
+    /// The token is valid
+    Valid {
+        /// Time until the token expires
+        expires_in: TimeDelta,
+
+        /// Whether the token can be refreshed without logging in again
+        refreshable: bool,
+    },
+
+    /// The token has expired
+    Expired {
+        /// When the token expired
+        expired_at: DateTime<Utc>,
+
+        /// Whether the token can be refreshed without logging in again
+        refreshable: bool,
+    },
+
+    /// The token store could not be read
+    Unavailable(String),
+}
+
+/// State of the trade token, as seen in responses to trading requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeTokenState {
+    /// No trading request has been answered yet
+    Unknown,
+
+    /// The last trading request was accepted
+    Valid {
+        /// When the request was answered
+        checked_at: DateTime<Utc>,
+    },
+
+    /// The last trading request was rejected because the trade token expired
+    Expired {
+        /// When the request was answered
+        at: DateTime<Utc>,
+    },
+}
+
+/// Skew between the local and server clocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockStatus {
+    /// Server time minus local time, or `None` if it has not been measured
+    pub skew: Option<TimeDelta>,
+
+    /// Skew above which a warning is logged
+    pub warn_threshold: Duration,
+}
+
+impl ClockStatus {
+    /// Check if the skew is above the warning threshold.
+    pub fn is_skewed(&self) -> bool {
+        self.skew
+            .is_some_and(|skew| skew.abs().to_std().unwrap_or(Duration::MAX) > self.warn_threshold)
+    }
+}
+
+/// Rate limit budget left, globally and per category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Budget shared by all requests
+    pub global: Headroom,
+
+    /// Budget of each category that has its own limit
+    pub categories: Vec<(RateCategory, Headroom)>,
+}
+
+/// Report returned by [`WebullClient::diagnose`].
+#[derive(Debug, Clone)]
+pub struct DiagnosticReport {
+    /// When the report was generated
+    pub generated_at: DateTime<Utc>,
+
+    /// Webull region the client is configured for
+    pub region: Region,
+
+    /// Whether the client is configured for paper trading
+    pub paper_trading: bool,
+
+    /// REST and streaming hosts
+    pub hosts: Vec<HostCheck>,
+
+    /// Access token
+    pub auth: AuthStatus,
+
+    /// Trade token
+    pub trade_token: TradeTokenState,
+
+    /// Clock skew
+    pub clock: ClockStatus,
+
+    /// Rate limit budget
+    pub rate_limits: RateLimitStatus,
+
+    /// Circuit of each category; empty if the circuit breaker is disabled
+    pub circuits: Vec<(RateCategory, CircuitState)>,
+
+    /// Response caches, followed by the disk tier if there is one
+    pub cache: Vec<CacheStats>,
+
+    /// Streaming clients that are still alive
+    pub streams: Vec<StreamStatus>,
+}
+
+impl DiagnosticReport {
+    /// Describe everything that would stop requests from succeeding.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for host in &self.hosts {
+            if let Err(error) = &host.addresses {
+                problems.push(format!(
+                    "{} host {} does not resolve: {}",
+                    host.role.name(),
+                    host.url,
+                    error
+                ));
+            } else {
+                match &host.response {
+                    Err(error) => problems.push(format!(
+                        "{} host {} is unreachable: {}",
+                        host.role.name(),
+                        host.url,
+                        error
+                    )),
+                    Ok(status) if status.is_server_error() => problems.push(format!(
+                        "{} host {} answered with {}",
+                        host.role.name(),
+                        host.url,
+                        status
+                    )),
+                    Ok(_) => {}
+                }
+            }
+        }
+
+        match &self.auth {
+            AuthStatus::LoggedOut => problems.push("not logged in".to_string()),
+            AuthStatus::Expired {
+                refreshable: false, ..
+            } => problems.push("access token expired and cannot be refreshed".to_string()),
+            AuthStatus::Unavailable(error) => {
+                problems.push(format!("access token unavailable: {}", error))
+            }
+            AuthStatus::Valid { .. } | AuthStatus::Expired { .. } => {}
+        }
+
+        if let TradeTokenState::Expired { at } = self.trade_token {
+            problems.push(format!("trade token expired at {}", at));
+        }
+
+        if self.clock.is_skewed() {
+            if let Some(skew) = self.clock.skew {
+                problems.push(format!(
+                    "local clock is {} ms behind the server",
+                    skew.num_milliseconds()
+                ));
+            }
+        }
+
+        if self.rate_limits.global.is_exhausted() {
+            problems.push("global rate limit exhausted".to_string());
+        }
+        for (category, headroom) in &self.rate_limits.categories {
+            if headroom.is_exhausted() {
+                problems.push(format!("{} rate limit exhausted", category.name()));
+            }
+        }
+
+        for (category, state) in &self.circuits {
+            if *state == CircuitState::Open {
+                problems.push(format!("{} circuit is open", category.name()));
+            }
+        }
+
+        for stream in &self.streams {
+            if matches!(
+                stream.state,
+                ConnectionState::Failed | ConnectionState::Reconnecting
+            ) {
+                problems.push(format!("stream {} is {:?}", stream.url, stream.state));
+            }
+        }
+
+        problems
+    }
+
+    /// Check if nothing would stop requests from succeeding.
+    pub fn is_healthy(&self) -> bool {
+        self.problems().is_empty()
+    }
+}
+
+impl fmt::Display for DiagnosticReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Webull diagnostics at {} ({}, {})",
+            self.generated_at,
+            self.region,
+            if self.paper_trading { "paper" } else { "live" }
+        )?;
+
+        writeln!(f, "Hosts:")?;
+        for host in &self.hosts {
+            let addresses = match &host.addresses {
+                Ok(addresses) => addresses
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Err(error) => format!("unresolved ({})", error),
+            };
+            let response = match &host.response {
+                Ok(status) => status.to_string(),
+                Err(error) => format!("failed ({})", error),
+            };
+            writeln!(
+                f,
+                "  {:<9} {}{} [{}] {} in {} ms",
+                host.role.name(),
+                host.url,
+                if host.active { " (active)" } else { "" },
+                addresses,
+                response,
+                host.latency.as_millis()
+            )?;
+        }
+
+        let auth = match &self.auth {
+            AuthStatus::LoggedOut => "logged out".to_string(),
+            AuthStatus::Valid {
+                expires_in,
+                refreshable,
+            } => format!(
+                "valid, expires in {} s{}",
+                expires_in.num_seconds(),
+                if *refreshable { ", refreshable" } else { "" }
+            ),
+            AuthStatus::Expired {
+                expired_at,
+                refreshable,
+            } => format!(
+                "expired at {}{}",
+                expired_at,
+                if *refreshable { ", refreshable" } else { "" }
+            ),
+            AuthStatus::Unavailable(error) => format!("unavailable ({})", error),
+        };
+        writeln!(f, "Access token: {}", auth)?;
+
+        let trade_token = match self.trade_token {
+            TradeTokenState::Unknown => "unknown".to_string(),
+            TradeTokenState::Valid { checked_at } => format!("valid at {}", checked_at),
+            TradeTokenState::Expired { at } => format!("expired at {}", at),
+        };
+        writeln!(f, "Trade token: {}", trade_token)?;
+
+        match self.clock.skew {
+            Some(skew) => writeln!(
+                f,
+                "Clock skew: {} ms (warn above {} ms)",
+                skew.num_milliseconds(),
+                self.clock.warn_threshold.as_millis()
+            )?,
+            None => writeln!(f, "Clock skew: not measured")?,
+        }
+
+        writeln!(f, "Rate limits:")?;
+        let budgets = std::iter::once(("global", &self.rate_limits.global)).chain(
+            self.rate_limits
+                .categories
+                .iter()
+                .map(|(category, headroom)| (category.name(), headroom)),
+        );
+        for (name, headroom) in budgets {
+            write!(
+                f,
+                "  {:<9} {}/{} per minute",
+                name, headroom.available, headroom.limit
+            )?;
+            match headroom.paused_for {
+                Some(paused_for) => writeln!(f, ", paused for {} ms", paused_for.as_millis())?,
+                None => writeln!(f)?,
+            }
+        }
+
+        if !self.circuits.is_empty() {
+            writeln!(f, "Circuits:")?;
+            for (category, state) in &self.circuits {
+                writeln!(f, "  {:<9} {}", category.name(), state.name())?;
+            }
+        }
+
+        writeln!(f, "Caches:")?;
+        for cache in &self.cache {
+            writeln!(
+                f,
+                "  {:<9} {} entries, {} hits, {} misses, {} evictions",
+                cache.name, cache.entries, cache.hits, cache.misses, cache.evictions
+            )?;
+        }
+
+        writeln!(f, "Streams:")?;
+        for stream in &self.streams {
+            writeln!(
+                f,
+                "  {} {:?}, {} subscriptions",
+                stream.url, stream.state, stream.subscriptions
+            )?;
+        }
+
+        let problems = self.problems();
+        if problems.is_empty() {
+            writeln!(f, "No problems found")
+        } else {
+            writeln!(f, "Problems:")?;
+            for problem in problems {
+                writeln!(f, "  - {}", problem)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Build a diagnostic report for a client.
+pub(crate) async fn diagnose(client: &WebullClient) -> DiagnosticReport {
+    let mut targets: Vec<(HostRole, Result<Url, String>, bool)> = match client.failover() {
+        Some(failover) => failover
+            .hosts()
+            .iter()
+            .enumerate()
+            .map(|(index, host)| {
+                let role = if index == 0 {
+                    HostRole::Rest
+                } else {
+                    HostRole::Fallback
+                };
+                (role, Ok(host.clone()), host == failover.active_host())
+            })
+            .collect(),
+        None => vec![(
+            HostRole::Rest,
+            Url::parse(&client.config().base_url).map_err(|error| error.to_string()),
+            true,
+        )],
+    };
+    targets.push((
+        HostRole::Streaming,
+        streaming_probe_url(&client.streaming_url()),
+        false,
+    ));
+
+    let hosts = join_all(
+        targets
+            .into_iter()
+            .map(|(role, url, active)| check_host(client, role, url, active)),
+    )
+    .await;
+
+    let config = client.config();
+    let rate_limiter = client.rate_limiter();
+    DiagnosticReport {
+        generated_at: Utc::now(),
+        region: config.region,
+        paper_trading: config.paper_trading,
+        hosts,
+        auth: auth_status(client),
+        trade_token: client.base().trade_token(),
+        clock: ClockStatus {
+            skew: client.clock().skew(),
+            warn_threshold: config.clock_skew.warn_threshold,
+        },
+        rate_limits: RateLimitStatus {
+            global: rate_limiter.global_headroom(),
+            categories: RateCategory::ALL
+                .into_iter()
+                .filter_map(|category| Some((category, rate_limiter.headroom(category)?)))
+                .collect(),
+        },
+        circuits: client
+            .circuit_breaker()
+            .map(|breaker| {
+                RateCategory::ALL
+                    .into_iter()
+                    .map(|category| (category, breaker.state(category)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        cache: cache_stats(client),
+        streams: client.streams(),
+    }
+}
+
+/// Get the HTTP URL to check a streaming host with.
+///
+/// A request to the `http` or `https` URL of a WebSocket host goes through
+/// the same DNS lookup, TCP connection and TLS handshake as the WebSocket
+/// connection itself.
+fn streaming_probe_url(url: &str) -> Result<Url, String> {
+    let mut url = Url::parse(url).map_err(|error| error.to_string())?;
+    let scheme = match url.scheme() {
+        "wss" => "https",
+        "ws" => "http",
+        other => return Err(format!("unsupported streaming scheme {}", other)),
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| format!("cannot check {} over {}", url, scheme))?;
+    Ok(url)
+}
+
+/// Resolve a host and send it an unauthenticated request.
+async fn check_host(
+    client: &WebullClient,
+    role: HostRole,
+    url: Result<Url, String>,
+    active: bool,
+) -> HostCheck {
+    let timeout = client.config().timeout;
+    let url = match url {
+        Ok(url) => url,
+        Err(error) => {
+            return HostCheck {
+                role,
+                url: String::new(),
+                addresses: Err(error.clone()),
+                response: Err(error),
+                latency: Duration::ZERO,
+                active,
+            }
+        }
+    };
+
+    let addresses = resolve(&url, timeout).await;
+    let started = Instant::now();
+    let sent_at = Utc::now();
+    let response = if addresses.is_ok() {
+        let request = HttpRequest::new(Method::GET, url.clone());
+        match tokio::time::timeout(timeout, client.transport().send(request)).await {
+            Ok(Ok(response)) => {
+                // Every REST host is expected to share the server clock
+                if active && role != HostRole::Streaming {
+                    client
+                        .clock()
+                        .observe(&response.headers, sent_at, Utc::now());
+                }
+                Ok(response.status)
+            }
+            Ok(Err(error)) => Err(error.to_string()),
+            Err(_) => Err(format!("timed out after {} ms", timeout.as_millis())),
+        }
+    } else {
+        Err("not sent".to_string())
+    };
+
+    HostCheck {
+        role,
+        url: redact_url(&url),
+        addresses,
+        response,
+        latency: started.elapsed(),
+        active,
+    }
+}
+
+/// Resolve the host of a URL.
+async fn resolve(url: &Url, timeout: Duration) -> Result<Vec<IpAddr>, String> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| "URL has no host".to_string())?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(443);
+    match tokio::time::timeout(timeout, tokio::net::lookup_host((host, port))).await {
+        Ok(Ok(addresses)) => Ok(addresses.map(|address| address.ip()).collect()),
+        Ok(Err(error)) => Err(error.to_string()),
+        Err(_) => Err(format!("timed out after {} ms", timeout.as_millis())),
+    }
+}
+
+/// Get the statistics of the response caches and the disk tier.
+fn cache_stats(client: &WebullClient) -> Vec<CacheStats> {
+    let cache_manager = client.cache_manager();
+    let mut stats = cache_manager.stats();
+    stats.extend(cache_manager.disk().map(|disk| disk.stats()));
+    stats
+}
+
+/// Get the state of the access token.
+fn auth_status(client: &WebullClient) -> AuthStatus {
+    match client.auth_manager().token_store.get_token() {
+        Ok(None) => AuthStatus::LoggedOut,
+        Ok(Some(token)) => {
+            let refreshable = token.refresh_token.is_some();
+            let expires_in = token.expires_at - Utc::now();
+            if expires_in > TimeDelta::zero() {
+                AuthStatus::Valid {
+                    expires_in,
+                    refreshable,
+                }
+            } else {
+                AuthStatus::Expired {
+                    expired_at: token.expires_at,
+                    refreshable,
+                }
+            }
+        }
+        Err(error) => AuthStatus::Unavailable(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StreamingConfig;
+    use crate::middleware::HttpResponse;
+    use crate::transport::testing::FakeTransport;
+
+    fn client(fake: FakeTransport) -> WebullClient {
+        // IP literals resolve without a DNS server
+        WebullClient::builder()
+            .with_transport(fake)
+            .with_custom_url("http://127.0.0.1:9")
+            .with_streaming_config(StreamingConfig {
+                url: Some("ws://127.0.0.1:9".to_string()),
+                ..StreamingConfig::default()
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn logged_out_clients_report_it() {
+        let fake = FakeTransport::new();
+        let client = client(fake.clone());
+
+        let report = client.diagnose().await;
+
+        assert_eq!(report.auth, AuthStatus::LoggedOut);
+        assert_eq!(report.trade_token, TradeTokenState::Unknown);
+        assert_eq!(
+            report
+                .hosts
+                .iter()
+                .map(|host| host.role)
+                .collect::<Vec<_>>(),
+            [HostRole::Rest, HostRole::Streaming]
+        );
+        assert!(report.hosts.iter().all(HostCheck::is_reachable));
+        assert_eq!(report.problems(), ["not logged in"]);
+        assert_eq!(fake.requests(), ["GET /", "GET /"]);
+        assert!(report.to_string().contains("Access token: logged out"));
+    }
+
+    #[tokio::test]
+    async fn reports_cover_hosts_tokens_caches_and_streams() {
+        let fake = FakeTransport::with_handler(|request| {
+            if request.url.path().starts_with("/api/trade") {
+                Some(HttpResponse::new(
+                    StatusCode::EXPECTATION_FAILED,
+                    r#"{"code":"TRADE_TOKEN_EXPIRED","msg":"trade token expired"}"#,
+                ))
+            } else if request.url.path() == "/" && request.url.scheme() == "http" {
+                Some(HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""))
+            } else {
+                None
+            }
+        });
+        let client = client(fake);
+        client.login("user", "password").await.unwrap();
+        client.accounts().get_accounts().await.unwrap();
+        assert!(client.orders().get_active_orders().await.is_err());
+        let stream = client.streaming();
+
+        let report = client.diagnose().await;
+
+        assert!(matches!(
+            report.auth,
+            AuthStatus::Valid {
+                refreshable: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            report.trade_token,
+            TradeTokenState::Expired { .. }
+        ));
+        assert!(!report.hosts[0].is_reachable());
+        assert_eq!(
+            report.streams,
+            [StreamStatus {
+                url: "ws://127.0.0.1:9/".to_string(),
+                state: ConnectionState::Disconnected,
+                subscriptions: 0,
+            }]
+        );
+        assert!(report.cache.iter().any(|cache| cache.entries > 0));
+        assert_eq!(report.circuits.len(), RateCategory::ALL.len());
+        assert!(report.rate_limits.global.limit > 0);
+
+        let problems = report.problems();
+        assert!(problems.iter().any(|problem| problem.contains("503")));
+        assert!(problems
+            .iter()
+            .any(|problem| problem.contains("trade token")));
+
+        drop(stream);
+        assert!(client.diagnose().await.streams.is_empty());
+    }
+}
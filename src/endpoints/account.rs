@@ -2,9 +2,12 @@ use crate::auth::AuthManager;
 use crate::endpoints::base::BaseEndpoint;
 use crate::error::WebullResult;
 use crate::models::account::{
-    Account, AccountBalance, AccountProfile, BalanceParams, Position, PositionParams, TradeHistory,
+    Account, AccountBalance, AccountProfile, BalanceParams, PaperAccountResetRequest,
+    PaperFundingRequest, Position, PositionParams, TradeHistory,
 };
-use reqwest::Client;
+use crate::region::Region;
+use crate::transport::HttpTransport;
+use crate::utils::cache::CachePolicy;
 use std::sync::Arc;
 
 /// Endpoints for account operations.
@@ -15,45 +18,97 @@ pub struct AccountEndpoints {
 
 impl AccountEndpoints {
     /// Create new account endpoints.
-    pub fn new(client: Client, base_url: String, auth_manager: Arc<AuthManager>) -> Self {
+    pub fn new(
+        transport: Arc<dyn HttpTransport>,
+        base_url: String,
+        auth_manager: Arc<AuthManager>,
+    ) -> Self {
         Self {
-            base: BaseEndpoint::new(client, base_url, auth_manager),
+            base: BaseEndpoint::new(transport, base_url, auth_manager),
         }
     }
 
+    /// Create new endpoints on top of an existing base endpoint.
+    ///
+    /// This lets several endpoint groups share one HTTP client, rate limiter
+    /// and cache.
+    pub fn from_base(base: BaseEndpoint) -> Self {
+        Self { base }
+    }
+
+    /// Set the Webull region used for region-specific behaviour.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.base = self.base.with_region(region);
+        self
+    }
+
+    /// Route requests to the paper-trading account API.
+    pub fn with_paper_trading(mut self, paper_trading: bool) -> Self {
+        self.base = self.base.with_paper_trading(paper_trading);
+        self
+    }
+
     /// Get a list of accounts.
+    ///
+    /// Fails if any returned account does not match the client's trading mode.
     pub async fn get_accounts(&self) -> WebullResult<Vec<Account>> {
-        self.base.get("/api/account/getSecAccountList").await
+        let path = self.base.trading_path("/api/account/getSecAccountList");
+        let accounts: Vec<Account> = self.base.get_cached(&path, CachePolicy::ACCOUNT).await?;
+        for account in &accounts {
+            self.base
+                .ensure_account_mode(&account.id, account.paper_trading)?;
+        }
+        Ok(accounts)
     }
 
     /// Get account details.
+    ///
+    /// Fails if the account does not match the client's trading mode.
     pub async fn get_account(&self, account_id: &str) -> WebullResult<Account> {
-        let path = format!("/api/account/getAccountMembers/{}", account_id);
-        self.base.get(&path).await
+        let path = self
+            .base
+            .trading_path(&format!("/api/account/getAccountMembers/{}", account_id));
+        let account: Account = self.base.get_cached(&path, CachePolicy::ACCOUNT).await?;
+        self.base
+            .ensure_account_mode(&account.id, account.paper_trading)?;
+        Ok(account)
     }
 
     /// Get account balance.
     pub async fn get_account_balance(&self, account_id: &str) -> WebullResult<AccountBalance> {
-        let path = format!("/api/asset/getAssetSummary/{}", account_id);
-        self.base.get(&path).await
+        self.base.check_account(account_id).await?;
+        let path = self
+            .base
+            .trading_path(&format!("/api/asset/getAssetSummary/{}", account_id));
+        self.base.get_cached(&path, CachePolicy::ACCOUNT).await
     }
 
     /// Get account positions.
     pub async fn get_positions(&self, account_id: &str) -> WebullResult<Vec<Position>> {
-        let path = format!("/api/position/getUserPositions/{}", account_id);
-        self.base.get(&path).await
+        self.base.check_account(account_id).await?;
+        let path = self
+            .base
+            .trading_path(&format!("/api/position/getUserPositions/{}", account_id));
+        self.base.get_cached(&path, CachePolicy::ACCOUNT).await
     }
 
     /// Get account position by symbol.
     pub async fn get_position(&self, account_id: &str, symbol: &str) -> WebullResult<Position> {
-        let path = format!("/api/position/getUserPositions/{}/{}", account_id, symbol);
-        self.base.get(&path).await
+        self.base.check_account(account_id).await?;
+        let path = self.base.trading_path(&format!(
+            "/api/position/getUserPositions/{}/{}",
+            account_id, symbol
+        ));
+        self.base.get_cached(&path, CachePolicy::ACCOUNT).await
     }
 
     /// Get account trade history.
     pub async fn get_trade_history(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>> {
-        let path = format!("/api/trade/history/{}", account_id);
-        self.base.get(&path).await
+        self.base.check_account(account_id).await?;
+        let path = self
+            .base
+            .trading_path(&format!("/api/trade/history/{}", account_id));
+        self.base.get_cached(&path, CachePolicy::ACCOUNT).await
     }
 
     /// Get account trade history with pagination.
@@ -63,6 +118,8 @@ impl AccountEndpoints {
         page: u32,
         page_size: u32,
     ) -> WebullResult<Vec<TradeHistory>> {
+        self.base.check_account(account_id).await?;
+
         #[derive(serde::Serialize)]
         struct TradeHistoryParams {
             account_id: String,
@@ -76,26 +133,38 @@ impl AccountEndpoints {
             page_size,
         };
 
-        self.base.post("/api/trade/history", &params).await
+        let path = self.base.trading_path("/api/trade/history");
+        self.base
+            .get_cached_with_query(&path, &params, CachePolicy::ACCOUNT)
+            .await
     }
 
     /// Get account profile information.
     pub async fn get_account_profile(&self, account_id: &str) -> WebullResult<AccountProfile> {
-        let path = format!("/api/account/profile/{}", account_id);
-        self.base.get(&path).await
+        let path = self
+            .base
+            .trading_path(&format!("/api/account/profile/{}", account_id));
+        let profile: AccountProfile = self.base.get_cached(&path, CachePolicy::ACCOUNT).await?;
+        self.base
+            .ensure_account_mode(&profile.id, profile.paper_trading)?;
+        Ok(profile)
     }
 
     /// Get account balance with parameters.
     pub async fn get_balance(&self, params: &BalanceParams) -> WebullResult<AccountBalance> {
-        self.base.post("/api/account/balance", params).await
+        self.base.check_account(&params.account_id).await?;
+        let path = self.base.trading_path("/api/account/balance");
+        self.base
+            .get_cached_with_query(&path, params, CachePolicy::ACCOUNT)
+            .await
     }
 
-    /// Helper method to get account balance with default currency.
+    /// Helper method to get account balance in the region's default currency.
     pub async fn get_balance_with_default_currency(
         &self,
         account_id: &str,
     ) -> WebullResult<AccountBalance> {
-        let params = BalanceParams::new_with_default_currency(account_id);
+        let params = BalanceParams::for_region(account_id, self.base.region());
         self.get_balance(&params).await
     }
 
@@ -104,7 +173,11 @@ impl AccountEndpoints {
         &self,
         params: &PositionParams,
     ) -> WebullResult<Vec<Position>> {
-        self.base.post("/api/account/positions", params).await
+        self.base.check_account(&params.account_id).await?;
+        let path = self.base.trading_path("/api/account/positions");
+        self.base
+            .get_cached_with_query(&path, params, CachePolicy::ACCOUNT)
+            .await
     }
 
     /// Helper method to get account positions with pagination.
@@ -120,4 +193,80 @@ impl AccountEndpoints {
         }
         self.get_positions_with_params(&params).await
     }
+
+    /// Reset a paper trading account to a fresh starting balance.
+    ///
+    /// Only available in paper trading mode.
+    pub async fn reset_paper_account(
+        &self,
+        request: &PaperAccountResetRequest,
+    ) -> WebullResult<AccountBalance> {
+        self.base.ensure_paper_trading("reset_paper_account")?;
+        self.base.check_account(&request.account_id).await?;
+        let response = self.base.post("/api/paper/account/reset", request).await?;
+        self.base.invalidate("/api/paper");
+        Ok(response)
+    }
+
+    /// Add virtual funds to a paper trading account.
+    ///
+    /// Only available in paper trading mode.
+    pub async fn fund_paper_account(
+        &self,
+        request: &PaperFundingRequest,
+    ) -> WebullResult<AccountBalance> {
+        self.base.ensure_paper_trading("fund_paper_account")?;
+        self.base.check_account(&request.account_id).await?;
+        let response = self.base.post("/api/paper/account/fund", request).await?;
+        self.base.invalidate("/api/paper");
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::account::PaperAccountResetRequest;
+    use crate::transport::testing::FakeTransport;
+    use crate::{WebullClient, WebullError};
+
+    #[tokio::test]
+    async fn paper_only_operations_require_paper_mode() {
+        let fake = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .build()
+            .unwrap();
+        let request = PaperAccountResetRequest::new("paper-1", 100_000.0);
+
+        match client.accounts().reset_paper_account(&request).await {
+            Err(WebullError::TradingModeMismatch(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(fake.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_accounts_are_not_mode_mismatches() {
+        let fake = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .without_cache()
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+
+        // Live clients leave unknown IDs to the server
+        client.accounts().get_positions("5550001").await.unwrap();
+        assert_eq!(
+            fake.requests()[1..],
+            ["GET /api/position/getUserPositions/5550001"]
+        );
+
+        let paper = client.paper_trading().unwrap();
+        paper.login("user", "password").await.unwrap();
+        match paper.accounts().get_positions("5550001").await {
+            Err(WebullError::InvalidRequest(message)) => assert!(message.contains("not found")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
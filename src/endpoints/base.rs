@@ -1,20 +1,37 @@
 use crate::auth::AuthManager;
-use crate::error::{WebullError, WebullResult};
+use crate::cassette::Cassette;
+use crate::config::RateLimitConfig;
+use crate::diagnostics::TradeTokenState;
+use crate::error::{ErrorKind, WebullError, WebullResult};
+use crate::middleware::{
+    invalidation_scopes, ApiRequest, AuthLayer, CacheLayer, CassetteLayer, CircuitBreakerLayer,
+    ClockLayer, FailoverLayer, HttpResponse, MetaLayer, Middleware, Next, RateLimitLayer,
+    RetryLayer, SingleFlightLayer, TraceLayer,
+};
+use crate::models::account::Account;
 use crate::models::response::ApiResponse;
-use crate::utils::cache::CacheManager;
-use crate::utils::rate_limit::RateLimiter;
+use crate::region::{Capability, Region};
+use crate::telemetry::{Metrics, NoopMetrics};
+use crate::transport::{HttpRequest, HttpTransport};
+use crate::utils::cache::{CacheManager, CachePolicy};
+use crate::utils::circuit_breaker::CircuitBreaker;
+use crate::utils::failover::Failover;
+use crate::utils::rate_limit::{Priority, RateCategory, RateLimiter};
+use crate::utils::retry::RetryPolicy;
+use crate::utils::serialization::to_query_string;
 use reqwest::header::AUTHORIZATION;
-use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use reqwest::Method;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use url::Url;
 
 /// Base endpoint for API requests.
+#[derive(Clone)]
 pub struct BaseEndpoint {
-    /// HTTP client
-    client: Client,
+    /// HTTP transport
+    transport: Arc<dyn HttpTransport>,
 
     /// Base URL for API requests
     base_url: String,
@@ -27,107 +44,286 @@ pub struct BaseEndpoint {
 
     /// Cache manager
     cache_manager: Arc<CacheManager>,
+
+    /// Retry policy
+    retry_policy: Arc<RetryPolicy>,
+
+    /// Coalescing of concurrent identical GET requests
+    single_flight: Arc<SingleFlightLayer>,
+
+    /// Circuit breaker, if enabled
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+
+    /// Hosts to fail over across, if there are fallback base URLs
+    failover: Option<Arc<Failover>>,
+
+    /// Custom middleware, run after the built-in layers
+    middleware: Vec<Arc<dyn Middleware>>,
+
+    /// Metrics sink
+    metrics: Arc<dyn Metrics>,
+
+    /// Cassette that records or replays requests
+    cassette: Option<Arc<Cassette>>,
+
+    /// Webull region
+    region: Region,
+
+    /// Whether trading requests go to the paper-trading API
+    paper_trading: bool,
+
+    /// Trading mode of each account seen, by account ID; `true` for paper accounts
+    account_modes: Arc<Mutex<HashMap<String, bool>>>,
+
+    /// Trade token state seen in the last trading response
+    trade_token: Arc<Mutex<TradeTokenState>>,
 }
 
 impl BaseEndpoint {
     /// Create a new base endpoint.
-    pub fn new(client: Client, base_url: String, auth_manager: Arc<AuthManager>) -> Self {
+    pub fn new(
+        transport: Arc<dyn HttpTransport>,
+        base_url: String,
+        auth_manager: Arc<AuthManager>,
+    ) -> Self {
         Self {
-            client,
+            transport,
             base_url,
             auth_manager,
-            rate_limiter: Arc::new(RateLimiter::new(60)), // Default to 60 requests per minute
+            rate_limiter: Arc::new(RateLimiter::from_config(&RateLimitConfig::default())),
             cache_manager: Arc::new(CacheManager::new()),
+            retry_policy: Arc::new(RetryPolicy::default()),
+            single_flight: Arc::new(SingleFlightLayer::new()),
+            circuit_breaker: None,
+            failover: None,
+            middleware: Vec::new(),
+            metrics: Arc::new(NoopMetrics),
+            cassette: None,
+            region: Region::default(),
+            paper_trading: false,
+            account_modes: Arc::new(Mutex::new(HashMap::new())),
+            trade_token: Arc::new(Mutex::new(TradeTokenState::Unknown)),
         }
     }
 
-    /// Build a request to the API.
-    pub fn request<T>(&self, method: Method, path: &str) -> RequestBuilder
-    where
-        T: DeserializeOwned,
-    {
-        let url = self.build_url(path);
-        self.client.request(method, url)
+    /// Set whether trading requests go to the paper-trading API.
+    pub fn with_paper_trading(mut self, paper_trading: bool) -> Self {
+        self.paper_trading = paper_trading;
+        self
     }
 
-    /// Send a request to the API and parse the response.
-    pub async fn send_request<T>(&self, request: RequestBuilder) -> WebullResult<T>
-    where
-        T: DeserializeOwned + Clone,
-    {
-        // Clone the request URL to get the path
-        let req_url = request
-            .try_clone()
-            .ok_or_else(|| WebullError::InvalidRequest("Failed to clone request".to_string()))?
-            .build()
-            .map_err(WebullError::NetworkError)?
-            .url()
-            .clone();
-
-        let path = req_url.path();
-
-        // Wait for rate limit
-        self.rate_limiter.wait(path).await;
-
-        // Send the request
-        let response = request.send().await.map_err(WebullError::NetworkError)?;
-
-        let status = response.status();
-
-        // Handle rate limiting
-        if status == StatusCode::TOO_MANY_REQUESTS {
-            // Get the retry-after header if available
-            let retry_after = response
-                .headers()
-                .get("retry-after")
-                .and_then(|h| h.to_str().ok())
-                .and_then(|s| s.parse::<u64>().ok())
-                .unwrap_or(1);
-
-            // Wait for the specified time
-            tokio::time::sleep(std::time::Duration::from_secs(retry_after)).await;
-
-            return Err(WebullError::RateLimitExceeded);
+    /// Check if trading requests go to the paper-trading API.
+    pub fn is_paper_trading(&self) -> bool {
+        self.paper_trading
+    }
+
+    /// Map a live trading path to the path for the current trading mode.
+    ///
+    /// In paper mode, `/api/trade/order` becomes `/api/paper/trade/order`.
+    pub fn trading_path(&self, path: &str) -> String {
+        if self.paper_trading {
+            format!("/api/paper{}", path.trim_start_matches("/api"))
+        } else {
+            path.to_string()
         }
+    }
 
-        // Handle unauthorized
-        if status == StatusCode::UNAUTHORIZED {
-            return Err(WebullError::Unauthorized);
+    /// Fail if an account's trading mode does not match the client's.
+    ///
+    /// The account's mode is remembered for [`BaseEndpoint::check_account`].
+    pub fn ensure_account_mode(&self, account_id: &str, paper_account: bool) -> WebullResult<()> {
+        self.account_modes
+            .lock()
+            .unwrap()
+            .insert(account_id.to_string(), paper_account);
+        if paper_account == self.paper_trading {
+            return Ok(());
         }
 
-        // Get the response body
-        let body = response.text().await.map_err(WebullError::NetworkError)?;
+        let mode = |paper: bool| if paper { "paper" } else { "live" };
+        Err(WebullError::TradingModeMismatch(format!(
+            "account {} is a {} account but the client is in {} trading mode",
+            account_id,
+            mode(paper_account),
+            mode(self.paper_trading)
+        )))
+    }
 
-        // Handle other errors
-        if !status.is_success() {
-            return Err(WebullError::ApiError {
-                code: status.as_u16().to_string(),
-                message: body,
-            });
+    /// Fail if an account ID does not belong to the client's trading mode.
+    ///
+    /// Live clients only reject accounts already known to be paper accounts
+    /// and leave other IDs to the server. Paper clients, whose requests are
+    /// routed by mode, look accounts they have not seen yet up in the paper
+    /// and then the live account list, and reject IDs that are in neither.
+    pub async fn check_account(&self, account_id: &str) -> WebullResult<()> {
+        if let Some(paper_account) = self.account_mode(account_id) {
+            return self.ensure_account_mode(account_id, paper_account);
+        }
+        if !self.paper_trading {
+            return Ok(());
         }
 
-        // Parse the response
-        let api_response: ApiResponse<T> =
-            serde_json::from_str(&body).map_err(|e| WebullError::SerializationError(e))?;
-
-        // Check for API errors
-        if !api_response.is_success() {
-            return Err(WebullError::ApiError {
-                code: api_response.code.unwrap_or_else(|| "unknown".to_string()),
-                message: api_response
-                    .message
-                    .unwrap_or_else(|| "Unknown error".to_string()),
-            });
+        // The live account list tells a live account apart from an unknown ID
+        const ACCOUNT_LIST: &str = "/api/account/getSecAccountList";
+        for path in [self.trading_path(ACCOUNT_LIST), ACCOUNT_LIST.to_string()] {
+            let accounts: Vec<Account> = self.get_cached(&path, CachePolicy::ACCOUNT).await?;
+            self.account_modes.lock().unwrap().extend(
+                accounts
+                    .iter()
+                    .map(|account| (account.id.clone(), account.paper_trading)),
+            );
+
+            if let Some(paper_account) = self.account_mode(account_id) {
+                return self.ensure_account_mode(account_id, paper_account);
+            }
         }
 
-        // Return the data
-        api_response
-            .get_data()
-            .cloned()
-            .ok_or_else(|| WebullError::ApiError {
-                code: "no_data".to_string(),
-                message: "Response did not contain data".to_string(),
+        Err(WebullError::InvalidRequest(format!(
+            "Account {} not found",
+            account_id
+        )))
+    }
+
+    /// Get the remembered trading mode of an account.
+    fn account_mode(&self, account_id: &str) -> Option<bool> {
+        self.account_modes.lock().unwrap().get(account_id).copied()
+    }
+
+    /// Fail if the client is not in paper trading mode.
+    pub fn ensure_paper_trading(&self, operation: &str) -> WebullResult<()> {
+        if self.paper_trading {
+            self.ensure_supported(Capability::PaperTrading, operation)
+        } else {
+            Err(WebullError::TradingModeMismatch(format!(
+                "{} is only available in paper trading mode",
+                operation
+            )))
+        }
+    }
+
+    /// Use a shared rate limiter instead of a private one.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Use a shared cache manager instead of a private one.
+    pub fn with_cache_manager(mut self, cache_manager: Arc<CacheManager>) -> Self {
+        self.cache_manager = cache_manager;
+        self
+    }
+
+    /// Use a shared retry policy.
+    pub fn with_retry_policy(mut self, retry_policy: Arc<RetryPolicy>) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Share in-flight GET requests with other endpoints.
+    pub fn with_single_flight(mut self, single_flight: Arc<SingleFlightLayer>) -> Self {
+        self.single_flight = single_flight;
+        self
+    }
+
+    /// Fail requests fast while a circuit breaker is open.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Fail over across a list of base URLs.
+    ///
+    /// The first URL of the list must be the base URL.
+    pub fn with_failover(mut self, failover: Arc<Failover>) -> Self {
+        self.failover = Some(failover);
+        self
+    }
+
+    /// Add a custom middleware layer.
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// Report request metrics to a sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Record requests to, or replay them from, a cassette.
+    pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Get the retry policy.
+    pub fn retry_policy(&self) -> &Arc<RetryPolicy> {
+        &self.retry_policy
+    }
+
+    /// Get the rate limiter.
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// Get the cache manager.
+    pub fn cache_manager(&self) -> &Arc<CacheManager> {
+        &self.cache_manager
+    }
+
+    /// Get the circuit breaker, if enabled.
+    pub fn circuit_breaker(&self) -> Option<&Arc<CircuitBreaker>> {
+        self.circuit_breaker.as_ref()
+    }
+
+    /// Get the hosts requests fail over across, if any.
+    pub fn failover(&self) -> Option<&Arc<Failover>> {
+        self.failover.as_ref()
+    }
+
+    /// Get the trade token state seen in the last trading response.
+    pub fn trade_token(&self) -> TradeTokenState {
+        *self.trade_token.lock().unwrap()
+    }
+
+    /// Set the Webull region.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.region = region;
+        self
+    }
+
+    /// Get the Webull region.
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Fail fast if an operation needs a capability the region does not offer.
+    pub fn ensure_supported(&self, capability: Capability, operation: &str) -> WebullResult<()> {
+        if self.region.supports(capability) {
+            Ok(())
+        } else {
+            Err(WebullError::UnsupportedInRegion {
+                operation: format!("{} ({})", operation, capability.name()),
+                region: self.region,
             })
+        }
+    }
+
+    /// Build a request to the API.
+    pub fn request(&self, method: Method, path: &str) -> HttpRequest {
+        HttpRequest::new(method, self.build_url(path))
+    }
+
+    /// Send a prebuilt request to the API and parse the response.
+    ///
+    /// The request bypasses the middleware chain, so it is not authenticated,
+    /// rate limited, retried or cached.
+    pub async fn send_request<T>(&self, request: HttpRequest) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone,
+    {
+        parse_response(self.transport.send(request).await?)
     }
 
     /// Build a URL for the API.
@@ -143,131 +339,290 @@ impl BaseEndpoint {
     }
 
     /// Add authentication headers to a request.
-    pub async fn authenticate_request(
-        &self,
-        request: RequestBuilder,
-    ) -> WebullResult<RequestBuilder> {
+    pub async fn authenticate_request(&self, request: HttpRequest) -> WebullResult<HttpRequest> {
         // Get the token from the auth manager
         let token = self.auth_manager.get_token().await?;
 
         // Add the token to the request headers
-        let request = request.header(AUTHORIZATION, format!("Bearer {}", token.token));
-
-        Ok(request)
+        Ok(request.with_header(AUTHORIZATION, token.bearer_header()?))
     }
 
-    /// Send a GET request to the API.
+    /// Send a GET request to the API, caching the response with the default policy.
     pub async fn get<T>(&self, path: &str) -> WebullResult<T>
     where
         T: DeserializeOwned + Clone + Send + Sync + 'static,
     {
-        // Check if we have a cached response
-        let cache = self.cache_manager.get_cache::<T>("get");
-        if let Some(cached) = cache.get("GET", path, None, None) {
-            return Ok(cached);
-        }
+        self.get_cached(path, CachePolicy::Default).await
+    }
 
-        // Send the request
-        let request = self.request::<T>(Method::GET, path);
-        let request = self.authenticate_request(request).await?;
-        let response: T = self.send_request(request).await?;
+    /// Send a GET request to the API, caching the response with a policy.
+    pub async fn get_cached<T>(&self, path: &str, policy: CachePolicy) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let request = self
+            .api_request(Method::GET, path)
+            .with_cache_policy(policy);
+        self.execute(request).await
+    }
 
-        // Cache the response
-        cache.set(
-            "GET",
-            path,
-            None,
-            None,
-            response.clone(),
-            Some(Duration::from_secs(60)),
-        );
+    /// Send a GET request with query parameters, caching the response with the
+    /// default policy.
+    pub async fn get_with_query<T, Q>(&self, path: &str, query: &Q) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+        Q: Serialize,
+    {
+        self.get_cached_with_query(path, query, CachePolicy::Default)
+            .await
+    }
 
-        Ok(response)
+    /// Send a GET request with query parameters, caching the response with a
+    /// policy.
+    ///
+    /// The parameters are serialized into a canonical query string, which is
+    /// part of the cache key.
+    pub async fn get_cached_with_query<T, Q>(
+        &self,
+        path: &str,
+        query: &Q,
+        policy: CachePolicy,
+    ) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+        Q: Serialize,
+    {
+        let request = self
+            .api_request(Method::GET, path)
+            .with_query(to_query_string(query)?)
+            .with_cache_policy(policy);
+        self.execute(request).await
     }
 
     /// Send a POST request to the API.
+    ///
+    /// The response is never cached and the request is never retried, since
+    /// most POSTs change server state.
+    /// Cached responses in the same scope as the path are invalidated.
     pub async fn post<T, B>(&self, path: &str, body: &B) -> WebullResult<T>
     where
         T: DeserializeOwned + Clone + Send + Sync + 'static,
         B: Serialize,
     {
-        // For POST requests, we only cache if the body is cacheable
-        // For simplicity, we'll just serialize the body and use it as part of the cache key
-        let body_str = match serde_json::to_string(body) {
-            Ok(s) => Some(s),
-            Err(_) => None,
-        };
+        let request = self
+            .api_request(Method::POST, path)
+            .with_body(to_json(body)?);
+        self.execute(request).await
+    }
 
-        // Check if we have a cached response
-        if let Some(body_str) = &body_str {
-            let cache = self.cache_manager.get_cache::<T>("post");
-            if let Some(cached) = cache.get("POST", path, None, Some(body_str)) {
-                return Ok(cached);
-            }
-        }
+    /// Send a POST request that is safe to repeat, such as an order carrying a
+    /// client order ID.
+    ///
+    /// The response is never cached, but the request is retried on transient
+    /// failures.
+    /// Cached responses in the same scope as the path are invalidated.
+    pub async fn post_idempotent<T, B>(&self, path: &str, body: &B) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+        B: Serialize,
+    {
+        let request = self
+            .api_request(Method::POST, path)
+            .with_body(to_json(body)?)
+            .with_idempotent(true);
+        self.execute(request).await
+    }
 
-        // Send the request
-        let request = self.request::<T>(Method::POST, path).json(body);
-        let request = self.authenticate_request(request).await?;
-        let response: T = self.send_request(request).await?;
-
-        // Cache the response if the body is cacheable
-        if let Some(body_str) = body_str {
-            let cache = self.cache_manager.get_cache::<T>("post");
-            cache.set(
-                "POST",
-                path,
-                None,
-                Some(&body_str),
-                response.clone(),
-                Some(Duration::from_secs(60)),
-            );
-        }
+    /// Send a read-only POST request to the API, such as an order preview.
+    ///
+    /// The response is never cached, but the request is retried on transient
+    /// failures and leaves cached responses alone.
+    pub async fn post_read_only<T, B>(&self, path: &str, body: &B) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+        B: Serialize,
+    {
+        self.post_cached(path, body, CachePolicy::Never).await
+    }
 
-        Ok(response)
+    /// Send a read-only POST request to the API, caching the response with a policy.
+    ///
+    /// The serialized body is part of the cache key. Cached responses are
+    /// never invalidated by the request, and it waits for rate limit budget
+    /// at normal priority even in the trade category.
+    pub async fn post_cached<T, B>(
+        &self,
+        path: &str,
+        body: &B,
+        policy: CachePolicy,
+    ) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+        B: Serialize,
+    {
+        let request = self
+            .api_request(Method::POST, path)
+            .with_body(to_json(body)?)
+            .with_idempotent(true)
+            .with_cache_policy(policy)
+            .with_invalidates(false)
+            .with_priority(Priority::Normal);
+        self.execute(request).await
     }
 
     /// Send a PUT request to the API.
+    ///
+    /// Cached responses in the same scope as the path are invalidated.
     pub async fn put<T, B>(&self, path: &str, body: &B) -> WebullResult<T>
     where
         T: DeserializeOwned + Clone + Send + Sync + 'static,
         B: Serialize,
     {
-        // For PUT requests, we don't cache the response, but we invalidate any cached GET responses
-        // for the same path
-
-        // Send the request
-        let request = self.request::<T>(Method::PUT, path).json(body);
-        let request = self.authenticate_request(request).await?;
-        let response: T = self.send_request(request).await?;
-
-        // Invalidate any cached GET responses for this path
-        let get_cache = self.cache_manager.get_cache::<T>("get");
-        get_cache.clear();
-
-        Ok(response)
+        let request = self
+            .api_request(Method::PUT, path)
+            .with_body(to_json(body)?);
+        self.execute(request).await
     }
 
     /// Send a DELETE request to the API.
+    ///
+    /// Cached responses in the same scope as the path are invalidated.
     pub async fn delete<T>(&self, path: &str) -> WebullResult<T>
     where
         T: DeserializeOwned + Clone + Send + Sync + 'static,
     {
-        // For DELETE requests, we don't cache the response, but we invalidate any cached responses
-        // for the same path
+        self.execute(self.api_request(Method::DELETE, path)).await
+    }
 
-        // Send the request
-        let request = self.request::<T>(Method::DELETE, path);
-        let request = self.authenticate_request(request).await?;
-        let response: T = self.send_request(request).await?;
+    /// Invalidate cached responses under a path prefix.
+    pub fn invalidate(&self, prefix: &str) {
+        self.cache_manager.invalidate_prefix(prefix);
+    }
 
-        // Invalidate any cached responses for this path
-        let get_cache = self.cache_manager.get_cache::<T>("get");
-        get_cache.clear();
+    /// Invalidate cached responses in the same scope as a path.
+    ///
+    /// The scope is the first segment after `/api` (and `/paper`), so a change
+    /// to `/api/trade/cancel/1` invalidates everything under `/api/trade`.
+    /// Trading paths also invalidate the account, asset and position scopes.
+    pub fn invalidate_scope(&self, path: &str) {
+        for scope in invalidation_scopes(path) {
+            self.invalidate(&scope);
+        }
+    }
 
-        let post_cache = self.cache_manager.get_cache::<T>("post");
-        post_cache.clear();
+    /// Create a request for an API path.
+    pub fn api_request(&self, method: Method, path: &str) -> ApiRequest {
+        ApiRequest::new(method, path, self.build_url(path))
+    }
 
-        Ok(response)
+    /// Send a request through the middleware chain and parse the response.
+    pub async fn execute<T>(&self, request: ApiRequest) -> WebullResult<T>
+    where
+        T: DeserializeOwned + Clone,
+    {
+        let trading = RateCategory::from_path(&request.path) == RateCategory::Trade;
+        let result = match self.chain().run(request).await {
+            Ok(response) => parse_response(response),
+            Err(error) => Err(error),
+        };
+
+        // Only responses from the server say anything about the trade token
+        if trading {
+            let state = match &result {
+                Ok(_) => Some(TradeTokenState::Valid {
+                    checked_at: chrono::Utc::now(),
+                }),
+                Err(error) if error.kind() == ErrorKind::TradeTokenExpired => {
+                    Some(TradeTokenState::Expired {
+                        at: chrono::Utc::now(),
+                    })
+                }
+                Err(_) => None,
+            };
+            if let Some(state) = state {
+                *self.trade_token.lock().unwrap() = state;
+            }
+        }
+        result
+    }
+
+    /// Build the middleware chain: tracing, cache, single-flight, retry, circuit
+    /// breaker, rate limit and authentication, followed by any custom layers,
+    /// response metadata capture, the cassette, if any, clock skew measurement
+    /// and failover, if any.
+    fn chain(&self) -> Next {
+        let mut layers: Vec<Arc<dyn Middleware>> = vec![
+            Arc::new(TraceLayer::new(self.metrics.clone())),
+            Arc::new(
+                CacheLayer::new(self.cache_manager.clone()).with_metrics(self.metrics.clone()),
+            ),
+            self.single_flight.clone(),
+            Arc::new(
+                RetryLayer::new(self.retry_policy.clone(), self.rate_limiter.clone())
+                    .with_metrics(self.metrics.clone()),
+            ),
+        ];
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            layers.push(Arc::new(CircuitBreakerLayer::new(circuit_breaker.clone())));
+        }
+        layers.push(Arc::new(
+            RateLimitLayer::new(self.rate_limiter.clone()).with_metrics(self.metrics.clone()),
+        ));
+        layers.push(Arc::new(AuthLayer::new(self.auth_manager.clone())));
+        layers.extend(self.middleware.iter().cloned());
+        layers.push(Arc::new(MetaLayer));
+        if let Some(cassette) = &self.cassette {
+            layers.push(Arc::new(CassetteLayer::new(cassette.clone())));
+        }
+        layers.push(Arc::new(ClockLayer::new(self.auth_manager.clock().clone())));
+        if let Some(failover) = &self.failover {
+            layers.push(Arc::new(FailoverLayer::new(failover.clone())));
+        }
+
+        Next::new(layers.into(), self.transport.clone())
+    }
+}
+
+/// Serialize a request body.
+fn to_json<B: Serialize>(body: &B) -> WebullResult<String> {
+    serde_json::to_string(body).map_err(WebullError::SerializationError)
+}
+
+/// Check the status of a response and parse its data.
+fn parse_response<T>(response: HttpResponse) -> WebullResult<T>
+where
+    T: DeserializeOwned + Clone,
+{
+    // Handle HTTP errors
+    if let Some(error) = response.status_error() {
+        return Err(error);
+    }
+
+    // Parse the response
+    let api_response: ApiResponse<T> =
+        serde_json::from_str(&response.body).map_err(WebullError::SerializationError)?;
+
+    // Check for API errors
+    if !api_response.is_success() {
+        return Err(WebullError::ApiError {
+            code: api_response.code.unwrap_or_else(|| "unknown".to_string()),
+            message: api_response
+                .message
+                .unwrap_or_else(|| "Unknown error".to_string()),
+            status: Some(response.status.as_u16()),
+        });
+    }
+
+    // Return the data; a response without data is only valid for unit or
+    // optional results
+    match api_response.data {
+        Some(data) => Ok(data),
+        None => {
+            serde_json::from_value(serde_json::Value::Null).map_err(|_| WebullError::ApiError {
+                code: "no_data".to_string(),
+                message: "Response did not contain data".to_string(),
+                status: Some(response.status.as_u16()),
+            })
+        }
     }
 }
@@ -6,7 +6,9 @@ use crate::models::market::{
     InstrumentParams, NewsArticle, NewsQueryParams, OptionChain, OptionChainQueryParams, Quote,
     SnapshotParams, TimeFrame,
 };
-use reqwest::Client;
+use crate::region::{Capability, Region};
+use crate::transport::HttpTransport;
+use crate::utils::cache::CachePolicy;
 use serde::Serialize;
 use std::sync::Arc;
 
@@ -18,16 +20,34 @@ pub struct MarketDataEndpoints {
 
 impl MarketDataEndpoints {
     /// Create new market data endpoints.
-    pub fn new(client: Client, base_url: String, auth_manager: Arc<AuthManager>) -> Self {
+    pub fn new(
+        transport: Arc<dyn HttpTransport>,
+        base_url: String,
+        auth_manager: Arc<AuthManager>,
+    ) -> Self {
         Self {
-            base: BaseEndpoint::new(client, base_url, auth_manager),
+            base: BaseEndpoint::new(transport, base_url, auth_manager),
         }
     }
 
+    /// Create new endpoints on top of an existing base endpoint.
+    ///
+    /// This lets several endpoint groups share one HTTP client, rate limiter
+    /// and cache.
+    pub fn from_base(base: BaseEndpoint) -> Self {
+        Self { base }
+    }
+
+    /// Set the Webull region used for region-specific behaviour.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.base = self.base.with_region(region);
+        self
+    }
+
     /// Get a real-time quote for a symbol.
     pub async fn get_quote(&self, symbol: &str) -> WebullResult<Quote> {
         let path = format!("/api/quote/tickerRealTimes/{}", symbol);
-        self.base.get(&path).await
+        self.base.get_cached(&path, CachePolicy::REALTIME).await
     }
 
     /// Get real-time quotes for multiple symbols.
@@ -41,12 +61,20 @@ impl MarketDataEndpoints {
             symbols: symbols.to_vec(),
         };
 
-        self.base.post("/api/quote/tickerRealTimes", &request).await
+        self.base
+            .get_cached_with_query(
+                "/api/quote/tickerRealTimes",
+                &request,
+                CachePolicy::REALTIME,
+            )
+            .await
     }
 
     /// Get snapshot data for symbols.
     pub async fn get_snapshot(&self, params: &SnapshotParams) -> WebullResult<Vec<Quote>> {
-        self.base.post("/api/quote/snapshot", params).await
+        self.base
+            .get_cached_with_query("/api/quote/snapshot", params, CachePolicy::REALTIME)
+            .await
     }
 
     /// Helper method to get snapshot for a single stock symbol.
@@ -63,7 +91,9 @@ impl MarketDataEndpoints {
 
     /// Get historical bar data for a symbol.
     pub async fn get_history_bar(&self, params: &BarQueryParams) -> WebullResult<Vec<Bar>> {
-        self.base.post("/api/quote/history/bars", params).await
+        self.base
+            .get_cached_with_query("/api/quote/history/bars", params, CachePolicy::HISTORICAL)
+            .await
     }
 
     /// Get option chain for a symbol.
@@ -71,22 +101,45 @@ impl MarketDataEndpoints {
         &self,
         params: &OptionChainQueryParams,
     ) -> WebullResult<OptionChain> {
-        self.base.post("/api/options/list", params).await
+        self.base
+            .ensure_supported(Capability::Options, "get_option_chain")?;
+        self.base
+            .get_cached_with_query("/api/options/list", params, CachePolicy::REALTIME)
+            .await
     }
 
     /// Get market news.
     pub async fn get_news(&self, params: &NewsQueryParams) -> WebullResult<Vec<NewsArticle>> {
-        self.base.post("/api/securities/news/list", params).await
+        self.base
+            .get_cached_with_query("/api/securities/news/list", params, CachePolicy::Default)
+            .await
     }
 
-    /// Get market calendar.
+    /// Get the market calendar for the region's home market.
     pub async fn get_market_calendar(&self) -> WebullResult<Vec<String>> {
-        self.base.get("/api/securities/financial/calendar").await
+        #[derive(Serialize)]
+        struct CalendarRequest<'a> {
+            market: &'a str,
+        }
+
+        let request = CalendarRequest {
+            market: self.base.region().code(),
+        };
+
+        self.base
+            .get_cached_with_query(
+                "/api/securities/financial/calendar",
+                &request,
+                CachePolicy::REFERENCE,
+            )
+            .await
     }
 
     /// Get instrument information.
     pub async fn get_instrument(&self, params: &InstrumentParams) -> WebullResult<Vec<Instrument>> {
-        self.base.post("/api/quote/instruments", params).await
+        self.base
+            .get_cached_with_query("/api/quote/instruments", params, CachePolicy::INSTRUMENT)
+            .await
     }
 
     /// Helper method to get instrument information for a single stock symbol.
@@ -103,8 +156,20 @@ impl MarketDataEndpoints {
 
     /// Get end-of-day bars for instruments.
     /// Only available for Webull JP.
+    ///
+    /// Bars up to a past date never change and are kept in the disk tier of
+    /// the cache, if there is one.
     pub async fn get_eod_bar(&self, params: &EodBarsParams) -> WebullResult<Vec<Bar>> {
-        self.base.post("/api/quote/eod/bars", params).await
+        self.base
+            .ensure_supported(Capability::EodBars, "get_eod_bar")?;
+        let policy = if is_past_date(params.date.as_deref()) {
+            CachePolicy::ARCHIVE
+        } else {
+            CachePolicy::HISTORICAL
+        };
+        self.base
+            .get_cached_with_query("/api/quote/eod/bars", params, policy)
+            .await
     }
 
     /// Helper method to get end-of-day bars for an instrument.
@@ -130,11 +195,23 @@ impl MarketDataEndpoints {
 
     /// Get corporate actions for instruments.
     /// Only available for Webull JP.
+    ///
+    /// Actions in a range that ended before today never change and are kept
+    /// in the disk tier of the cache, if there is one.
     pub async fn get_corp_action(
         &self,
         params: &CorpActionParams,
     ) -> WebullResult<Vec<Instrument>> {
-        self.base.post("/api/quote/corp/action", params).await
+        self.base
+            .ensure_supported(Capability::CorporateActions, "get_corp_action")?;
+        let policy = if is_past_date(params.end_date.as_deref()) {
+            CachePolicy::ARCHIVE
+        } else {
+            CachePolicy::REFERENCE
+        };
+        self.base
+            .get_cached_with_query("/api/quote/corp/action", params, policy)
+            .await
     }
 
     /// Helper method to get stock split corporate actions for an instrument.
@@ -191,3 +268,61 @@ impl MarketDataEndpoints {
         self.get_history_bar(&params).await
     }
 }
+
+/// Check if a `yyyy-MM-dd` date is before today in UTC.
+fn is_past_date(date: Option<&str>) -> bool {
+    date.and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .is_some_and(|date| date < chrono::Utc::now().date_naive())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::market::EodBarsParams;
+    use crate::transport::testing::FakeTransport;
+    use crate::{Region, WebullClient, WebullError};
+    use reqwest::Method;
+
+    #[tokio::test]
+    async fn region_only_endpoints_fail_fast() {
+        let fake = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .with_region(Region::Us)
+            .build()
+            .unwrap();
+        let params = EodBarsParams::new("913256135", 5);
+
+        match client.market_data().get_eod_bar(&params).await {
+            Err(WebullError::UnsupportedInRegion { region, .. }) => assert_eq!(region, Region::Us),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(fake.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_endpoints_send_sorted_query_strings() {
+        let fake = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+
+        let quotes = client
+            .market_data()
+            .get_stock_snapshots(&["MSFT", "AAPL"])
+            .await
+            .unwrap();
+        assert!(quotes.is_empty());
+
+        let sent = fake.sent();
+        let snapshot = sent.last().unwrap();
+        assert_eq!(snapshot.method, Method::GET);
+        assert_eq!(snapshot.url.path(), "/api/quote/snapshot");
+        assert_eq!(
+            snapshot.url.query(),
+            Some("category=STK&symbols=MSFT%2CAAPL")
+        );
+        assert!(snapshot.body.is_none());
+    }
+}
@@ -5,7 +5,9 @@ use crate::models::order::{
     OptionOrderPreviewRequest, OptionOrderPreviewResponse, OptionOrderRequest, Order,
     OrderQueryParams, OrderRequest, OrderResponse,
 };
-use reqwest::Client;
+use crate::region::{Capability, Region};
+use crate::transport::HttpTransport;
+use crate::utils::cache::CachePolicy;
 use std::sync::Arc;
 
 /// Endpoints for order operations.
@@ -16,42 +18,83 @@ pub struct OrderEndpoints {
 
 impl OrderEndpoints {
     /// Create new order endpoints.
-    pub fn new(client: Client, base_url: String, auth_manager: Arc<AuthManager>) -> Self {
+    pub fn new(
+        transport: Arc<dyn HttpTransport>,
+        base_url: String,
+        auth_manager: Arc<AuthManager>,
+    ) -> Self {
         Self {
-            base: BaseEndpoint::new(client, base_url, auth_manager),
+            base: BaseEndpoint::new(transport, base_url, auth_manager),
         }
     }
 
+    /// Create new endpoints on top of an existing base endpoint.
+    ///
+    /// This lets several endpoint groups share one HTTP client, rate limiter
+    /// and cache.
+    pub fn from_base(base: BaseEndpoint) -> Self {
+        Self { base }
+    }
+
+    /// Set the Webull region used for region-specific behaviour.
+    pub fn with_region(mut self, region: Region) -> Self {
+        self.base = self.base.with_region(region);
+        self
+    }
+
+    /// Route requests to the paper-trading order API.
+    pub fn with_paper_trading(mut self, paper_trading: bool) -> Self {
+        self.base = self.base.with_paper_trading(paper_trading);
+        self
+    }
+
     /// Place an order.
+    ///
+    /// The submission is only retried on transient failures if the order has a
+    /// client order ID, which keeps a retried submission from placing a second order.
     pub async fn place_order(&self, order: &OrderRequest) -> WebullResult<OrderResponse> {
-        self.base.post("/api/trade/order", order).await
+        let path = self.base.trading_path("/api/trade/order");
+        if order.client_order_id.is_some() {
+            self.base.post_idempotent(&path, order).await
+        } else {
+            self.base.post(&path, order).await
+        }
     }
 
     /// Cancel an order.
     pub async fn cancel_order(&self, order_id: &str) -> WebullResult<()> {
-        let path = format!("/api/trade/cancel/{}", order_id);
+        let path = self
+            .base
+            .trading_path(&format!("/api/trade/cancel/{}", order_id));
         self.base.delete(&path).await
     }
 
     /// Get an order by ID.
     pub async fn get_order(&self, order_id: &str) -> WebullResult<Order> {
-        let path = format!("/api/trade/order/{}", order_id);
-        self.base.get(&path).await
+        let path = self
+            .base
+            .trading_path(&format!("/api/trade/order/{}", order_id));
+        self.base.get_cached(&path, CachePolicy::Never).await
     }
 
     /// Get orders based on query parameters.
     pub async fn get_orders(&self, params: &OrderQueryParams) -> WebullResult<Vec<Order>> {
-        self.base.post("/api/trade/orders", params).await
+        let path = self.base.trading_path("/api/trade/orders");
+        self.base
+            .get_cached_with_query(&path, params, CachePolicy::Never)
+            .await
     }
 
     /// Get active orders.
     pub async fn get_active_orders(&self) -> WebullResult<Vec<Order>> {
-        self.base.get("/api/trade/active").await
+        let path = self.base.trading_path("/api/trade/active");
+        self.base.get_cached(&path, CachePolicy::Never).await
     }
 
     /// Get filled orders.
     pub async fn get_filled_orders(&self) -> WebullResult<Vec<Order>> {
-        self.base.get("/api/trade/filled").await
+        let path = self.base.trading_path("/api/trade/filled");
+        self.base.get_cached(&path, CachePolicy::Never).await
     }
 
     /// Modify an existing order.
@@ -60,14 +103,19 @@ impl OrderEndpoints {
         order_id: &str,
         order: &OrderRequest,
     ) -> WebullResult<OrderResponse> {
-        let path = format!("/api/trade/modify/{}", order_id);
+        let path = self
+            .base
+            .trading_path(&format!("/api/trade/modify/{}", order_id));
         self.base.put(&path, order).await
     }
 
     /// Get open orders for an account.
     pub async fn get_open_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
-        let path = format!("/api/trade/account/{}/orders/open", account_id);
-        self.base.get(&path).await
+        self.base.check_account(account_id).await?;
+        let path = self
+            .base
+            .trading_path(&format!("/api/trade/account/{}/orders/open", account_id));
+        self.base.get_cached(&path, CachePolicy::Never).await
     }
 
     /// Get open orders for an account with pagination.
@@ -77,6 +125,8 @@ impl OrderEndpoints {
         page_size: u32,
         last_order_id: Option<&str>,
     ) -> WebullResult<Vec<Order>> {
+        self.base.check_account(account_id).await?;
+
         #[derive(serde::Serialize)]
         struct OpenOrdersRequest {
             account_id: String,
@@ -95,13 +145,19 @@ impl OrderEndpoints {
             request.last_client_order_id = Some(order_id.to_string());
         }
 
-        self.base.post("/api/trade/orders/open", &request).await
+        let path = self.base.trading_path("/api/trade/orders/open");
+        self.base
+            .get_cached_with_query(&path, &request, CachePolicy::Never)
+            .await
     }
 
     /// Get today's orders for an account.
     pub async fn get_today_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
-        let path = format!("/api/trade/account/{}/orders/today", account_id);
-        self.base.get(&path).await
+        self.base.check_account(account_id).await?;
+        let path = self
+            .base
+            .trading_path(&format!("/api/trade/account/{}/orders/today", account_id));
+        self.base.get_cached(&path, CachePolicy::Never).await
     }
 
     /// Get today's orders for an account with pagination.
@@ -111,6 +167,8 @@ impl OrderEndpoints {
         page_size: u32,
         last_order_id: Option<&str>,
     ) -> WebullResult<Vec<Order>> {
+        self.base.check_account(account_id).await?;
+
         #[derive(serde::Serialize)]
         struct TodayOrdersRequest {
             account_id: String,
@@ -129,7 +187,10 @@ impl OrderEndpoints {
             request.last_client_order_id = Some(order_id.to_string());
         }
 
-        self.base.post("/api/trade/orders/today", &request).await
+        let path = self.base.trading_path("/api/trade/orders/today");
+        self.base
+            .get_cached_with_query(&path, &request, CachePolicy::Never)
+            .await
     }
 
     /// Preview an option order.
@@ -138,8 +199,11 @@ impl OrderEndpoints {
         preview_request: &OptionOrderPreviewRequest,
     ) -> WebullResult<OptionOrderPreviewResponse> {
         self.base
-            .post("/api/trade/option/preview", preview_request)
-            .await
+            .ensure_supported(Capability::Options, "preview_option_order")?;
+        self.base.check_account(&preview_request.account_id).await?;
+
+        let path = self.base.trading_path("/api/trade/option/preview");
+        self.base.post_read_only(&path, preview_request).await
     }
 
     /// Place an option order.
@@ -148,6 +212,10 @@ impl OrderEndpoints {
         account_id: &str,
         orders: &[OptionOrderRequest],
     ) -> WebullResult<Vec<OrderResponse>> {
+        self.base
+            .ensure_supported(Capability::Options, "place_option_order")?;
+        self.base.check_account(account_id).await?;
+
         #[derive(serde::Serialize)]
         struct PlaceOptionRequest<'a> {
             account_id: &'a str,
@@ -159,7 +227,12 @@ impl OrderEndpoints {
             new_orders: orders,
         };
 
-        self.base.post("/api/trade/option/place", &request).await
+        let path = self.base.trading_path("/api/trade/option/place");
+        if has_client_order_ids(orders) {
+            self.base.post_idempotent(&path, &request).await
+        } else {
+            self.base.post(&path, &request).await
+        }
     }
 
     /// Replace an option order.
@@ -168,6 +241,10 @@ impl OrderEndpoints {
         account_id: &str,
         orders: &[OptionOrderRequest],
     ) -> WebullResult<Vec<OrderResponse>> {
+        self.base
+            .ensure_supported(Capability::Options, "replace_option_order")?;
+        self.base.check_account(account_id).await?;
+
         #[derive(serde::Serialize)]
         struct ReplaceOptionRequest<'a> {
             account_id: &'a str,
@@ -179,7 +256,12 @@ impl OrderEndpoints {
             modify_orders: orders,
         };
 
-        self.base.post("/api/trade/option/replace", &request).await
+        let path = self.base.trading_path("/api/trade/option/replace");
+        if has_client_order_ids(orders) {
+            self.base.post_idempotent(&path, &request).await
+        } else {
+            self.base.post(&path, &request).await
+        }
     }
 
     /// Cancel an option order.
@@ -188,6 +270,10 @@ impl OrderEndpoints {
         account_id: &str,
         client_order_id: &str,
     ) -> WebullResult<()> {
+        self.base
+            .ensure_supported(Capability::Options, "cancel_option_order")?;
+        self.base.check_account(account_id).await?;
+
         #[derive(serde::Serialize)]
         struct CancelOptionRequest<'a> {
             account_id: &'a str,
@@ -199,6 +285,57 @@ impl OrderEndpoints {
             client_order_id,
         };
 
-        self.base.post("/api/trade/option/cancel", &request).await
+        let path = self.base.trading_path("/api/trade/option/cancel");
+        self.base.post_idempotent(&path, &request).await
+    }
+}
+
+/// Check if every option order carries a client order ID.
+fn has_client_order_ids(orders: &[OptionOrderRequest]) -> bool {
+    orders.iter().all(|order| !order.client_order_id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::middleware::HttpResponse;
+    use crate::models::order::OptionOrderPreviewRequest;
+    use crate::transport::testing::FakeTransport;
+    use crate::WebullClient;
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn previews_keep_cached_positions() {
+        let fake = FakeTransport::with_handler(|request| match request.url.path() {
+            "/api/account/getSecAccountList" => Some(HttpResponse::new(
+                StatusCode::OK,
+                r#"{"success":true,"data":[{"id":"1001","account_number":"1001","account_type":"MARGIN","status":"ACTIVE","created_at":"2024-01-02T00:00:00Z","currency":"USD","paper_trading":false}]}"#,
+            )),
+            "/api/trade/option/preview" => Some(HttpResponse::new(
+                StatusCode::OK,
+                r#"{"success":true,"data":{"id":"p-1","commission":0.65,"estimated_cost":120.0,"estimated_proceeds":0.0,"buying_power_effect":-120.65,"margin_requirement":0.0}}"#,
+            )),
+            _ => None,
+        });
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+
+        client.accounts().get_positions("1001").await.unwrap();
+        let preview = client
+            .orders()
+            .preview_option_order(&OptionOrderPreviewRequest::new("1001"))
+            .await
+            .unwrap();
+        assert_eq!(preview.id, "p-1");
+        client.accounts().get_positions("1001").await.unwrap();
+
+        let positions = fake
+            .requests()
+            .iter()
+            .filter(|request| request.contains("getUserPositions"))
+            .count();
+        assert_eq!(positions, 1);
     }
 }
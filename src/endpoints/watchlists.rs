@@ -1,7 +1,7 @@
 use crate::auth::AuthManager;
 use crate::endpoints::base::BaseEndpoint;
 use crate::error::WebullResult;
-use reqwest::Client;
+use crate::transport::HttpTransport;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -52,12 +52,24 @@ pub struct WatchlistEndpoints {
 
 impl WatchlistEndpoints {
     /// Create new watchlist endpoints.
-    pub fn new(client: Client, base_url: String, auth_manager: Arc<AuthManager>) -> Self {
+    pub fn new(
+        transport: Arc<dyn HttpTransport>,
+        base_url: String,
+        auth_manager: Arc<AuthManager>,
+    ) -> Self {
         Self {
-            base: BaseEndpoint::new(client, base_url, auth_manager),
+            base: BaseEndpoint::new(transport, base_url, auth_manager),
         }
     }
 
+    /// Create new endpoints on top of an existing base endpoint.
+    ///
+    /// This lets several endpoint groups share one HTTP client, rate limiter
+    /// and cache.
+    pub fn from_base(base: BaseEndpoint) -> Self {
+        Self { base }
+    }
+
     /// Get all watchlists.
     pub async fn get_watchlists(&self) -> WebullResult<Vec<Watchlist>> {
         self.base.get("/api/wlas/watchlist").await
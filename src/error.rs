@@ -1,3 +1,6 @@
+use crate::region::Region;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur when interacting with the Webull API.
@@ -8,8 +11,17 @@ pub enum WebullError {
     AuthenticationError(String),
 
     /// API error with code and message
+    ///
+    /// More fields may be added, so patterns need `..` and errors are built
+    /// with [`WebullError::api_error`] outside this crate.
     #[error("API error: {code} - {message}")]
-    ApiError { code: String, message: String },
+    #[non_exhaustive]
+    ApiError {
+        code: String,
+        message: String,
+        /// HTTP status of the response, if the error came from one
+        status: Option<u16>,
+    },
 
     /// Rate limit exceeded
     #[error("Rate limit exceeded")]
@@ -23,6 +35,18 @@ pub enum WebullError {
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
+    /// Invalid configuration value
+    #[error("Invalid configuration for `{key}`: {message}")]
+    InvalidConfig { key: String, message: String },
+
+    /// Operation not available in the configured region
+    #[error("{operation} is not available in the {region} region")]
+    UnsupportedInRegion { operation: String, region: Region },
+
+    /// Client trading mode does not match the account or operation
+    #[error("Trading mode mismatch: {0}")]
+    TradingModeMismatch(String),
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
@@ -35,10 +59,354 @@ pub enum WebullError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    /// Request still failed after being retried
+    #[error("{source} (after {attempts} attempts)")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<WebullError>,
+    },
+
+    /// Request failed fast because the circuit breaker for its endpoint
+    /// category is open
+    #[error("Circuit breaker is open for {category} requests (retry in {retry_in:?})")]
+    CircuitOpen {
+        category: &'static str,
+        retry_in: Duration,
+    },
+
+    /// The local clock is further from the server clock than allowed
+    #[error("Local clock is {skew_ms} ms off the server clock, more than the allowed {max:?}")]
+    ClockSkew { skew_ms: i64, max: Duration },
+
+    /// Error shared by concurrent identical requests that were sent as one
+    #[error(transparent)]
+    Shared(Arc<WebullError>),
+
     /// Unknown error
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+impl WebullError {
+    /// Create an API error with a code and message, not tied to a response.
+    pub fn api_error(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::ApiError {
+            code: code.into(),
+            message: message.into(),
+            status: None,
+        }
+    }
+
+    /// Create an error for an unsuccessful HTTP response.
+    ///
+    /// Client errors use the business error code and message from a JSON body
+    /// when there is one; otherwise the HTTP status is used as the code.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let value = serde_json::from_str::<serde_json::Value>(body).ok();
+        let field = |names: &[&str]| {
+            let value = value.as_ref()?;
+            names.iter().find_map(|name| match value.get(name)? {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+        };
+
+        match field(&["code", "error_code", "errorCode"]) {
+            Some(code) if (400..500).contains(&status) => Self::ApiError {
+                code,
+                message: field(&["msg", "message", "error_msg"])
+                    .unwrap_or_else(|| body.to_string()),
+                status: Some(status),
+            },
+            _ => Self::ApiError {
+                code: status.to_string(),
+                message: body.to_string(),
+                status: Some(status),
+            },
+        }
+    }
+
+    /// Get the kind of failure, for reacting to errors programmatically.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ApiError { code, status, .. } => ErrorKind::from_code(code, *status),
+            Self::RateLimitExceeded => ErrorKind::RateLimited,
+            Self::AuthenticationError(_) | Self::MfaRequired | Self::Unauthorized => {
+                ErrorKind::Unauthorized
+            }
+            Self::NetworkError(_) => ErrorKind::Network,
+            Self::InvalidRequest(_)
+            | Self::InvalidConfig { .. }
+            | Self::UnsupportedInRegion { .. }
+            | Self::TradingModeMismatch(_) => ErrorKind::InvalidRequest,
+            Self::CircuitOpen { .. } => ErrorKind::CircuitOpen,
+            Self::ClockSkew { .. } => ErrorKind::ClockSkew,
+            Self::RetriesExhausted { source, .. } => source.kind(),
+            Self::Shared(error) => error.kind(),
+            Self::SerializationError(_) | Self::Unknown(_) => ErrorKind::Other,
+        }
+    }
+
+    /// Check if the request may succeed if it is sent again later.
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
+    /// Check if the error requires logging in or refreshing a token.
+    pub fn is_auth(&self) -> bool {
+        self.kind().is_auth()
+    }
+
+    /// Check if the broker rejected an order.
+    pub fn is_order_rejection(&self) -> bool {
+        self.kind().is_order_rejection()
+    }
+
+    /// Get the number of attempts made before the request failed.
+    pub fn attempts(&self) -> u32 {
+        match self {
+            Self::RetriesExhausted { attempts, .. } => *attempts,
+            Self::Shared(error) => error.attempts(),
+            _ => 1,
+        }
+    }
+
+    /// Get the error from the last attempt, looking through retries and
+    /// shared errors.
+    pub fn last_error(&self) -> &WebullError {
+        match self {
+            Self::RetriesExhausted { source, .. } => source.last_error(),
+            Self::Shared(error) => error.last_error(),
+            _ => self,
+        }
+    }
+}
+
+/// Kind of failure reported by the Webull API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Not enough buying power for the order
+    InsufficientBuyingPower,
+
+    /// The market is closed for the order's session
+    MarketClosed,
+
+    /// The price is not a multiple of the instrument's tick size
+    InvalidPriceIncrement,
+
+    /// The order would break the pattern day trader rule
+    PdtRestriction,
+
+    /// The symbol cannot be traded
+    SymbolNotTradable,
+
+    /// The order does not exist
+    OrderNotFound,
+
+    /// The trade token has expired and the trading PIN must be re-entered
+    TradeTokenExpired,
+
+    /// The request was rejected by the server or the client as invalid
+    InvalidRequest,
+
+    /// Too many requests
+    RateLimited,
+
+    /// Not logged in, or the access token is no longer valid
+    Unauthorized,
+
+    /// The request could not be sent or the response could not be read
+    Network,
+
+    /// The server failed to handle the request
+    ServerError,
+
+    /// The request was not sent because the server has been failing
+    CircuitOpen,
+
+    /// The request timestamp was rejected because the local clock is off
+    ClockSkew,
+
+    /// Any other failure
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify a Webull error code, falling back to the HTTP status of the
+    /// response it came with.
+    ///
+    /// Codes are matched exactly after lowercasing and dropping separators, so
+    /// `INSUFFICIENT_BUYING_POWER` and `insufficient-buying-power` map to the
+    /// same kind. A namespaced code such as `trade.insufficient_buying_power`
+    /// also matches on its last segments.
+    pub fn from_code(code: &str, status: Option<u16>) -> Self {
+        Self::from_known_code(code)
+            .or_else(|| status.and_then(Self::from_status))
+            .unwrap_or(Self::Other)
+    }
+
+    /// Classify an HTTP status.
+    ///
+    /// Returns `None` for statuses that do not indicate a failure.
+    pub fn from_status(status: u16) -> Option<Self> {
+        match status {
+            401 | 403 => Some(Self::Unauthorized),
+            429 => Some(Self::RateLimited),
+            400..=499 => Some(Self::InvalidRequest),
+            500..=599 => Some(Self::ServerError),
+            _ => None,
+        }
+    }
+
+    /// Classify a code that is, or ends in, a known Webull error code.
+    fn from_known_code(code: &str) -> Option<Self> {
+        const CODES: &[(&str, ErrorKind)] = &[
+            (
+                "insufficientbuyingpower",
+                ErrorKind::InsufficientBuyingPower,
+            ),
+            ("insufficientfund", ErrorKind::InsufficientBuyingPower),
+            ("insufficientfunds", ErrorKind::InsufficientBuyingPower),
+            ("insufficientbalance", ErrorKind::InsufficientBuyingPower),
+            ("marketclosed", ErrorKind::MarketClosed),
+            ("marketnotopen", ErrorKind::MarketClosed),
+            ("nottradingtime", ErrorKind::MarketClosed),
+            ("outsidetradinghours", ErrorKind::MarketClosed),
+            ("invalidpriceincrement", ErrorKind::InvalidPriceIncrement),
+            ("invalidticksize", ErrorKind::InvalidPriceIncrement),
+            ("invalidpricestep", ErrorKind::InvalidPriceIncrement),
+            ("pdt", ErrorKind::PdtRestriction),
+            ("pdtrestriction", ErrorKind::PdtRestriction),
+            ("patterndaytrader", ErrorKind::PdtRestriction),
+            ("daytradelimit", ErrorKind::PdtRestriction),
+            ("symbolnottradable", ErrorKind::SymbolNotTradable),
+            ("symbolnottradeable", ErrorKind::SymbolNotTradable),
+            ("nottradable", ErrorKind::SymbolNotTradable),
+            ("untradable", ErrorKind::SymbolNotTradable),
+            ("ordernotfound", ErrorKind::OrderNotFound),
+            ("ordernotexist", ErrorKind::OrderNotFound),
+            ("nosuchorder", ErrorKind::OrderNotFound),
+            ("invalidtimestamp", ErrorKind::ClockSkew),
+            ("timestampexpired", ErrorKind::ClockSkew),
+            ("clockskew", ErrorKind::ClockSkew),
+            ("tradetokenexpire", ErrorKind::TradeTokenExpired),
+            ("tradetokenexpired", ErrorKind::TradeTokenExpired),
+            ("tradetokeninvalid", ErrorKind::TradeTokenExpired),
+            ("tokenexpire", ErrorKind::Unauthorized),
+            ("tokenexpired", ErrorKind::Unauthorized),
+            ("unauthorized", ErrorKind::Unauthorized),
+            ("ratelimit", ErrorKind::RateLimited),
+            ("ratelimitexceeded", ErrorKind::RateLimited),
+            ("toomanyrequests", ErrorKind::RateLimited),
+            ("internalerror", ErrorKind::ServerError),
+            ("systemerror", ErrorKind::ServerError),
+            ("serviceunavailable", ErrorKind::ServerError),
+        ];
+
+        // Try the whole code, then drop namespace segments from the front
+        let segments: Vec<&str> = code.split(['.', '/', ':']).collect();
+        (0..segments.len()).find_map(|start| {
+            let candidate = normalize(&segments[start..].concat());
+            CODES
+                .iter()
+                .find(|(known, _)| *known == candidate)
+                .map(|(_, kind)| *kind)
+        })
+    }
+
+    /// Check if the request may succeed if it is sent again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited | Self::Network | Self::ServerError | Self::CircuitOpen
+        )
+    }
+
+    /// Check if the error requires logging in or refreshing a token.
+    pub fn is_auth(&self) -> bool {
+        matches!(self, Self::Unauthorized | Self::TradeTokenExpired)
+    }
+
+    /// Check if the broker rejected an order.
+    pub fn is_order_rejection(&self) -> bool {
+        matches!(
+            self,
+            Self::InsufficientBuyingPower
+                | Self::MarketClosed
+                | Self::InvalidPriceIncrement
+                | Self::PdtRestriction
+                | Self::SymbolNotTradable
+        )
+    }
+}
+
+/// Lowercase text and drop everything but letters and digits.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
 /// Result type for Webull API operations
 pub type WebullResult<T> = Result<T, WebullError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn business_codes_map_to_kinds() {
+        let error = WebullError::from_response(
+            400,
+            r#"{"error_code":"INSUFFICIENT_BUYING_POWER","message":"Buying power is insufficient"}"#,
+        );
+        assert_eq!(error.kind(), ErrorKind::InsufficientBuyingPower);
+        assert!(error.is_order_rejection());
+        assert!(!error.is_retryable());
+
+        let error = WebullError::api_error("trade.token.expire", "Trade token expired");
+        assert_eq!(error.kind(), ErrorKind::TradeTokenExpired);
+        assert!(error.is_auth());
+
+        let error = WebullError::from_response(417, r#"{"code":"INVALID_TIMESTAMP"}"#);
+        assert_eq!(error.kind(), ErrorKind::ClockSkew);
+
+        let error = WebullError::RetriesExhausted {
+            attempts: 3,
+            source: Box::new(WebullError::from_response(503, "Service Unavailable")),
+        };
+        assert_eq!(error.kind(), ErrorKind::ServerError);
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn unknown_codes_fall_back_to_the_status() {
+        // Messages are never matched, however suggestive
+        let error = WebullError::from_response(
+            400,
+            r#"{"code":"INVALID_PARAMETER","msg":"start timestamp must be before end timestamp"}"#,
+        );
+        assert_eq!(error.kind(), ErrorKind::InvalidRequest);
+
+        let error = WebullError::from_response(
+            404,
+            r#"{"code":"QUOTE_NOT_FOUND","msg":"No quote for SPDT"}"#,
+        );
+        assert_eq!(error.kind(), ErrorKind::InvalidRequest);
+
+        // Codes only match whole known codes, not words inside them
+        let error = WebullError::from_response(400, r#"{"code":"BAR_TIMESTAMP_OUT_OF_RANGE"}"#);
+        assert_eq!(error.kind(), ErrorKind::InvalidRequest);
+        let error = WebullError::from_response(400, r#"{"code":"SPDT_NOT_SHORTABLE"}"#);
+        assert_eq!(error.kind(), ErrorKind::InvalidRequest);
+
+        let error = WebullError::from_response(429, r#"{"code":"QUOTA_USED_UP"}"#);
+        assert_eq!(error.kind(), ErrorKind::RateLimited);
+
+        let error = WebullError::api_error("UNKNOWN_FAILURE", "Pattern day trader rule");
+        assert_eq!(error.kind(), ErrorKind::Other);
+    }
+}
@@ -0,0 +1,826 @@
+//! In-memory fake of the Webull API.
+//!
+//! [`FakeWebull`] implements the traits in [`crate::api`] from seeded data,
+//! so code written against them can be unit tested without HTTP or a server:
+//!
+//! ```
+//! # async fn example() -> webull_rs::WebullResult<()> {
+//! use webull_rs::api::{MarketDataApi, OrderApi};
+//! use webull_rs::fake::FakeWebull;
+//! use webull_rs::models::order::{OrderRequest, OrderSide, OrderStatus};
+//!
+//! let fake = FakeWebull::builder().with_price("AAPL", 190.0).build();
+//!
+//! assert_eq!(fake.get_quote("AAPL").await?.last_price, 190.0);
+//! let order = OrderRequest::market()
+//!     .symbol("AAPL")
+//!     .quantity(1.0)
+//!     .side(OrderSide::Buy);
+//! let placed = fake.place_order(&order).await?;
+//! assert_eq!(placed.status, OrderStatus::New);
+//! assert_eq!(fake.orders().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The fake keeps no ledger: placed orders stay [`OrderStatus::New`] until
+//! they are changed with [`FakeWebull::set_order_status`], and balances and
+//! positions only change when they are seeded again. Orders are not tied to
+//! accounts, so the account-scoped order queries return orders from all of
+//! them. Option trading is not supported, and news, instruments, end-of-day
+//! bars, corporate actions and the market calendar are always empty.
+
+use crate::api::{AccountApi, MarketDataApi, OrderApi, WatchlistApi};
+use crate::endpoints::watchlists::{CreateWatchlistRequest, ModifyWatchlistRequest, Watchlist};
+use crate::error::{WebullError, WebullResult};
+use crate::models::account::{
+    Account, AccountBalance, AccountProfile, BalanceParams, PaperAccountResetRequest,
+    PaperFundingRequest, Position, PositionParams, TradeHistory,
+};
+use crate::models::market::{
+    Bar, BarQueryParams, CorpActionParams, EodBarsParams, Instrument, InstrumentParams,
+    NewsArticle, NewsQueryParams, OptionChain, OptionChainQueryParams, Quote, SnapshotParams,
+};
+use crate::models::order::{
+    OptionOrderPreviewRequest, OptionOrderPreviewResponse, OptionOrderRequest, Order,
+    OrderQueryParams, OrderRequest, OrderResponse, OrderStatus,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Seeded data and orders of a [`FakeWebull`].
+#[derive(Default)]
+struct FakeState {
+    accounts: Vec<Account>,
+    balances: HashMap<String, AccountBalance>,
+    positions: HashMap<String, Vec<Position>>,
+    trades: HashMap<String, Vec<TradeHistory>>,
+    quotes: BTreeMap<String, Quote>,
+    bars: HashMap<String, Vec<Bar>>,
+    orders: Vec<Order>,
+    client_order_ids: HashMap<String, String>,
+    watchlists: BTreeMap<String, Watchlist>,
+    next_id: u64,
+}
+
+impl FakeState {
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("FAKE-{}-{}", prefix, self.next_id)
+    }
+
+    fn account(&self, account_id: &str) -> WebullResult<&Account> {
+        self.accounts
+            .iter()
+            .find(|account| account.id == account_id)
+            .ok_or_else(|| {
+                api_error(
+                    "ACCOUNT_NOT_FOUND",
+                    format!("Account {} not found", account_id),
+                )
+            })
+    }
+
+    fn balance(&self, account_id: &str) -> WebullResult<AccountBalance> {
+        self.account(account_id)?;
+        self.balances.get(account_id).cloned().ok_or_else(|| {
+            api_error(
+                "BALANCE_NOT_FOUND",
+                format!("No balance seeded for account {}", account_id),
+            )
+        })
+    }
+
+    fn positions(&self, account_id: &str) -> WebullResult<Vec<Position>> {
+        self.account(account_id)?;
+        Ok(self.positions.get(account_id).cloned().unwrap_or_default())
+    }
+
+    fn trades(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>> {
+        self.account(account_id)?;
+        Ok(self.trades.get(account_id).cloned().unwrap_or_default())
+    }
+
+    fn order_mut(&mut self, order_id: &str) -> WebullResult<&mut Order> {
+        self.orders
+            .iter_mut()
+            .find(|order| order.id == order_id)
+            .ok_or_else(|| order_not_found(order_id))
+    }
+
+    fn open_order_mut(&mut self, order_id: &str) -> WebullResult<&mut Order> {
+        let order = self.order_mut(order_id)?;
+        if order.status.is_open() {
+            Ok(order)
+        } else {
+            Err(api_error(
+                "ORDER_NOT_OPEN",
+                format!("Order {} is no longer open", order_id),
+            ))
+        }
+    }
+
+    fn orders_where(&self, filter: impl Fn(&Order) -> bool) -> Vec<Order> {
+        self.orders
+            .iter()
+            .filter(|order| filter(order))
+            .cloned()
+            .collect()
+    }
+
+    fn watchlist_mut(&mut self, watchlist_id: &str) -> WebullResult<&mut Watchlist> {
+        self.watchlists
+            .get_mut(watchlist_id)
+            .ok_or_else(|| watchlist_not_found(watchlist_id))
+    }
+}
+
+/// Builder for a [`FakeWebull`] with seeded data.
+#[derive(Default)]
+pub struct FakeWebullBuilder {
+    state: FakeState,
+}
+
+impl FakeWebullBuilder {
+    /// Create a builder with no data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an account.
+    pub fn with_account(mut self, account: Account) -> Self {
+        self.state.accounts.push(account);
+        self
+    }
+
+    /// Set the balance of an account.
+    pub fn with_balance(mut self, account_id: &str, balance: AccountBalance) -> Self {
+        self.state.balances.insert(account_id.to_string(), balance);
+        self
+    }
+
+    /// Add a position to an account.
+    pub fn with_position(mut self, account_id: &str, position: Position) -> Self {
+        self.state
+            .positions
+            .entry(account_id.to_string())
+            .or_default()
+            .push(position);
+        self
+    }
+
+    /// Add a trade to an account's history.
+    pub fn with_trade(mut self, account_id: &str, trade: TradeHistory) -> Self {
+        self.state
+            .trades
+            .entry(account_id.to_string())
+            .or_default()
+            .push(trade);
+        self
+    }
+
+    /// Add or replace a quote.
+    pub fn with_quote(mut self, quote: Quote) -> Self {
+        self.state.quotes.insert(quote.symbol.clone(), quote);
+        self
+    }
+
+    /// Add or replace a quote with only a last price.
+    ///
+    /// The bid and ask are a cent either side of the price, and the other
+    /// prices equal it.
+    pub fn with_price(self, symbol: &str, price: f64) -> Self {
+        self.with_quote(quote_at(symbol, price))
+    }
+
+    /// Set the historical bars of a symbol, most recent first.
+    pub fn with_bars(mut self, symbol: &str, bars: Vec<Bar>) -> Self {
+        self.state.bars.insert(symbol.to_string(), bars);
+        self
+    }
+
+    /// Add an existing order.
+    pub fn with_order(mut self, order: Order) -> Self {
+        self.state.orders.push(order);
+        self
+    }
+
+    /// Add a watchlist.
+    pub fn with_watchlist(mut self, watchlist: Watchlist) -> Self {
+        self.state
+            .watchlists
+            .insert(watchlist.id.clone(), watchlist);
+        self
+    }
+
+    /// Build the fake.
+    pub fn build(self) -> FakeWebull {
+        FakeWebull {
+            state: Arc::new(Mutex::new(self.state)),
+        }
+    }
+}
+
+/// In-memory fake of the Webull API.
+///
+/// Clones share their data, so a test can keep one to inspect what the code
+/// under test did with another.
+#[derive(Clone)]
+pub struct FakeWebull {
+    state: Arc<Mutex<FakeState>>,
+}
+
+impl FakeWebull {
+    /// Create a builder for seeding the fake.
+    pub fn builder() -> FakeWebullBuilder {
+        FakeWebullBuilder::new()
+    }
+
+    /// Get all orders, seeded and placed, in order.
+    pub fn orders(&self) -> Vec<Order> {
+        self.state().orders.clone()
+    }
+
+    /// Change the status of an order, e.g. to simulate a fill.
+    ///
+    /// Filling an order also sets its filled quantity.
+    pub fn set_order_status(&self, order_id: &str, status: OrderStatus) -> WebullResult<Order> {
+        let mut state = self.state();
+        let order = state.order_mut(order_id)?;
+        order.status = status;
+        if status == OrderStatus::Filled {
+            order.filled_quantity = order.quantity;
+        }
+        order.updated_at = Utc::now();
+        Ok(order.clone())
+    }
+
+    /// Add or replace a quote.
+    pub fn set_quote(&self, quote: Quote) {
+        self.state().quotes.insert(quote.symbol.clone(), quote);
+    }
+
+    /// Set the last price of a symbol, keeping the rest of its quote.
+    pub fn set_price(&self, symbol: &str, price: f64) {
+        let mut state = self.state();
+        match state.quotes.get_mut(symbol) {
+            Some(quote) => {
+                quote.last_price = price;
+                quote.timestamp = Utc::now();
+            }
+            None => {
+                state
+                    .quotes
+                    .insert(symbol.to_string(), quote_at(symbol, price));
+            }
+        }
+    }
+
+    /// Set the balance of an account.
+    pub fn set_balance(&self, account_id: &str, balance: AccountBalance) {
+        self.state()
+            .balances
+            .insert(account_id.to_string(), balance);
+    }
+
+    /// Replace the positions of an account.
+    pub fn set_positions(&self, account_id: &str, positions: Vec<Position>) {
+        self.state()
+            .positions
+            .insert(account_id.to_string(), positions);
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl AccountApi for FakeWebull {
+    async fn get_accounts(&self) -> WebullResult<Vec<Account>> {
+        Ok(self.state().accounts.clone())
+    }
+
+    async fn get_account(&self, account_id: &str) -> WebullResult<Account> {
+        self.state().account(account_id).cloned()
+    }
+
+    async fn get_account_balance(&self, account_id: &str) -> WebullResult<AccountBalance> {
+        self.state().balance(account_id)
+    }
+
+    async fn get_positions(&self, account_id: &str) -> WebullResult<Vec<Position>> {
+        self.state().positions(account_id)
+    }
+
+    async fn get_position(&self, account_id: &str, symbol: &str) -> WebullResult<Position> {
+        self.state()
+            .positions(account_id)?
+            .into_iter()
+            .find(|position| position.symbol == symbol)
+            .ok_or_else(|| {
+                api_error(
+                    "POSITION_NOT_FOUND",
+                    format!("No {} position in account {}", symbol, account_id),
+                )
+            })
+    }
+
+    async fn get_trade_history(&self, account_id: &str) -> WebullResult<Vec<TradeHistory>> {
+        self.state().trades(account_id)
+    }
+
+    async fn get_trade_history_paged(
+        &self,
+        account_id: &str,
+        page: u32,
+        page_size: u32,
+    ) -> WebullResult<Vec<TradeHistory>> {
+        let size = page_size as usize;
+        let start = (page.max(1) as usize - 1) * size;
+        let trades = self.state().trades(account_id)?;
+        Ok(trades.into_iter().skip(start).take(size).collect())
+    }
+
+    async fn get_account_profile(&self, account_id: &str) -> WebullResult<AccountProfile> {
+        let state = self.state();
+        let account = state.account(account_id)?;
+        Ok(AccountProfile {
+            id: account.id.clone(),
+            account_number: account.account_number.clone(),
+            account_type: account.account_type,
+            status: account.status,
+            region: account.region.clone().unwrap_or_default(),
+            name: account.name.clone().unwrap_or_default(),
+            email: account.email.clone(),
+            phone: account.phone.clone(),
+            currency: account.currency.clone(),
+            paper_trading: account.paper_trading,
+            created_at: account.created_at,
+            kyc_status: None,
+            risk_level: None,
+            permissions: None,
+        })
+    }
+
+    async fn get_balance(&self, params: &BalanceParams) -> WebullResult<AccountBalance> {
+        self.state().balance(&params.account_id)
+    }
+
+    async fn get_balance_with_default_currency(
+        &self,
+        account_id: &str,
+    ) -> WebullResult<AccountBalance> {
+        self.state().balance(account_id)
+    }
+
+    async fn get_positions_with_params(
+        &self,
+        params: &PositionParams,
+    ) -> WebullResult<Vec<Position>> {
+        let positions = self.state().positions(&params.account_id)?;
+        let start = match &params.last_instrument_id {
+            Some(last) => positions
+                .iter()
+                .position(|position| &position.instrument_id == last)
+                .map_or(positions.len(), |index| index + 1),
+            None => 0,
+        };
+        Ok(positions
+            .into_iter()
+            .skip(start)
+            .take(params.page_size as usize)
+            .collect())
+    }
+
+    async fn reset_paper_account(
+        &self,
+        request: &PaperAccountResetRequest,
+    ) -> WebullResult<AccountBalance> {
+        let mut state = self.state();
+        let currency = state.account(&request.account_id)?.currency.clone();
+        let cash = request.initial_balance;
+        let balance = AccountBalance {
+            cash,
+            buying_power: cash,
+            market_value: 0.0,
+            total_value: cash,
+            unrealized_profit_loss: 0.0,
+            unrealized_profit_loss_percentage: 0.0,
+            currency,
+            settled_cash: Some(cash),
+            unsettled_cash: Some(0.0),
+            withdrawable_cash: Some(cash),
+            tradable_cash: Some(cash),
+            margin_buying_power: None,
+            option_buying_power: None,
+            day_trading_buying_power: None,
+        };
+
+        state.positions.remove(&request.account_id);
+        state.trades.remove(&request.account_id);
+        state
+            .balances
+            .insert(request.account_id.clone(), balance.clone());
+        Ok(balance)
+    }
+
+    async fn fund_paper_account(
+        &self,
+        request: &PaperFundingRequest,
+    ) -> WebullResult<AccountBalance> {
+        let mut state = self.state();
+        let mut balance = state.balance(&request.account_id)?;
+        balance.cash += request.amount;
+        balance.buying_power += request.amount;
+        balance.total_value += request.amount;
+
+        state
+            .balances
+            .insert(request.account_id.clone(), balance.clone());
+        Ok(balance)
+    }
+}
+
+#[async_trait]
+impl OrderApi for FakeWebull {
+    async fn place_order(&self, order: &OrderRequest) -> WebullResult<OrderResponse> {
+        let mut state = self.state();
+
+        // A resubmitted client order ID returns the original order
+        if let Some(client_order_id) = &order.client_order_id {
+            if let Some(order_id) = state.client_order_ids.get(client_order_id).cloned() {
+                return state.order_mut(&order_id).map(|order| order.clone().into());
+            }
+        }
+
+        let now = Utc::now();
+        let placed = Order {
+            id: state.next_id("ORDER"),
+            symbol: order.symbol.clone(),
+            quantity: order.quantity,
+            filled_quantity: 0.0,
+            price: order.price,
+            stop_price: order.stop_price,
+            status: OrderStatus::New,
+            side: order.side,
+            order_type: order.order_type,
+            time_in_force: order.time_in_force,
+            extended_hours: order.extended_hours,
+            created_at: now,
+            updated_at: now,
+            commission: 0.0,
+            rejected_reason: None,
+            average_fill_price: None,
+        };
+
+        if let Some(client_order_id) = &order.client_order_id {
+            state
+                .client_order_ids
+                .insert(client_order_id.clone(), placed.id.clone());
+        }
+        state.orders.push(placed.clone());
+        Ok(placed.into())
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> WebullResult<()> {
+        let mut state = self.state();
+        let order = state.open_order_mut(order_id)?;
+        order.status = OrderStatus::Canceled;
+        order.updated_at = Utc::now();
+        Ok(())
+    }
+
+    async fn get_order(&self, order_id: &str) -> WebullResult<Order> {
+        self.state().order_mut(order_id).map(|order| order.clone())
+    }
+
+    async fn get_orders(&self, params: &OrderQueryParams) -> WebullResult<Vec<Order>> {
+        let mut orders = self.state().orders_where(|order| {
+            params.status.is_none_or(|status| order.status == status)
+                && params
+                    .symbol
+                    .as_ref()
+                    .is_none_or(|symbol| &order.symbol == symbol)
+                && params
+                    .start_date
+                    .is_none_or(|start| order.created_at >= start)
+                && params.end_date.is_none_or(|end| order.created_at <= end)
+        });
+        if let Some(limit) = params.limit {
+            orders.truncate(limit as usize);
+        }
+        Ok(orders)
+    }
+
+    async fn get_active_orders(&self) -> WebullResult<Vec<Order>> {
+        Ok(self.state().orders_where(|order| order.status.is_open()))
+    }
+
+    async fn get_filled_orders(&self) -> WebullResult<Vec<Order>> {
+        Ok(self
+            .state()
+            .orders_where(|order| order.status == OrderStatus::Filled))
+    }
+
+    async fn modify_order(
+        &self,
+        order_id: &str,
+        order: &OrderRequest,
+    ) -> WebullResult<OrderResponse> {
+        let mut state = self.state();
+        let modified = state.open_order_mut(order_id)?;
+        modified.quantity = order.quantity;
+        modified.price = order.price.or(modified.price);
+        modified.stop_price = order.stop_price.or(modified.stop_price);
+        modified.time_in_force = order.time_in_force;
+        modified.updated_at = Utc::now();
+        Ok(modified.clone().into())
+    }
+
+    async fn get_open_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        let state = self.state();
+        state.account(account_id)?;
+        Ok(state.orders_where(|order| order.status.is_open()))
+    }
+
+    async fn get_open_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        let orders = self.get_open_orders(account_id).await?;
+        Ok(page_after(orders, page_size, last_order_id))
+    }
+
+    async fn get_today_orders(&self, account_id: &str) -> WebullResult<Vec<Order>> {
+        let state = self.state();
+        state.account(account_id)?;
+        let today = Utc::now().date_naive();
+        Ok(state.orders_where(|order| order.created_at.date_naive() == today))
+    }
+
+    async fn get_today_orders_paged(
+        &self,
+        account_id: &str,
+        page_size: u32,
+        last_order_id: Option<&str>,
+    ) -> WebullResult<Vec<Order>> {
+        let orders = self.get_today_orders(account_id).await?;
+        Ok(page_after(orders, page_size, last_order_id))
+    }
+
+    async fn preview_option_order(
+        &self,
+        _preview_request: &OptionOrderPreviewRequest,
+    ) -> WebullResult<OptionOrderPreviewResponse> {
+        Err(not_supported("preview_option_order"))
+    }
+
+    async fn place_option_order(
+        &self,
+        _account_id: &str,
+        _orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        Err(not_supported("place_option_order"))
+    }
+
+    async fn replace_option_order(
+        &self,
+        _account_id: &str,
+        _orders: &[OptionOrderRequest],
+    ) -> WebullResult<Vec<OrderResponse>> {
+        Err(not_supported("replace_option_order"))
+    }
+
+    async fn cancel_option_order(
+        &self,
+        _account_id: &str,
+        _client_order_id: &str,
+    ) -> WebullResult<()> {
+        Err(not_supported("cancel_option_order"))
+    }
+}
+
+#[async_trait]
+impl MarketDataApi for FakeWebull {
+    async fn get_quote(&self, symbol: &str) -> WebullResult<Quote> {
+        self.state().quotes.get(symbol).cloned().ok_or_else(|| {
+            api_error(
+                "SYMBOL_NOT_FOUND",
+                format!("No quote seeded for {}", symbol),
+            )
+        })
+    }
+
+    async fn get_quotes(&self, symbols: &[&str]) -> WebullResult<Vec<Quote>> {
+        let state = self.state();
+        Ok(symbols
+            .iter()
+            .filter_map(|symbol| state.quotes.get(*symbol).cloned())
+            .collect())
+    }
+
+    async fn get_snapshot(&self, params: &SnapshotParams) -> WebullResult<Vec<Quote>> {
+        let symbols: Vec<&str> = params.symbols.split(',').map(str::trim).collect();
+        self.get_quotes(&symbols).await
+    }
+
+    async fn get_history_bar(&self, params: &BarQueryParams) -> WebullResult<Vec<Bar>> {
+        let count = params.count.parse().unwrap_or(usize::MAX);
+        let bars = self
+            .state()
+            .bars
+            .get(&params.symbol)
+            .cloned()
+            .unwrap_or_default();
+        Ok(bars.into_iter().take(count).collect())
+    }
+
+    async fn get_option_chain(
+        &self,
+        _params: &OptionChainQueryParams,
+    ) -> WebullResult<OptionChain> {
+        Err(not_supported("get_option_chain"))
+    }
+
+    async fn get_news(&self, _params: &NewsQueryParams) -> WebullResult<Vec<NewsArticle>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_market_calendar(&self) -> WebullResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_instrument(&self, _params: &InstrumentParams) -> WebullResult<Vec<Instrument>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_eod_bar(&self, _params: &EodBarsParams) -> WebullResult<Vec<Bar>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_corp_action(&self, _params: &CorpActionParams) -> WebullResult<Vec<Instrument>> {
+        Ok(Vec::new())
+    }
+}
+
+#[async_trait]
+impl WatchlistApi for FakeWebull {
+    async fn get_watchlists(&self) -> WebullResult<Vec<Watchlist>> {
+        Ok(self.state().watchlists.values().cloned().collect())
+    }
+
+    async fn get_watchlist(&self, watchlist_id: &str) -> WebullResult<Watchlist> {
+        self.state()
+            .watchlist_mut(watchlist_id)
+            .map(|watchlist| watchlist.clone())
+    }
+
+    async fn create_watchlist(&self, request: &CreateWatchlistRequest) -> WebullResult<Watchlist> {
+        let mut state = self.state();
+        let watchlist = Watchlist {
+            id: state.next_id("WATCHLIST"),
+            name: request.name.clone(),
+            symbols: request.symbols.clone(),
+        };
+        state
+            .watchlists
+            .insert(watchlist.id.clone(), watchlist.clone());
+        Ok(watchlist)
+    }
+
+    async fn modify_watchlist(&self, request: &ModifyWatchlistRequest) -> WebullResult<Watchlist> {
+        let mut state = self.state();
+        let watchlist = state.watchlist_mut(&request.id)?;
+        if let Some(name) = &request.name {
+            watchlist.name = name.clone();
+        }
+        for symbol in request.add_symbols.iter().flatten() {
+            if !watchlist.symbols.contains(symbol) {
+                watchlist.symbols.push(symbol.clone());
+            }
+        }
+        if let Some(remove) = &request.remove_symbols {
+            watchlist.symbols.retain(|symbol| !remove.contains(symbol));
+        }
+        Ok(watchlist.clone())
+    }
+
+    async fn delete_watchlist(&self, watchlist_id: &str) -> WebullResult<()> {
+        self.state()
+            .watchlists
+            .remove(watchlist_id)
+            .map(|_| ())
+            .ok_or_else(|| watchlist_not_found(watchlist_id))
+    }
+}
+
+/// Build a quote around a last price.
+fn quote_at(symbol: &str, price: f64) -> Quote {
+    Quote {
+        symbol: symbol.to_string(),
+        last_price: price,
+        change: 0.0,
+        change_percent: 0.0,
+        volume: 0,
+        average_volume: 0,
+        bid_price: price - 0.01,
+        bid_size: 100,
+        ask_price: price + 0.01,
+        ask_size: 100,
+        high: price,
+        low: price,
+        open: price,
+        prev_close: price,
+        fifty_two_week_high: price,
+        fifty_two_week_low: price,
+        market_cap: None,
+        pe_ratio: None,
+        timestamp: Utc::now(),
+    }
+}
+
+/// Take a page of orders after the one with an ID.
+fn page_after(orders: Vec<Order>, page_size: u32, last_order_id: Option<&str>) -> Vec<Order> {
+    let start = match last_order_id {
+        Some(last) => orders
+            .iter()
+            .position(|order| order.id == last)
+            .map_or(orders.len(), |index| index + 1),
+        None => 0,
+    };
+    orders
+        .into_iter()
+        .skip(start)
+        .take(page_size as usize)
+        .collect()
+}
+
+/// Build the error a Webull server would return.
+fn api_error(code: &str, message: impl Into<String>) -> WebullError {
+    WebullError::api_error(code, message)
+}
+
+fn order_not_found(order_id: &str) -> WebullError {
+    api_error("ORDER_NOT_FOUND", format!("Order {} not found", order_id))
+}
+
+fn watchlist_not_found(watchlist_id: &str) -> WebullError {
+    api_error(
+        "WATCHLIST_NOT_FOUND",
+        format!("Watchlist {} not found", watchlist_id),
+    )
+}
+
+fn not_supported(operation: &str) -> WebullError {
+    WebullError::InvalidRequest(format!("{} is not supported by FakeWebull", operation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::WebullApi;
+    use crate::models::order::OrderSide;
+    use crate::WebullClient;
+
+    #[tokio::test]
+    async fn fake_webull_stands_in_for_the_client() {
+        async fn buy_below(api: &impl WebullApi, symbol: &str, limit: f64) -> WebullResult<bool> {
+            if api.get_quote(symbol).await?.last_price >= limit {
+                return Ok(false);
+            }
+            let order = OrderRequest::market()
+                .symbol(symbol)
+                .quantity(1.0)
+                .side(OrderSide::Buy)
+                .client_order_id("dip-1");
+            api.place_order(&order).await?;
+            Ok(true)
+        }
+
+        fn assert_api<T: WebullApi>() {}
+        assert_api::<WebullClient>();
+
+        let fake = FakeWebull::builder().with_price("AAPL", 180.0).build();
+        assert!(!buy_below(&fake, "AAPL", 150.0).await.unwrap());
+        fake.set_price("AAPL", 140.0);
+        assert!(buy_below(&fake, "AAPL", 150.0).await.unwrap());
+        // The client order ID makes a retried order a no-op
+        assert!(buy_below(&fake, "AAPL", 150.0).await.unwrap());
+
+        let orders = fake.orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(fake.get_active_orders().await.unwrap().len(), 1);
+        fake.cancel_order(&orders[0].id).await.unwrap();
+        assert_eq!(
+            fake.get_order(&orders[0].id).await.unwrap().status,
+            OrderStatus::Canceled
+        );
+        assert!(fake.cancel_order(&orders[0].id).await.is_err());
+        assert!(fake.get_quote("MSFT").await.is_err());
+    }
+}
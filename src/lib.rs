@@ -4,10 +4,23 @@
 //! enabling developers to build trading applications, algorithms, and bots in Rust.
 
 // Re-export core modules
+pub mod api;
 pub mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cassette;
 pub mod client;
 pub mod config;
+pub mod diagnostics;
 pub mod error;
+pub mod fake;
+pub mod meta;
+pub mod middleware;
+#[cfg(feature = "mock-server")]
+pub mod mock_server;
+pub mod region;
+pub mod telemetry;
+pub mod transport;
 
 // Re-export models and endpoints
 pub mod endpoints;
@@ -18,7 +31,8 @@ pub mod utils;
 // Re-export key types for convenience
 pub use client::{WebullClient, WebullClientBuilder};
 pub use config::WebullConfig;
-pub use error::{WebullError, WebullResult};
+pub use error::{ErrorKind, WebullError, WebullResult};
+pub use region::Region;
 
 #[cfg(test)]
 mod tests {
@@ -30,5 +44,3 @@ mod tests {
         assert!(client.is_ok());
     }
 }
-
-// Test modules will be added in the future
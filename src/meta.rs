@@ -0,0 +1,179 @@
+//! Response metadata.
+//!
+//! Endpoint methods return only the typed data of a response. Wrap a call in
+//! [`capture`] to also get the status, headers, request ID, latency and raw
+//! body of the responses it received:
+//!
+//! ```no_run
+//! # async fn example(client: webull_rs::WebullClient) {
+//! let captured = webull_rs::meta::capture(client.market_data().get_quote("AAPL")).await;
+//! if let Some(meta) = captured.last() {
+//!     println!("request {:?} took {:?}", meta.request_id, meta.latency);
+//! }
+//! let quote = captured.result;
+//! # }
+//! ```
+
+use crate::error::WebullResult;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, DATE};
+use reqwest::{Method, StatusCode};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Headers that may carry the server's request ID, in order of preference.
+const REQUEST_ID_HEADERS: [&str; 3] = ["x-request-id", "x-trace-id", "request-id"];
+
+tokio::task_local! {
+    /// Metadata of the responses received by the current [`capture`] call.
+    static CAPTURED: Arc<Mutex<Vec<ResponseMeta>>>;
+}
+
+/// Metadata of an API response.
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// HTTP method of the request
+    pub method: Method,
+
+    /// API path of the request
+    pub path: String,
+
+    /// HTTP status
+    pub status: StatusCode,
+
+    /// Response headers
+    pub headers: HeaderMap,
+
+    /// Request ID assigned by the server, for support tickets
+    pub request_id: Option<String>,
+
+    /// Server time from the `Date` header
+    pub server_time: Option<DateTime<Utc>>,
+
+    /// Time from sending the request to receiving the response body
+    pub latency: Duration,
+
+    /// Raw response body
+    pub raw_body: String,
+}
+
+impl ResponseMeta {
+    /// Create response metadata, extracting the request ID and server time
+    /// from the headers.
+    pub fn new(
+        method: Method,
+        path: impl Into<String>,
+        status: StatusCode,
+        headers: HeaderMap,
+        latency: Duration,
+        raw_body: impl Into<String>,
+    ) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+        let request_id = REQUEST_ID_HEADERS
+            .iter()
+            .find_map(|name| header(name))
+            .map(str::to_string);
+        let server_time = header(DATE.as_str())
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&Utc));
+
+        Self {
+            method,
+            path: path.into(),
+            status,
+            headers,
+            request_id,
+            server_time,
+            latency,
+            raw_body: raw_body.into(),
+        }
+    }
+}
+
+/// The result of an API call together with the metadata of its responses.
+#[derive(Debug)]
+pub struct Captured<T> {
+    /// Result of the call
+    pub result: WebullResult<T>,
+
+    /// Metadata of every response received, in order, including responses to
+    /// attempts that were retried
+    pub responses: Vec<ResponseMeta>,
+}
+
+impl<T> Captured<T> {
+    /// Get the metadata of the last response received.
+    pub fn last(&self) -> Option<&ResponseMeta> {
+        self.responses.last()
+    }
+
+    /// Get the request ID of the last response received.
+    pub fn request_id(&self) -> Option<&str> {
+        self.last()?.request_id.as_deref()
+    }
+}
+
+/// Run an API call and capture the metadata of the responses it receives.
+///
+/// Metadata is captured for requests made by the future itself, including
+/// when the call fails, but not for requests made by tasks it spawns.
+pub async fn capture<F, T>(future: F) -> Captured<T>
+where
+    F: Future<Output = WebullResult<T>>,
+{
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let result = CAPTURED.scope(responses.clone(), future).await;
+    let responses = std::mem::take(&mut *responses.lock().unwrap());
+
+    Captured { result, responses }
+}
+
+/// Check if response metadata is being captured for the current task.
+pub(crate) fn is_capturing() -> bool {
+    CAPTURED.try_with(|_| ()).is_ok()
+}
+
+/// Record response metadata if it is being captured for the current task.
+pub(crate) fn record(meta: ResponseMeta) {
+    let _ = CAPTURED.try_with(|responses| responses.lock().unwrap().push(meta));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::HttpResponse;
+    use crate::transport::testing::FakeTransport;
+    use crate::{WebullClient, WebullError};
+    use reqwest::header::HeaderValue;
+
+    #[tokio::test]
+    async fn capture_exposes_response_metadata() {
+        let fake = FakeTransport::with_handler(|request| {
+            (request.url.path() == "/api/quote/tickerRealTimes/MSFT").then(|| {
+                let mut response = HttpResponse::new(
+                    StatusCode::OK,
+                    r#"{"success":true,"data":{"unexpected":1}}"#,
+                );
+                response
+                    .headers
+                    .insert("x-request-id", HeaderValue::from_static("req-42"));
+                response
+            })
+        });
+        let client = WebullClient::builder()
+            .with_transport(fake)
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+
+        let captured = capture(client.market_data().get_quote("MSFT")).await;
+        assert!(matches!(
+            captured.result,
+            Err(WebullError::SerializationError(_))
+        ));
+        assert_eq!(captured.request_id(), Some("req-42"));
+        assert!(captured.last().unwrap().raw_body.contains("unexpected"));
+    }
+}
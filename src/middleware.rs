@@ -0,0 +1,1219 @@
+//! Request middleware.
+//!
+//! Every API request made through the endpoint groups passes through a chain
+//! of [`Middleware`] layers before it reaches the network. The built-in layers
+//! handle tracing, caching, retries, the circuit breaker, rate limiting and
+//! authentication, in that order;
+//! layers registered with [`WebullClientBuilder::with_middleware`] run after
+//! them, once per attempt, followed by [`MetaLayer`] just before the request
+//! is sent. When a [`Cassette`] is configured, [`CassetteLayer`] comes next and
+//! records the request or answers it from a recording. [`ClockLayer`] then
+//! measures clock skew from the responses that come from the network. When
+//! fallback base URLs are configured, [`FailoverLayer`] comes last and moves
+//! the request to another host if its host fails.
+//!
+//! [`WebullClientBuilder::with_middleware`]: crate::WebullClientBuilder::with_middleware
+
+use crate::auth::AuthManager;
+use crate::cassette::{Cassette, CassetteRequest};
+use crate::error::{WebullError, WebullResult};
+use crate::meta::{self, ResponseMeta};
+use crate::models::response::ApiResponse;
+use crate::telemetry::{self, Metrics, NoopMetrics};
+use crate::transport::{HttpRequest, HttpTransport};
+use crate::utils::cache::{
+    CacheKey, CacheLookup, CacheManager, CachePolicy, DiskCache, ResponseCache,
+};
+use crate::utils::circuit_breaker::CircuitBreaker;
+use crate::utils::clock::Clock;
+use crate::utils::failover::Failover;
+use crate::utils::rate_limit::{Priority, RateCategory, RateLimiter};
+use crate::utils::retry::RetryPolicy;
+use async_trait::async_trait;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+use url::Url;
+
+/// An API request on its way through the middleware chain.
+#[derive(Debug, Clone)]
+pub struct ApiRequest {
+    /// HTTP method
+    pub method: Method,
+
+    /// API path, e.g. `/api/quote/tickerRealTimes/AAPL`
+    pub path: String,
+
+    /// Full request URL, including the query string
+    pub url: Url,
+
+    /// Canonical query string
+    pub query: Option<String>,
+
+    /// Request headers
+    pub headers: HeaderMap,
+
+    /// JSON request body
+    pub body: Option<String>,
+
+    /// Whether the request is safe to send more than once
+    pub idempotent: bool,
+
+    /// How the response may be cached
+    pub cache_policy: CachePolicy,
+
+    /// Whether a successful response invalidates cached responses in the
+    /// same scope, i.e. whether the request changes server state
+    pub invalidates: bool,
+
+    /// Priority of the request when waiting for rate limit budget
+    pub priority: Priority,
+}
+
+impl ApiRequest {
+    /// Create a request for an API path.
+    pub fn new(method: Method, path: impl Into<String>, url: Url) -> Self {
+        let idempotent = matches!(method, Method::GET | Method::PUT | Method::DELETE);
+        let invalidates = !matches!(method, Method::GET | Method::HEAD);
+        let path = path.into();
+        let priority = RateCategory::from_path(&path).default_priority(&method);
+        Self {
+            method,
+            path,
+            url,
+            query: None,
+            headers: HeaderMap::new(),
+            body: None,
+            idempotent,
+            cache_policy: CachePolicy::Never,
+            invalidates,
+            priority,
+        }
+    }
+
+    /// Set the canonical query string.
+    pub fn with_query(mut self, query: Option<String>) -> Self {
+        self.url.set_query(query.as_deref());
+        self.query = query;
+        self
+    }
+
+    /// Set the JSON request body.
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set whether the request is safe to send more than once.
+    pub fn with_idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    /// Set how the response may be cached.
+    pub fn with_cache_policy(mut self, cache_policy: CachePolicy) -> Self {
+        self.cache_policy = cache_policy;
+        self
+    }
+
+    /// Set whether a successful response invalidates cached responses.
+    ///
+    /// Requests other than GET and HEAD invalidate by default; read-only
+    /// POSTs such as order previews should not.
+    pub fn with_invalidates(mut self, invalidates: bool) -> Self {
+        self.invalidates = invalidates;
+        self
+    }
+
+    /// Set the priority of the request when waiting for rate limit budget.
+    ///
+    /// Requests that change orders are high priority by default; read-only
+    /// POSTs such as order previews should not be.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// A raw HTTP response on its way back through the middleware chain.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// HTTP status
+    pub status: StatusCode,
+
+    /// Response headers
+    pub headers: HeaderMap,
+
+    /// Response body
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Create a response.
+    pub fn new(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Get the delay requested by a `Retry-After` header, in seconds.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.headers
+            .get(RETRY_AFTER)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+    }
+
+    /// Get the error for an unsuccessful HTTP status, if any.
+    pub fn status_error(&self) -> Option<WebullError> {
+        match self.status {
+            StatusCode::TOO_MANY_REQUESTS => Some(WebullError::RateLimitExceeded),
+            StatusCode::UNAUTHORIZED => Some(WebullError::Unauthorized),
+            status if !status.is_success() => {
+                Some(WebullError::from_response(status.as_u16(), &self.body))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if the response carries a successful API result.
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+            && serde_json::from_str::<ApiResponse<serde_json::Value>>(&self.body)
+                .map(|response| response.is_success())
+                .unwrap_or(false)
+    }
+}
+
+/// A layer in the request pipeline.
+///
+/// Implement [`Middleware::before_request`] and
+/// [`Middleware::after_response`] for simple hooks, or override
+/// [`Middleware::handle`] to wrap the rest of the chain, e.g. to short-circuit
+/// or repeat a request.
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Inspect or modify a request before it is passed on.
+    async fn before_request(&self, _request: &mut ApiRequest) -> WebullResult<()> {
+        Ok(())
+    }
+
+    /// Inspect or modify a response before it is passed back.
+    async fn after_response(
+        &self,
+        _request: &ApiRequest,
+        _response: &mut HttpResponse,
+    ) -> WebullResult<()> {
+        Ok(())
+    }
+
+    /// Handle a request, passing it on with `next`.
+    async fn handle(&self, mut request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        self.before_request(&mut request).await?;
+        let mut response = next.run(request.clone()).await?;
+        self.after_response(&request, &mut response).await?;
+        Ok(response)
+    }
+}
+
+/// The rest of the middleware chain.
+#[derive(Clone)]
+pub struct Next {
+    /// All layers of the chain
+    layers: Arc<[Arc<dyn Middleware>]>,
+
+    /// Index of the next layer to run
+    index: usize,
+
+    /// Transport that sends the request after the last layer
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl Next {
+    /// Create a chain that runs the layers in order, then sends the request.
+    pub fn new(layers: Arc<[Arc<dyn Middleware>]>, transport: Arc<dyn HttpTransport>) -> Self {
+        Self {
+            layers,
+            index: 0,
+            transport,
+        }
+    }
+
+    /// Run the rest of the chain.
+    pub async fn run(self, request: ApiRequest) -> WebullResult<HttpResponse> {
+        match self.layers.get(self.index).cloned() {
+            Some(layer) => {
+                let next = Self {
+                    index: self.index + 1,
+                    ..self
+                };
+                layer.handle(request, next).await
+            }
+            None => self.transport.send(request.into()).await,
+        }
+    }
+}
+
+impl From<ApiRequest> for HttpRequest {
+    fn from(request: ApiRequest) -> Self {
+        let mut headers = request.headers;
+        if request.body.is_some() {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+
+        Self {
+            method: request.method,
+            url: request.url,
+            headers,
+            body: request.body,
+        }
+    }
+}
+
+/// Adds the access token to requests.
+pub struct AuthLayer {
+    /// Authentication manager
+    auth_manager: Arc<AuthManager>,
+}
+
+impl AuthLayer {
+    /// Create an authentication layer.
+    pub fn new(auth_manager: Arc<AuthManager>) -> Self {
+        Self { auth_manager }
+    }
+}
+
+#[async_trait]
+impl Middleware for AuthLayer {
+    async fn before_request(&self, request: &mut ApiRequest) -> WebullResult<()> {
+        let token = self.auth_manager.get_token().await?;
+        request
+            .headers
+            .insert(AUTHORIZATION, token.bearer_header()?);
+        Ok(())
+    }
+}
+
+/// Waits for rate limit budget and adapts it to the server's responses.
+pub struct RateLimitLayer {
+    /// Rate limiter
+    rate_limiter: Arc<RateLimiter>,
+
+    /// Metrics sink for wait times
+    metrics: Arc<dyn Metrics>,
+}
+
+impl RateLimitLayer {
+    /// Create a rate limiting layer.
+    pub fn new(rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            rate_limiter,
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// Report wait times to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimitLayer {
+    async fn before_request(&self, request: &mut ApiRequest) -> WebullResult<()> {
+        let started = Instant::now();
+        self.rate_limiter
+            .acquire(RateCategory::from_path(&request.path), request.priority)
+            .await;
+        let waited = started.elapsed();
+
+        Span::current().record("rate_limit_wait_ms", waited.as_millis() as u64);
+        self.metrics.record_histogram(
+            telemetry::RATE_LIMIT_WAIT_SECONDS,
+            waited.as_secs_f64(),
+            &[("category", category(&request.path))],
+        );
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        request: &ApiRequest,
+        response: &mut HttpResponse,
+    ) -> WebullResult<()> {
+        self.rate_limiter
+            .update_from_headers(&request.path, &response.headers);
+
+        if response.status == StatusCode::TOO_MANY_REQUESTS {
+            if let Some(retry_after) = response.retry_after() {
+                self.rate_limiter.pause(&request.path, retry_after);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Retries idempotent requests on transient failures.
+pub struct RetryLayer {
+    /// Retry policy
+    policy: Arc<RetryPolicy>,
+
+    /// Rate limiter, for backing off after a 429 without `Retry-After` and
+    /// for checking whether it already waits one out
+    rate_limiter: Arc<RateLimiter>,
+
+    /// Metrics sink for retries
+    metrics: Arc<dyn Metrics>,
+}
+
+impl RetryLayer {
+    /// Create a retry layer.
+    pub fn new(policy: Arc<RetryPolicy>, rate_limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            policy,
+            rate_limiter,
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// Report retries to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
+#[async_trait]
+impl Middleware for RetryLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        let max_attempts = if request.idempotent {
+            self.policy.max_attempts.max(1)
+        } else {
+            1
+        };
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            Span::current().record("attempt", attempt);
+            let result = next.clone().run(request.clone()).await;
+
+            // Work out whether the attempt failed and why
+            let (response, error) = match result {
+                Ok(response) => match response.status_error() {
+                    Some(error) => (Some(response), error),
+                    None => return Ok(response),
+                },
+                Err(error @ WebullError::NetworkError(_)) => (None, error),
+                Err(error) => return Err(error),
+            };
+            let status = response.as_ref().map(|response| response.status);
+
+            if attempt >= max_attempts || !self.policy.should_retry(status, &error) {
+                return match (attempt, response) {
+                    // Not retried: hand back the response unchanged
+                    (1, Some(response)) => Ok(response),
+                    (1, None) => Err(error),
+                    _ => Err(WebullError::RetriesExhausted {
+                        attempts: attempt,
+                        source: Box::new(error),
+                    }),
+                };
+            }
+
+            // Honour Retry-After on 429s, otherwise back off. The rate limit
+            // layer normally pauses the category for Retry-After already, and
+            // the next attempt then waits there rather than here as well
+            let delay = match response.as_ref().and_then(HttpResponse::retry_after) {
+                Some(_) if self.rate_limiter.is_paused(&request.path) => Duration::ZERO,
+                Some(delay) => delay,
+                None if status == Some(StatusCode::TOO_MANY_REQUESTS) => {
+                    self.rate_limiter.handle_rate_limit_error(attempt - 1).await
+                }
+                None => self.policy.delay(attempt),
+            };
+
+            log::debug!(
+                "Retrying {} {} in {:?} after attempt {} failed: {}",
+                request.method,
+                request.path,
+                delay,
+                attempt,
+                error
+            );
+            self.metrics.increment_counter(
+                telemetry::RETRIES_TOTAL,
+                &[
+                    ("category", category(&request.path)),
+                    ("method", request.method.as_str()),
+                ],
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Fails requests fast while the circuit breaker for their category is open.
+///
+/// Runs after [`RetryLayer`], so every attempt is counted and retries stop as
+/// soon as the circuit opens.
+pub struct CircuitBreakerLayer {
+    /// Circuit breaker
+    circuit_breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerLayer {
+    /// Create a circuit breaker layer.
+    pub fn new(circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        Self { circuit_breaker }
+    }
+}
+
+#[async_trait]
+impl Middleware for CircuitBreakerLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        self.circuit_breaker.acquire(&request.path)?;
+        let path = request.path.clone();
+        let result = next.run(request).await;
+
+        // Errors raised before the request was sent, e.g. by the auth layer,
+        // say nothing about the health of the API
+        match &result {
+            Ok(response) => self
+                .circuit_breaker
+                .record(&path, response.status.is_server_error()),
+            Err(WebullError::NetworkError(_)) => self.circuit_breaker.record(&path, true),
+            Err(_) => self.circuit_breaker.release(&path),
+        }
+        result
+    }
+}
+
+/// Runs each request in a `webull.request` span and reports its outcome.
+///
+/// Runs first in the chain, so the span covers cache lookups, retries and
+/// rate limit waits, which the other built-in layers record on it.
+pub struct TraceLayer {
+    /// Metrics sink for requests
+    metrics: Arc<dyn Metrics>,
+}
+
+impl TraceLayer {
+    /// Create a tracing layer.
+    pub fn new(metrics: Arc<dyn Metrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+#[async_trait]
+impl Middleware for TraceLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        let category = category(&request.path);
+        let method = request.method.clone();
+        let span = tracing::info_span!(
+            "webull.request",
+            category,
+            method = %method,
+            path = %telemetry::path_template(&request.path),
+            status = Empty,
+            attempt = Empty,
+            latency_ms = Empty,
+            cache = Empty,
+            rate_limit_wait_ms = Empty,
+        );
+
+        let started = Instant::now();
+        let result = next.run(request).instrument(span.clone()).await;
+        let latency = started.elapsed();
+
+        let status = match &result {
+            Ok(response) => response.status.as_str().to_string(),
+            Err(_) => "error".to_string(),
+        };
+        span.record("status", status.as_str());
+        span.record("latency_ms", latency.as_millis() as u64);
+        if let Err(error) = &result {
+            tracing::debug!(parent: &span, kind = ?error.kind(), "request failed");
+        }
+
+        let labels = [("category", category), ("method", method.as_str())];
+        self.metrics.record_histogram(
+            telemetry::REQUEST_DURATION_SECONDS,
+            latency.as_secs_f64(),
+            &labels,
+        );
+        self.metrics.increment_counter(
+            telemetry::REQUESTS_TOTAL,
+            &[labels[0], labels[1], ("status", status.as_str())],
+        );
+
+        result
+    }
+}
+
+/// Serves responses from the cache and keeps it up to date.
+pub struct CacheLayer {
+    /// Cache manager
+    cache_manager: Arc<CacheManager>,
+
+    /// Metrics sink for cache lookups
+    metrics: Arc<dyn Metrics>,
+}
+
+impl CacheLayer {
+    /// Create a caching layer.
+    pub fn new(cache_manager: Arc<CacheManager>) -> Self {
+        Self {
+            cache_manager,
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// Report cache lookups to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Record the result of a cache lookup.
+    fn record_lookup(&self, request: &ApiRequest, result: &'static str) {
+        Span::current().record("cache", result);
+        self.metrics.increment_counter(
+            telemetry::CACHE_LOOKUPS_TOTAL,
+            &[("category", category(&request.path)), ("result", result)],
+        );
+    }
+}
+
+#[async_trait]
+impl Middleware for CacheLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        let method = request.method.as_str().to_string();
+
+        // Writes invalidate cached responses in the same scope
+        if request.invalidates {
+            let response = next.run(request.clone()).await?;
+            if response.status.is_success() {
+                for scope in invalidation_scopes(&request.path) {
+                    self.cache_manager.invalidate_prefix(&scope);
+                }
+            }
+            return Ok(response);
+        }
+
+        if !request.cache_policy.is_cacheable() || !self.cache_manager.is_enabled() {
+            Span::current().record("cache", "bypass");
+            return next.run(request).await;
+        }
+
+        let cache = self.cache_manager.get_cache::<HttpResponse>(&method);
+        let disk = self.cache_manager.disk().cloned();
+        let (query, body) = (request.query.as_deref(), request.body.as_deref());
+        let mut lookup = cache.lookup(&method, &request.path, query, body);
+        let mut from_disk = false;
+        if let CacheLookup::Miss = lookup {
+            if let Some(restored) = restore(disk.as_deref(), &request, &cache) {
+                lookup = restored;
+                from_disk = true;
+            }
+        }
+
+        match lookup {
+            CacheLookup::Fresh(cached) => {
+                self.record_lookup(&request, if from_disk { "disk" } else { "hit" });
+                return Ok(cached);
+            }
+            CacheLookup::Stale(cached) => {
+                self.record_lookup(&request, "stale");
+                // Serve the stale response and refresh it in the background
+                if cache.begin_revalidation(&method, &request.path, query, body) {
+                    tokio::spawn(async move {
+                        let (query, body) = (request.query.as_deref(), request.body.as_deref());
+                        match next.run(request.clone()).await {
+                            Ok(response) if response.is_success() => {
+                                persist(disk.as_deref(), &request, &response);
+                                cache.set_with_policy(
+                                    &method,
+                                    &request.path,
+                                    query,
+                                    body,
+                                    response,
+                                    request.cache_policy,
+                                )
+                            }
+                            result => {
+                                log::warn!(
+                                    "Failed to refresh cached response for {}: {:?}",
+                                    request.path,
+                                    result.err()
+                                );
+                                cache.end_revalidation(&method, &request.path, query, body);
+                            }
+                        }
+                    });
+                }
+                return Ok(cached);
+            }
+            CacheLookup::Miss => self.record_lookup(&request, "miss"),
+        }
+
+        let response = next.run(request.clone()).await?;
+        if response.is_success() {
+            persist(disk.as_deref(), &request, &response);
+            cache.set_with_policy(
+                &method,
+                &request.path,
+                query,
+                body,
+                response.clone(),
+                request.cache_policy,
+            );
+        }
+
+        Ok(response)
+    }
+}
+
+/// A response as kept in the disk tier; headers are not stored.
+#[derive(Serialize, Deserialize)]
+struct StoredResponse {
+    /// HTTP status
+    status: u16,
+
+    /// Response body
+    body: String,
+}
+
+/// Get the cache key of a request.
+fn cache_key(request: &ApiRequest) -> CacheKey {
+    CacheKey::new(
+        request.method.as_str(),
+        &request.path,
+        request.query.as_deref(),
+        request.body.as_deref(),
+    )
+}
+
+/// Store a response in the disk tier, if there is one and the request's
+/// cache policy is persistent.
+fn persist(disk: Option<&DiskCache>, request: &ApiRequest, response: &HttpResponse) {
+    if let Some(disk) = disk.filter(|_| request.cache_policy.is_persistent()) {
+        let stored = StoredResponse {
+            status: response.status.as_u16(),
+            body: response.body.clone(),
+        };
+        disk.set(&cache_key(request), &stored);
+    }
+}
+
+/// Move a response from the disk tier into the memory cache, if there is a
+/// disk tier and the request's cache policy is persistent.
+///
+/// Only responses still within the policy's fresh or stale window are
+/// restored, aged by the time since they were stored.
+fn restore(
+    disk: Option<&DiskCache>,
+    request: &ApiRequest,
+    cache: &ResponseCache<HttpResponse>,
+) -> Option<CacheLookup<HttpResponse>> {
+    let disk = disk?;
+    let CachePolicy::Persistent { ttl, stale } = request.cache_policy else {
+        return None;
+    };
+    let key = cache_key(request);
+    let (stored, age) = disk.get::<StoredResponse>(&key, ttl + stale)?;
+    let status = StatusCode::from_u16(stored.status).ok()?;
+    let response = HttpResponse::new(status, stored.body);
+
+    cache.set_aged(key, response.clone(), request.cache_policy, age);
+    Some(if age < ttl {
+        CacheLookup::Fresh(response)
+    } else {
+        CacheLookup::Stale(response)
+    })
+}
+
+/// Outcome of a request shared by concurrent identical callers.
+type SharedResponse = Shared<BoxFuture<'static, Result<HttpResponse, Arc<WebullError>>>>;
+
+/// Coalesces concurrent identical GET requests into one network call.
+///
+/// Requests are identified by the same [`CacheKey`] the response cache uses.
+/// Every caller receives a clone of the response, or of the error wrapped in
+/// [`WebullError::Shared`] if more than one caller was waiting for it.
+#[derive(Default)]
+pub struct SingleFlightLayer {
+    /// Requests currently in flight
+    in_flight: Arc<Mutex<HashMap<CacheKey, SharedResponse>>>,
+}
+
+impl SingleFlightLayer {
+    /// Create a single-flight layer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of distinct requests currently in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl Middleware for SingleFlightLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        if request.method != Method::GET {
+            return next.run(request).await;
+        }
+
+        let key = cache_key(&request);
+
+        let shared = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(shared) => shared.clone(),
+                None => {
+                    // Whichever caller completes the request removes it, so an
+                    // abandoned leader does not leave a stale entry behind
+                    let registry = self.in_flight.clone();
+                    let entry = key.clone();
+                    let shared = async move {
+                        let result = next.run(request).await.map_err(Arc::new);
+                        registry.lock().unwrap().remove(&entry);
+                        result
+                    }
+                    .boxed()
+                    .shared();
+
+                    in_flight.insert(key, shared.clone());
+                    shared
+                }
+            }
+        };
+
+        shared.await.map_err(|error| match Arc::try_unwrap(error) {
+            Ok(error) => error,
+            Err(error) => WebullError::Shared(error),
+        })
+    }
+}
+
+/// Records response metadata for [`meta::capture`].
+///
+/// Runs after authentication and any custom layers, and before the cassette,
+/// clock and failover layers. It has to stay there: inside [`RetryLayer`] it
+/// sees every attempt, ahead of custom layers it sees the body before they
+/// rewrite it, and outside [`CassetteLayer`] it captures replayed responses
+/// just like live ones.
+pub struct MetaLayer;
+
+#[async_trait]
+impl Middleware for MetaLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        if !meta::is_capturing() {
+            return next.run(request).await;
+        }
+
+        let (method, path) = (request.method.clone(), request.path.clone());
+        let started = Instant::now();
+        let response = next.run(request).await?;
+        meta::record(ResponseMeta::new(
+            method,
+            path,
+            response.status,
+            response.headers.clone(),
+            started.elapsed(),
+            response.body.clone(),
+        ));
+
+        Ok(response)
+    }
+}
+
+/// Records requests to, or replays them from, a [`Cassette`].
+///
+/// Runs after [`MetaLayer`], so in replay mode it stands in for the network.
+pub struct CassetteLayer {
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteLayer {
+    /// Create a cassette layer.
+    pub fn new(cassette: Arc<Cassette>) -> Self {
+        Self { cassette }
+    }
+}
+
+#[async_trait]
+impl Middleware for CassetteLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        let recorded = CassetteRequest::new(
+            &request.method,
+            request.path.clone(),
+            request.query.clone(),
+            request.body.clone(),
+        );
+        self.cassette.exchange(recorded, next.run(request)).await
+    }
+}
+
+/// Sends requests to the first healthy host of a [`Failover`] list.
+///
+/// Runs last, so each host tried is sent exactly the same request.
+pub struct FailoverLayer {
+    /// Hosts to fail over across
+    failover: Arc<Failover>,
+}
+
+impl FailoverLayer {
+    /// Create a failover layer.
+    pub fn new(failover: Arc<Failover>) -> Self {
+        Self { failover }
+    }
+}
+
+#[async_trait]
+impl Middleware for FailoverLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        let url = request.url.clone();
+        let idempotent = request.idempotent;
+        self.failover
+            .send(&url, idempotent, |url| {
+                next.clone().run(ApiRequest {
+                    url,
+                    ..request.clone()
+                })
+            })
+            .await
+    }
+}
+
+/// Measures the skew between the local and server clocks from the `Date`
+/// header of each response.
+///
+/// Runs after [`CassetteLayer`], so replayed responses, whose dates are in the
+/// past, are not measured.
+pub struct ClockLayer {
+    /// Clock to report measurements to
+    clock: Arc<Clock>,
+}
+
+impl ClockLayer {
+    /// Create a clock skew layer.
+    pub fn new(clock: Arc<Clock>) -> Self {
+        Self { clock }
+    }
+}
+
+#[async_trait]
+impl Middleware for ClockLayer {
+    async fn handle(&self, request: ApiRequest, next: Next) -> WebullResult<HttpResponse> {
+        let sent_at = chrono::Utc::now();
+        let response = next.run(request).await?;
+        self.clock
+            .observe(&response.headers, sent_at, chrono::Utc::now());
+        Ok(response)
+    }
+}
+
+/// Get the rate limit category name of an API path, for span fields and
+/// metric labels.
+fn category(path: &str) -> &'static str {
+    RateCategory::from_path(path).name()
+}
+
+/// Get the cache invalidation scopes of an API path.
+///
+/// The scope is the first segment after `/api` (and `/paper`), so a change to
+/// `/api/trade/cancel/1` invalidates everything under `/api/trade`. Trading
+/// changes balances and positions as well, so it also invalidates the
+/// account, asset and position scopes.
+pub(crate) fn invalidation_scopes(path: &str) -> Vec<String> {
+    let rest = path.trim_start_matches("/api/");
+    let (prefix, rest) = match rest.strip_prefix("paper/") {
+        Some(rest) => ("/api/paper/", rest),
+        None => ("/api/", rest),
+    };
+    let segment = rest.split('/').next().unwrap_or_default();
+
+    let segments: &[&str] = match segment {
+        "trade" => &["trade", "account", "asset", "position"],
+        segment => &[segment],
+    };
+    segments
+        .iter()
+        .map(|segment| format!("{}{}", prefix, segment))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiRequest, HttpResponse, Middleware};
+    use crate::models::order::OrderRequest;
+    use crate::telemetry::{self, Metrics};
+    use crate::transport::testing::FakeTransport;
+    use crate::utils::rate_limit::BackoffStrategy;
+    use crate::utils::retry::RetryPolicy;
+    use crate::{ErrorKind, WebullClient, WebullClientBuilder, WebullResult};
+    use async_trait::async_trait;
+    use reqwest::header::{HeaderValue, AUTHORIZATION, RETRY_AFTER};
+    use reqwest::StatusCode;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// Build a logged-in client that sends its requests to a fake transport.
+    async fn logged_in(builder: WebullClientBuilder, fake: &FakeTransport) -> WebullClient {
+        let client = builder
+            .with_transport(fake.clone())
+            .without_cache()
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+        client
+    }
+
+    /// Count the requests sent to a path.
+    fn count(fake: &FakeTransport, request: &str) -> usize {
+        fake.requests()
+            .iter()
+            .filter(|sent| sent.as_str() == request)
+            .count()
+    }
+
+    #[tokio::test]
+    async fn custom_middleware_wraps_requests() {
+        struct Tagging;
+
+        #[async_trait]
+        impl Middleware for Tagging {
+            async fn before_request(&self, request: &mut ApiRequest) -> WebullResult<()> {
+                request
+                    .headers
+                    .insert("x-team", HeaderValue::from_static("quant"));
+                Ok(())
+            }
+
+            async fn after_response(
+                &self,
+                _request: &ApiRequest,
+                response: &mut HttpResponse,
+            ) -> WebullResult<()> {
+                response.body = r#"{"success":true,"data":["2026-01-02"]}"#.to_string();
+                Ok(())
+            }
+        }
+
+        let fake = FakeTransport::new();
+        let client = logged_in(WebullClient::builder().with_middleware(Tagging), &fake).await;
+
+        let days = client.market_data().get_market_calendar().await.unwrap();
+        assert_eq!(days, vec!["2026-01-02".to_string()]);
+
+        let sent = fake.sent();
+        let calendar = sent.last().unwrap();
+        assert_eq!(
+            calendar.url.as_str(),
+            format!(
+                "{}/api/securities/financial/calendar?market=US",
+                client.config().base_url
+            )
+        );
+        assert_eq!(calendar.headers["x-team"], "quant");
+        assert_eq!(calendar.headers[AUTHORIZATION], "Bearer a");
+    }
+
+    #[tokio::test]
+    async fn concurrent_identical_gets_share_one_request() {
+        let fake = FakeTransport::with_handler(|request| {
+            (request.url.path() == "/api/account/getSecAccountList")
+                .then(|| HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR, ""))
+        })
+        .with_latency(Duration::from_millis(20));
+        let client = logged_in(
+            WebullClient::builder().with_retry_policy(RetryPolicy::none()),
+            &fake,
+        )
+        .await;
+
+        let (first, second) = (client.accounts(), client.clone().accounts());
+        let (first, second) = tokio::join!(first.get_accounts(), second.get_accounts());
+        assert_eq!(first.unwrap_err().kind(), ErrorKind::ServerError);
+        assert_eq!(second.unwrap_err().kind(), ErrorKind::ServerError);
+        assert_eq!(count(&fake, "GET /api/account/getSecAccountList"), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_only_idempotent_requests() {
+        let fake = FakeTransport::with_handler(|request| {
+            (request.url.path() != "/api/passport/login/v5/account")
+                .then(|| HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""))
+        });
+        let policy = RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_backoff(BackoffStrategy::Constant(Duration::ZERO));
+        let client = logged_in(WebullClient::builder().with_retry_policy(policy), &fake).await;
+
+        let err = client.market_data().get_quote("AAPL").await.unwrap_err();
+        assert_eq!(err.attempts(), 3);
+        assert_eq!(count(&fake, "GET /api/quote/tickerRealTimes/AAPL"), 3);
+
+        let request = OrderRequest::market().symbol("AAPL").quantity(1.0);
+        let err = client.orders().place_order(&request).await.unwrap_err();
+        assert_eq!(err.attempts(), 1);
+        assert_eq!(count(&fake, "POST /api/trade/order"), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_after_is_waited_out_once() {
+        let limited = AtomicBool::new(false);
+        let fake = FakeTransport::with_handler(move |request| {
+            let first_quote = request.url.path() == "/api/quote/tickerRealTimes/AAPL"
+                && !limited.swap(true, Ordering::SeqCst);
+            first_quote.then(|| {
+                let mut response = HttpResponse::new(StatusCode::TOO_MANY_REQUESTS, "");
+                response
+                    .headers
+                    .insert(RETRY_AFTER, HeaderValue::from_static("5"));
+                response
+            })
+        });
+        let client = logged_in(WebullClient::builder(), &fake).await;
+
+        let started = tokio::time::Instant::now();
+        let _ = client.market_data().get_quote("AAPL").await;
+        let waited = started.elapsed();
+
+        assert_eq!(count(&fake, "GET /api/quote/tickerRealTimes/AAPL"), 2);
+        assert!(
+            waited >= Duration::from_secs(5) && waited < Duration::from_secs(6),
+            "waited {:?}",
+            waited
+        );
+    }
+
+    /// Subscriber that keeps the `path` field of every span.
+    #[derive(Clone, Default)]
+    struct PathRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for PathRecorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "path" {
+                self.0.lock().unwrap().push(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl tracing::Subscriber for PathRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn requests_report_metrics() {
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl Metrics for Recorder {
+            fn increment_counter(&self, name: &str, labels: &[(&str, &str)]) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {:?}", name, labels));
+            }
+        }
+
+        let fake = FakeTransport::with_handler(|request| {
+            (request.url.path() == "/api/quote/tickerRealTimes/TSLA")
+                .then(|| HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""))
+        });
+        let recorder = Recorder::default();
+        let client = logged_in(
+            WebullClient::builder()
+                .with_metrics(recorder.clone())
+                .with_retry_policy(
+                    RetryPolicy::new().with_backoff(BackoffStrategy::Constant(Duration::ZERO)),
+                ),
+            &fake,
+        )
+        .await;
+
+        let result = client.market_data().get_quote("TSLA").await;
+        assert!(result.is_err());
+
+        let recorded = recorder.0.lock().unwrap();
+        assert!(recorded.contains(&format!(
+            "{} {:?}",
+            telemetry::REQUESTS_TOTAL,
+            [
+                ("category", "quote"),
+                ("method", "GET"),
+                ("status", "error")
+            ]
+        )));
+        assert_eq!(
+            recorded
+                .iter()
+                .filter(|metric| metric.starts_with(telemetry::RETRIES_TOTAL))
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn request_spans_leave_out_account_ids() {
+        let recorder = PathRecorder::default();
+        let _default = tracing::subscriber::set_default(recorder.clone());
+
+        let transport = FakeTransport::with_handler(|request| {
+            (request.url.path() == "/api/account/getSecAccountList").then(|| {
+                HttpResponse::new(
+                    StatusCode::OK,
+                    r#"{"success":true,"data":[{"id":"5550001","account_number":"5550001","account_type":"MARGIN","status":"ACTIVE","created_at":"2024-01-02T00:00:00Z","currency":"USD","paper_trading":false}]}"#,
+                )
+            })
+        });
+        let client = WebullClient::builder()
+            .with_transport(transport)
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+        client.accounts().get_positions("5550001").await.unwrap();
+
+        let paths = recorder.0.lock().unwrap();
+        assert!(paths.contains(&"/api/position/getUserPositions/{account_id}".to_string()));
+        assert!(paths.iter().all(|path| !path.contains("5550001")));
+    }
+}
@@ -0,0 +1,381 @@
+//! An in-process fake Webull server for offline testing.
+//!
+//! [`MockServer`] listens on a local port and implements the passport,
+//! account, order, market-data and watchlist paths used by the endpoint
+//! groups, so a real [`WebullClient`] can be tested end to end without
+//! network access. It is stateful: logins issue tokens that later requests
+//! must carry, orders move through [`OrderStatus`] as prices change, and
+//! fills update positions, balances and trade history. Errors and rate
+//! limiting can be scripted per path.
+//!
+//! Requires the `mock-server` feature.
+//!
+//! ```no_run
+//! # async fn example() -> webull_rs::WebullResult<()> {
+//! use webull_rs::mock_server::{self, MockServer, ScriptedResponse};
+//! use webull_rs::models::order::{OrderRequest, OrderSide};
+//!
+//! let server = MockServer::start().await.unwrap();
+//! let client = server.client_builder().build()?;
+//! client.login(mock_server::USERNAME, mock_server::PASSWORD).await?;
+//!
+//! // Limit orders rest until the price crosses them
+//! let order = OrderRequest::limit()
+//!     .symbol("AAPL")
+//!     .quantity(10.0)
+//!     .price(180.0)
+//!     .side(OrderSide::Buy);
+//! let placed = client.orders().place_order(&order).await?;
+//! server.set_price("AAPL", 179.5);
+//!
+//! // The next quote request is rejected with a 429
+//! server.script("/api/quote", ScriptedResponse::rate_limited(1));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`OrderStatus`]: crate::models::order::OrderStatus
+
+mod routes;
+mod state;
+
+use crate::client::WebullClientBuilder;
+use crate::models::order::Order;
+use crate::WebullClient;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Response, Server};
+use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use serde_json::json;
+use state::MockState;
+use std::convert::Infallible;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Username accepted by the mock server.
+pub const USERNAME: &str = "mock-user";
+
+/// Password accepted by the mock server.
+pub const PASSWORD: &str = "mock-password";
+
+/// Code accepted by the multi-factor authentication path.
+pub const MFA_CODE: &str = "123456";
+
+/// ID of the live account.
+pub const LIVE_ACCOUNT_ID: &str = "LIVE-ACCOUNT";
+
+/// ID of the paper trading account.
+pub const PAPER_ACCOUNT_ID: &str = "PAPER-ACCOUNT";
+
+/// A response the mock server sends instead of handling a request.
+#[derive(Debug, Clone)]
+pub struct ScriptedResponse {
+    /// HTTP status
+    pub status: StatusCode,
+
+    /// Extra response headers
+    pub headers: Vec<(String, String)>,
+
+    /// Response body
+    pub body: String,
+}
+
+impl ScriptedResponse {
+    /// Create a response with a status and raw body.
+    pub fn new(status: StatusCode, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Create a Webull error response with a business code and message.
+    pub fn error(status: StatusCode, code: &str, message: &str) -> Self {
+        let body = json!({ "success": false, "code": code, "msg": message });
+        Self::new(status, body.to_string())
+    }
+
+    /// Create a 429 response asking the client to wait before retrying.
+    pub fn rate_limited(retry_after_secs: u64) -> Self {
+        Self::error(
+            StatusCode::TOO_MANY_REQUESTS,
+            "TOO_MANY_REQUESTS",
+            "Rate limit exceeded",
+        )
+        .with_header(RETRY_AFTER.as_str(), &retry_after_secs.to_string())
+    }
+
+    /// Create a 500 response.
+    pub fn server_error() -> Self {
+        Self::error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "SYSTEM_ERROR",
+            "Internal error",
+        )
+    }
+
+    /// Add a response header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A scripted response waiting to be sent.
+pub(crate) struct Script {
+    method: Option<Method>,
+    path_prefix: String,
+    remaining: usize,
+    response: ScriptedResponse,
+}
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: Method,
+
+    /// Request path
+    pub path: String,
+
+    /// Query string, if any
+    pub query: Option<String>,
+
+    /// Request body
+    pub body: String,
+
+    /// HTTP status of the response
+    pub status: StatusCode,
+}
+
+/// An in-process fake Webull server.
+///
+/// The server shuts down when it is dropped.
+pub struct MockServer {
+    /// Address the server listens on
+    addr: SocketAddr,
+
+    /// Server state
+    state: Arc<Mutex<MockState>>,
+
+    /// Signal to stop the server
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Start a server on a random local port.
+    ///
+    /// The server starts with a live and a paper account holding $100,000 in
+    /// cash each, and quotes for AAPL, MSFT and TSLA.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let shared = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = shared.clone();
+            async move { Ok::<_, Infallible>(service_fn(move |request| serve(state.clone(), request))) }
+        });
+
+        let (shutdown, signal) = oneshot::channel();
+        let server = Server::from_tcp(listener)
+            .map_err(io::Error::other)?
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                let _ = signal.await;
+            });
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                log::warn!("Mock Webull server failed: {}", e);
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Get the base URL of the server.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Create a client builder pointed at the server.
+    pub fn client_builder(&self) -> WebullClientBuilder {
+        WebullClient::builder().with_custom_url(self.url())
+    }
+
+    /// Add a user the server accepts, besides [`USERNAME`].
+    pub fn add_user(&self, username: &str, password: &str) {
+        self.state().add_user(username, password);
+    }
+
+    /// Issue an access token without logging in, e.g. to seed a token store.
+    pub fn issue_token(&self) -> crate::auth::AccessToken {
+        let tokens = self.state().issue_tokens();
+        crate::auth::AccessToken {
+            token: tokens.access_token.into(),
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(tokens.expires_in),
+            refresh_token: Some(tokens.refresh_token.into()),
+        }
+    }
+
+    /// Answer the next request under a path prefix with a scripted response.
+    pub fn script(&self, path_prefix: &str, response: ScriptedResponse) {
+        self.script_times(None, path_prefix, 1, response);
+    }
+
+    /// Answer the next `times` requests with a method under a path prefix with
+    /// a scripted response.
+    ///
+    /// Scripts are matched in the order they were added. Pass `None` as the
+    /// method to match any method.
+    pub fn script_times(
+        &self,
+        method: Option<Method>,
+        path_prefix: &str,
+        times: usize,
+        response: ScriptedResponse,
+    ) {
+        self.state().scripts.push(Script {
+            method,
+            path_prefix: path_prefix.to_string(),
+            remaining: times,
+            response,
+        });
+    }
+
+    /// Set the last price of a symbol, adding it if it is new.
+    ///
+    /// Open orders that the new price makes marketable are filled at it.
+    pub fn set_price(&self, symbol: &str, price: f64) {
+        self.state().set_price(symbol, price);
+    }
+
+    /// Fill an open order in either account.
+    ///
+    /// `quantity` defaults to the unfilled quantity and `price` to the
+    /// order's limit price or the last price.
+    pub fn fill_order(
+        &self,
+        order_id: &str,
+        quantity: Option<f64>,
+        price: Option<f64>,
+    ) -> Result<Order, String> {
+        self.state()
+            .fill_order(order_id, quantity, price)
+            .map_err(|rejection| rejection.message)
+    }
+
+    /// Get the requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Count the requests received with a method under a path prefix.
+    pub fn request_count(&self, method: Method, path_prefix: &str) -> usize {
+        self.state()
+            .requests
+            .iter()
+            .filter(|request| request.method == method && request.path.starts_with(path_prefix))
+            .count()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// Handle one HTTP request.
+async fn serve(
+    state: Arc<Mutex<MockState>>,
+    request: hyper::Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+    let body = String::from_utf8_lossy(&body).into_owned();
+
+    let path = parts.uri.path().to_string();
+    let query = parts.uri.query().map(str::to_string);
+    let bearer = parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let mut state = state.lock().unwrap();
+    let script = state.scripts.iter_mut().position(|script| {
+        script.remaining > 0
+            && script.method.as_ref().is_none_or(|m| *m == parts.method)
+            && path.starts_with(&script.path_prefix)
+    });
+
+    let (status, headers, response_body) = match script {
+        Some(index) => {
+            let script = &mut state.scripts[index];
+            script.remaining -= 1;
+            let response = script.response.clone();
+            if script.remaining == 0 {
+                state.scripts.remove(index);
+            }
+            (response.status, response.headers, response.body)
+        }
+        None => {
+            let request = routes::Request {
+                method: &parts.method,
+                path: &path,
+                query: query
+                    .as_deref()
+                    .map(|query| {
+                        url::form_urlencoded::parse(query.as_bytes())
+                            .into_owned()
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                bearer,
+                body: &body,
+            };
+            let (status, body) = routes::handle(&mut state, &request);
+            (status, Vec::new(), body)
+        }
+    };
+
+    state.requests.push(RecordedRequest {
+        method: parts.method.clone(),
+        path,
+        query,
+        body,
+        status,
+    });
+
+    let mut response = Response::new(Body::from(response_body));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+
+    Ok(response)
+}
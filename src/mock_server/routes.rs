@@ -0,0 +1,390 @@
+use super::state::{IssuedTokens, MockState, Rejection, INITIAL_CASH};
+use crate::endpoints::watchlists::{CreateWatchlistRequest, ModifyWatchlistRequest};
+use crate::models::account::{PaperAccountResetRequest, PaperFundingRequest};
+use crate::models::market::OptionChain;
+use crate::models::order::{Order, OrderRequest, OrderResponse, OrderStatus};
+use crate::models::response::ApiResponse;
+use crate::utils::crypto::decrypt_data;
+use chrono::{Datelike, Duration, Utc, Weekday};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// An incoming request, reduced to what the routes need.
+pub(crate) struct Request<'a> {
+    pub method: &'a Method,
+    pub path: &'a str,
+    pub query: HashMap<String, String>,
+    pub bearer: Option<&'a str>,
+    pub body: &'a str,
+}
+
+impl Request<'_> {
+    fn query(&self, key: &str) -> Option<&str> {
+        self.query.get(key).map(String::as_str)
+    }
+
+    /// Get a comma-separated query parameter as a list.
+    fn query_list(&self, key: &str) -> Vec<&str> {
+        self.query(key)
+            .map(|value| value.split(',').filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    fn json<T: DeserializeOwned>(&self) -> Result<T, Rejection> {
+        serde_json::from_str(self.body).map_err(|e| {
+            Rejection::bad_request("INVALID_PARAMETER", format!("Invalid request body: {}", e))
+        })
+    }
+}
+
+/// Status and JSON body of a response.
+pub(crate) type Reply = (StatusCode, String);
+
+/// Route a request and build the response.
+pub(crate) fn handle(state: &mut MockState, request: &Request) -> Reply {
+    let result = if let Some(rest) = request.path.strip_prefix("/api/passport/") {
+        return passport(state, request, rest).unwrap_or_else(rejected);
+    } else if !request
+        .bearer
+        .is_some_and(|token| state.is_authorized(token))
+    {
+        Err(Rejection::unauthorized())
+    } else {
+        route(state, request)
+    };
+
+    match result {
+        Ok(data) => (
+            StatusCode::OK,
+            serde_json::to_string(&ApiResponse::success(data)).unwrap(),
+        ),
+        Err(rejection) => rejected(rejection),
+    }
+}
+
+/// Build the response for a rejected request.
+pub(crate) fn rejected(rejection: Rejection) -> Reply {
+    let body = json!({
+        "success": false,
+        "code": rejection.code,
+        "msg": rejection.message,
+    });
+    (rejection.status, body.to_string())
+}
+
+/// Handle login, MFA, token refresh and logout.
+///
+/// Passport responses are not wrapped in the usual `success`/`data` envelope.
+fn passport(state: &mut MockState, request: &Request, path: &str) -> Result<Reply, Rejection> {
+    let body: Value = request.json()?;
+    let field = |name: &str| body.get(name).and_then(Value::as_str).unwrap_or_default();
+
+    let tokens = match path {
+        "login/v5/account" => {
+            let password = decrypt_data(field("password"), "").unwrap_or_default();
+            state.login(field("username"), &password)?
+        }
+        "verificationCode/verify" => {
+            state.verify_mfa(field("username"), field("verificationCode"))?
+        }
+        "refreshToken" => state.refresh(field("refreshToken"))?,
+        "logout" => {
+            state.logout(field("accessToken"));
+            return Ok((StatusCode::OK, json!({ "success": true }).to_string()));
+        }
+        _ => return Err(not_found(request)),
+    };
+
+    Ok((StatusCode::OK, token_body(&tokens)))
+}
+
+fn token_body(tokens: &IssuedTokens) -> String {
+    json!({
+        "access_token": tokens.access_token,
+        "refresh_token": tokens.refresh_token,
+        "expires_in": tokens.expires_in,
+    })
+    .to_string()
+}
+
+/// Route an authenticated request to its handler.
+fn route(state: &mut MockState, request: &Request) -> Result<Value, Rejection> {
+    // Trading paths under /api/paper use the paper account
+    let (paper, path) = match request.path.strip_prefix("/api/paper/") {
+        Some(rest) => (true, rest),
+        None => (false, request.path.trim_start_matches("/api/")),
+    };
+    let segments: Vec<&str> = path.split('/').collect();
+
+    match (request.method.clone(), segments.as_slice()) {
+        // Accounts
+        (Method::GET, ["account", "getSecAccountList"]) => to_value(vec![state.account(paper)]),
+        (Method::GET, ["account", "getAccountMembers", id]) => {
+            state.check_account(paper, id)?;
+            to_value(state.account(paper))
+        }
+        (Method::GET, ["account", "profile", id]) => {
+            state.check_account(paper, id)?;
+            to_value(state.profile(paper))
+        }
+        (Method::GET, ["asset", "getAssetSummary", id]) => {
+            state.check_account(paper, id)?;
+            to_value(state.balance(paper))
+        }
+        (Method::GET, ["account", "balance"]) => {
+            state.check_account(paper, request.query("account_id").unwrap_or_default())?;
+            to_value(state.balance(paper))
+        }
+        (Method::GET, ["position", "getUserPositions", id]) => {
+            state.check_account(paper, id)?;
+            to_value(state.positions(paper))
+        }
+        (Method::GET, ["position", "getUserPositions", id, symbol]) => {
+            state.check_account(paper, id)?;
+            let position = state
+                .positions(paper)
+                .into_iter()
+                .find(|position| position.symbol == *symbol)
+                .ok_or_else(|| {
+                    Rejection::not_found("POSITION_NOT_FOUND", format!("No {} position", symbol))
+                })?;
+            to_value(position)
+        }
+        (Method::GET, ["account", "positions"]) => {
+            state.check_account(paper, request.query("account_id").unwrap_or_default())?;
+            let positions = state.positions(paper);
+            let start = match request.query("last_instrument_id") {
+                Some(last) => positions
+                    .iter()
+                    .position(|position| position.instrument_id == last)
+                    .map_or(positions.len(), |index| index + 1),
+                None => 0,
+            };
+            to_value(page(positions, start, page_size(request)))
+        }
+        (Method::GET, ["trade", "history", id]) => {
+            state.check_account(paper, id)?;
+            to_value(state.trades(paper))
+        }
+        (Method::GET, ["trade", "history"]) => {
+            state.check_account(paper, request.query("account_id").unwrap_or_default())?;
+            let size = page_size(request);
+            let number: usize = request
+                .query("page")
+                .and_then(|page| page.parse().ok())
+                .unwrap_or(1);
+            to_value(page(
+                state.trades(paper),
+                number.saturating_sub(1) * size,
+                size,
+            ))
+        }
+        (Method::POST, ["account", "reset"]) if paper => {
+            let reset: PaperAccountResetRequest = request.json()?;
+            state.check_account(true, &reset.account_id)?;
+            let cash = if reset.initial_balance > 0.0 {
+                reset.initial_balance
+            } else {
+                INITIAL_CASH
+            };
+            to_value(state.reset_paper(cash))
+        }
+        (Method::POST, ["account", "fund"]) if paper => {
+            let funding: PaperFundingRequest = request.json()?;
+            state.check_account(true, &funding.account_id)?;
+            to_value(state.fund_paper(funding.amount)?)
+        }
+
+        // Orders
+        (Method::POST, ["trade", "order"]) => {
+            let order = state.place_order(paper, request.json()?)?;
+            to_value(OrderResponse::from(order))
+        }
+        (Method::GET, ["trade", "order", id]) => to_value(state.order(paper, id)?),
+        (Method::PUT, ["trade", "modify", id]) => {
+            let modification: OrderRequest = request.json()?;
+            let order = state.modify_order(paper, id, modification)?;
+            to_value(OrderResponse::from(order))
+        }
+        (Method::DELETE, ["trade", "cancel", id]) => {
+            state.cancel_order(paper, id)?;
+            Ok(Value::Null)
+        }
+        (Method::GET, ["trade", "orders"]) => {
+            let status = request
+                .query("status")
+                .and_then(|status| serde_json::from_value::<OrderStatus>(json!(status)).ok());
+            let symbol = request.query("symbol");
+            let limit = request
+                .query("limit")
+                .and_then(|limit| limit.parse().ok())
+                .unwrap_or(usize::MAX);
+            let orders: Vec<_> = state
+                .orders(paper)
+                .into_iter()
+                .filter(|order| status.is_none_or(|status| order.status == status))
+                .filter(|order| symbol.is_none_or(|symbol| order.symbol == symbol))
+                .take(limit)
+                .collect();
+            to_value(orders)
+        }
+        (Method::GET, ["trade", "active"]) => {
+            to_value(orders_where(state, paper, OrderStatus::is_open))
+        }
+        (Method::GET, ["trade", "filled"]) => to_value(orders_where(state, paper, |status| {
+            status == OrderStatus::Filled
+        })),
+        (Method::GET, ["trade", "account", id, "orders", "open"]) => {
+            state.check_account(paper, id)?;
+            to_value(orders_where(state, paper, OrderStatus::is_open))
+        }
+        (Method::GET, ["trade", "account", id, "orders", "today"]) => {
+            state.check_account(paper, id)?;
+            to_value(state.orders(paper))
+        }
+        (Method::GET, ["trade", "orders", "open"])
+        | (Method::GET, ["trade", "orders", "today"]) => {
+            state.check_account(paper, request.query("account_id").unwrap_or_default())?;
+            let orders = if segments[2] == "open" {
+                orders_where(state, paper, OrderStatus::is_open)
+            } else {
+                state.orders(paper)
+            };
+            let start = match request.query("last_client_order_id") {
+                Some(last) => orders
+                    .iter()
+                    .position(|order| order.id == last)
+                    .map_or(orders.len(), |index| index + 1),
+                None => 0,
+            };
+            to_value(page(orders, start, page_size(request)))
+        }
+        (_, ["trade", "option", ..]) => Err(Rejection::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "NOT_SUPPORTED",
+            "Option orders are not supported by the mock server",
+        )),
+
+        // Market data
+        (Method::GET, ["quote", "tickerRealTimes", symbol]) => to_value(state.quote(symbol)?),
+        (Method::GET, ["quote", "tickerRealTimes"]) => {
+            to_value(state.quotes(&request.query_list("symbols")))
+        }
+        (Method::GET, ["quote", "snapshot"]) => {
+            to_value(state.quotes(&request.query_list("symbols")))
+        }
+        (Method::GET, ["quote", "history", "bars"]) => {
+            let symbol = request.query("symbol").unwrap_or_default();
+            to_value(state.bars(symbol, count(request))?)
+        }
+        (Method::GET, ["quote", "eod", "bars"]) => {
+            let mut bars = Vec::new();
+            for instrument_id in request.query_list("instrument_ids") {
+                if let Some(symbol) = state.symbol_of(instrument_id) {
+                    bars.extend(state.bars(&symbol, count(request))?);
+                }
+            }
+            to_value(bars)
+        }
+        (Method::GET, ["quote", "instruments"]) => {
+            to_value(state.instruments(&request.query_list("symbols")))
+        }
+        (Method::GET, ["quote", "corp", "action"]) => Ok(json!([])),
+        (Method::GET, ["options", "list"]) => to_value(OptionChain {
+            underlying_symbol: request
+                .query("underlying_symbol")
+                .unwrap_or_default()
+                .to_string(),
+            expiration_dates: Vec::new(),
+            strike_prices: Vec::new(),
+            contracts: Vec::new(),
+        }),
+        (Method::GET, ["securities", "news", "list"]) => Ok(json!([])),
+        (Method::GET, ["securities", "financial", "calendar"]) => to_value(trading_days(5)),
+
+        // Watchlists
+        (Method::GET, ["wlas", "watchlist"]) => to_value(state.watchlists()),
+        (Method::GET, ["wlas", "watchlist", id]) => to_value(state.watchlist(id)?),
+        (Method::POST, ["wlas", "watchlist"]) => {
+            let create: CreateWatchlistRequest = request.json()?;
+            to_value(state.create_watchlist(create.name, create.symbols))
+        }
+        (Method::POST, ["wlas", "watchlist", "modify"]) => {
+            let modify: ModifyWatchlistRequest = request.json()?;
+            to_value(state.modify_watchlist(
+                &modify.id,
+                modify.name,
+                modify.add_symbols.unwrap_or_default(),
+                modify.remove_symbols.unwrap_or_default(),
+            )?)
+        }
+        (Method::DELETE, ["wlas", "watchlist", "delete", id]) => {
+            state.delete_watchlist(id)?;
+            Ok(Value::Null)
+        }
+
+        _ => Err(not_found(request)),
+    }
+}
+
+fn to_value<T: Serialize>(data: T) -> Result<Value, Rejection> {
+    serde_json::to_value(data).map_err(|e| {
+        Rejection::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "SYSTEM_ERROR",
+            e.to_string(),
+        )
+    })
+}
+
+fn not_found(request: &Request) -> Rejection {
+    Rejection::not_found(
+        "NOT_FOUND",
+        format!("No mock route for {} {}", request.method, request.path),
+    )
+}
+
+fn orders_where(
+    state: &MockState,
+    paper: bool,
+    filter: impl Fn(OrderStatus) -> bool,
+) -> Vec<Order> {
+    state
+        .orders(paper)
+        .into_iter()
+        .filter(|order| filter(order.status))
+        .collect()
+}
+
+fn page_size(request: &Request) -> usize {
+    request
+        .query("page_size")
+        .and_then(|size| size.parse().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(100)
+}
+
+fn page<T>(items: Vec<T>, start: usize, size: usize) -> Vec<T> {
+    items.into_iter().skip(start).take(size).collect()
+}
+
+fn count(request: &Request) -> usize {
+    request
+        .query("count")
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(200)
+}
+
+/// Get the next weekdays, starting today, as `YYYY-MM-DD` dates.
+fn trading_days(count: usize) -> Vec<String> {
+    let today = Utc::now().date_naive();
+    (0..)
+        .map(|offset| today + Duration::days(offset))
+        .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        .take(count)
+        .map(|day| day.format("%Y-%m-%d").to_string())
+        .collect()
+}
@@ -0,0 +1,849 @@
+use super::{
+    RecordedRequest, Script, LIVE_ACCOUNT_ID, MFA_CODE, PAPER_ACCOUNT_ID, PASSWORD, USERNAME,
+};
+use crate::endpoints::watchlists::Watchlist;
+use crate::models::account::{
+    Account, AccountBalance, AccountProfile, AccountStatus, AccountType, Position, TradeHistory,
+};
+use crate::models::market::{Bar, Instrument, Quote};
+use crate::models::order::{Order, OrderRequest, OrderSide, OrderStatus, OrderType};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Quantities below this are treated as zero.
+const EPSILON: f64 = 1e-9;
+
+/// Starting cash of the live and paper accounts.
+pub(crate) const INITIAL_CASH: f64 = 100_000.0;
+
+/// A request the mock server rejected, with the HTTP status, business code
+/// and message of the error response.
+#[derive(Debug, Clone)]
+pub(crate) struct Rejection {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+}
+
+impl Rejection {
+    pub fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn not_found(code: &str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, code, message)
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "UNAUTHORIZED", "Unauthorized")
+    }
+}
+
+/// Tokens issued by a login or refresh.
+pub(crate) struct IssuedTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: i64,
+}
+
+/// Shares of one symbol held in an account.
+struct Holding {
+    quantity: f64,
+    average_cost: f64,
+    opened_at: DateTime<Utc>,
+}
+
+/// The account, cash, holdings and orders of one trading mode.
+pub(crate) struct Ledger {
+    account: Account,
+    cash: f64,
+    holdings: BTreeMap<String, Holding>,
+    orders: Vec<Order>,
+    client_order_ids: HashMap<String, String>,
+    trades: Vec<TradeHistory>,
+}
+
+impl Ledger {
+    fn new(id: &str, paper_trading: bool) -> Self {
+        Self {
+            account: Account {
+                id: id.to_string(),
+                account_number: format!("{}-0001", id),
+                account_type: AccountType::Cash,
+                status: AccountStatus::Active,
+                created_at: Utc::now(),
+                currency: "USD".to_string(),
+                paper_trading,
+                region: Some("US".to_string()),
+                name: Some(USERNAME.to_string()),
+                email: None,
+                phone: None,
+            },
+            cash: INITIAL_CASH,
+            holdings: BTreeMap::new(),
+            orders: Vec::new(),
+            client_order_ids: HashMap::new(),
+            trades: Vec::new(),
+        }
+    }
+
+    /// Reset the account to a starting balance with no holdings or orders.
+    fn reset(&mut self, cash: f64) {
+        self.cash = cash;
+        self.holdings.clear();
+        self.orders.clear();
+        self.client_order_ids.clear();
+        self.trades.clear();
+    }
+
+    /// Cash held back for the unfilled part of open buy orders.
+    fn reserved_cash(&self, quotes: &BTreeMap<String, f64>) -> f64 {
+        self.orders
+            .iter()
+            .filter(|order| order.status.is_open() && is_buy(order.side))
+            .map(|order| remaining(order) * reference_price(order, quotes))
+            .sum()
+    }
+
+    /// Shares held back for the unfilled part of open sell orders.
+    fn reserved_shares(&self, symbol: &str) -> f64 {
+        self.orders
+            .iter()
+            .filter(|order| order.status.is_open() && !is_buy(order.side) && order.symbol == symbol)
+            .map(remaining)
+            .sum()
+    }
+
+    /// Check that the account can afford or cover an order.
+    fn check_funds(&self, order: &Order, quotes: &BTreeMap<String, f64>) -> Result<(), Rejection> {
+        let quantity = remaining(order);
+        if is_buy(order.side) {
+            let cost = quantity * reference_price(order, quotes);
+            if cost > self.cash - self.reserved_cash(quotes) + EPSILON {
+                return Err(Rejection::bad_request(
+                    "INSUFFICIENT_BUYING_POWER",
+                    format!("Order cost {:.2} exceeds buying power", cost),
+                ));
+            }
+        } else {
+            let held = self
+                .holdings
+                .get(&order.symbol)
+                .map(|holding| holding.quantity)
+                .unwrap_or_default();
+            if quantity > held - self.reserved_shares(&order.symbol) + EPSILON {
+                return Err(Rejection::bad_request(
+                    "INSUFFICIENT_POSITION",
+                    format!("Not enough {} shares to sell", order.symbol),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill part or all of an order and update cash and holdings.
+    fn fill(&mut self, index: usize, quantity: f64, price: f64) {
+        let now = Utc::now();
+        let order = &mut self.orders[index];
+        let quantity = quantity.min(remaining(order));
+        if quantity <= EPSILON {
+            return;
+        }
+
+        let filled = order.filled_quantity + quantity;
+        let average = order.average_fill_price.unwrap_or_default();
+        order.average_fill_price =
+            Some((average * order.filled_quantity + price * quantity) / filled);
+        order.filled_quantity = filled;
+        order.status = if remaining(order) <= EPSILON {
+            OrderStatus::Filled
+        } else {
+            OrderStatus::PartiallyFilled
+        };
+        order.updated_at = now;
+
+        let (symbol, side, order_id) = (order.symbol.clone(), order.side, order.id.clone());
+        let amount = quantity * price;
+        if is_buy(side) {
+            self.cash -= amount;
+            let holding = self.holdings.entry(symbol.clone()).or_insert(Holding {
+                quantity: 0.0,
+                average_cost: 0.0,
+                opened_at: now,
+            });
+            let total = holding.quantity + quantity;
+            holding.average_cost =
+                (holding.average_cost * holding.quantity + price * quantity) / total;
+            holding.quantity = total;
+        } else {
+            self.cash += amount;
+            if let Some(holding) = self.holdings.get_mut(&symbol) {
+                holding.quantity -= quantity;
+                if holding.quantity <= EPSILON {
+                    self.holdings.remove(&symbol);
+                }
+            }
+        }
+
+        self.trades.push(TradeHistory {
+            id: format!("TRADE-{}", self.trades.len() + 1),
+            symbol: symbol.clone(),
+            instrument_id: instrument_id(&symbol),
+            name: Some(symbol),
+            action: if is_buy(side) { "BUY" } else { "SELL" }.to_string(),
+            quantity,
+            price,
+            amount,
+            fees: Some(0.0),
+            trade_time: now,
+            status: "FILLED".to_string(),
+            order_id: Some(order_id),
+            currency: Some(self.account.currency.clone()),
+            exchange: Some("NASDAQ".to_string()),
+            security_type: Some("STK".to_string()),
+        });
+    }
+
+    /// Fill every open order that the current prices make marketable.
+    fn match_orders(&mut self, quotes: &BTreeMap<String, f64>) {
+        for index in 0..self.orders.len() {
+            let order = &mut self.orders[index];
+            if !order.status.is_open() {
+                continue;
+            }
+            let Some(&last) = quotes.get(&order.symbol) else {
+                continue;
+            };
+
+            // A triggered stop becomes a market or limit order
+            if matches!(order.order_type, OrderType::Stop | OrderType::StopLimit) {
+                let stop = order.stop_price.unwrap_or(last);
+                let triggered = if is_buy(order.side) {
+                    last >= stop
+                } else {
+                    last <= stop
+                };
+                if !triggered {
+                    continue;
+                }
+                order.order_type = match order.order_type {
+                    OrderType::Stop => OrderType::Market,
+                    _ => OrderType::Limit,
+                };
+            }
+
+            let marketable = match order.order_type {
+                OrderType::Market => true,
+                OrderType::Limit => match (order.price, is_buy(order.side)) {
+                    (Some(limit), true) => last <= limit,
+                    (Some(limit), false) => last >= limit,
+                    (None, _) => false,
+                },
+                _ => false,
+            };
+            if marketable {
+                let quantity = remaining(order);
+                self.fill(index, quantity, last);
+            }
+        }
+    }
+
+    fn position(&self, symbol: &str, holding: &Holding, price: f64) -> Position {
+        let market_value = holding.quantity * price;
+        let cost = holding.quantity * holding.average_cost;
+        Position {
+            symbol: symbol.to_string(),
+            instrument_id: instrument_id(symbol),
+            quantity: holding.quantity,
+            cost_basis: holding.average_cost,
+            market_value,
+            unrealized_profit_loss: market_value - cost,
+            unrealized_profit_loss_percentage: percentage(market_value - cost, cost),
+            current_price: price,
+            opened_at: holding.opened_at,
+            name: Some(symbol.to_string()),
+            security_type: Some("STK".to_string()),
+            exchange: Some("NASDAQ".to_string()),
+            currency: Some(self.account.currency.clone()),
+            side: Some("LONG".to_string()),
+            status: Some("OPEN".to_string()),
+            tradable_quantity: Some(holding.quantity - self.reserved_shares(symbol)),
+            unsettled_quantity: Some(0.0),
+        }
+    }
+}
+
+/// State of the mock Webull server.
+pub(crate) struct MockState {
+    users: HashMap<String, String>,
+    access_tokens: HashSet<String>,
+    refresh_tokens: HashSet<String>,
+    next_id: u64,
+    quotes: BTreeMap<String, f64>,
+    live: Ledger,
+    paper: Ledger,
+    watchlists: BTreeMap<String, Watchlist>,
+    pub scripts: Vec<Script>,
+    pub requests: Vec<RecordedRequest>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        let quotes = [("AAPL", 190.0), ("MSFT", 420.0), ("TSLA", 250.0)]
+            .into_iter()
+            .map(|(symbol, price)| (symbol.to_string(), price))
+            .collect();
+
+        Self {
+            users: HashMap::from([(USERNAME.to_string(), PASSWORD.to_string())]),
+            access_tokens: HashSet::new(),
+            refresh_tokens: HashSet::new(),
+            next_id: 0,
+            quotes,
+            live: Ledger::new(LIVE_ACCOUNT_ID, false),
+            paper: Ledger::new(PAPER_ACCOUNT_ID, true),
+            watchlists: BTreeMap::new(),
+            scripts: Vec::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl MockState {
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}-{}", prefix, self.next_id)
+    }
+
+    fn ledger(&self, paper: bool) -> &Ledger {
+        if paper {
+            &self.paper
+        } else {
+            &self.live
+        }
+    }
+
+    fn ledger_mut(&mut self, paper: bool) -> &mut Ledger {
+        if paper {
+            &mut self.paper
+        } else {
+            &mut self.live
+        }
+    }
+
+    // Authentication
+
+    pub fn add_user(&mut self, username: &str, password: &str) {
+        self.users
+            .insert(username.to_string(), password.to_string());
+    }
+
+    pub fn login(&mut self, username: &str, password: &str) -> Result<IssuedTokens, Rejection> {
+        match self.users.get(username) {
+            Some(expected) if expected == password => Ok(self.issue_tokens()),
+            _ => Err(Rejection::unauthorized()),
+        }
+    }
+
+    pub fn verify_mfa(&mut self, username: &str, code: &str) -> Result<IssuedTokens, Rejection> {
+        if self.users.contains_key(username) && code == MFA_CODE {
+            Ok(self.issue_tokens())
+        } else {
+            Err(Rejection::unauthorized())
+        }
+    }
+
+    pub fn refresh(&mut self, refresh_token: &str) -> Result<IssuedTokens, Rejection> {
+        if self.refresh_tokens.remove(refresh_token) {
+            Ok(self.issue_tokens())
+        } else {
+            Err(Rejection::unauthorized())
+        }
+    }
+
+    pub fn logout(&mut self, access_token: &str) {
+        self.access_tokens.remove(access_token);
+    }
+
+    pub fn issue_tokens(&mut self) -> IssuedTokens {
+        let access_token = self.next_id("access");
+        let refresh_token = self.next_id("refresh");
+        self.access_tokens.insert(access_token.clone());
+        self.refresh_tokens.insert(refresh_token.clone());
+
+        IssuedTokens {
+            access_token,
+            refresh_token,
+            expires_in: 3600,
+        }
+    }
+
+    pub fn is_authorized(&self, access_token: &str) -> bool {
+        self.access_tokens.contains(access_token)
+    }
+
+    // Accounts
+
+    pub fn account(&self, paper: bool) -> Account {
+        self.ledger(paper).account.clone()
+    }
+
+    pub fn check_account(&self, paper: bool, account_id: &str) -> Result<(), Rejection> {
+        if self.ledger(paper).account.id == account_id {
+            Ok(())
+        } else {
+            Err(Rejection::not_found(
+                "ACCOUNT_NOT_FOUND",
+                format!("Account {} not found", account_id),
+            ))
+        }
+    }
+
+    pub fn profile(&self, paper: bool) -> AccountProfile {
+        let account = &self.ledger(paper).account;
+        AccountProfile {
+            id: account.id.clone(),
+            account_number: account.account_number.clone(),
+            account_type: account.account_type,
+            status: account.status,
+            region: account.region.clone().unwrap_or_default(),
+            name: account.name.clone().unwrap_or_default(),
+            email: None,
+            phone: None,
+            currency: account.currency.clone(),
+            paper_trading: account.paper_trading,
+            created_at: account.created_at,
+            kyc_status: Some("APPROVED".to_string()),
+            risk_level: None,
+            permissions: Some(vec!["STOCK".to_string()]),
+        }
+    }
+
+    pub fn balance(&self, paper: bool) -> AccountBalance {
+        let ledger = self.ledger(paper);
+        let (market_value, cost) = ledger
+            .holdings
+            .iter()
+            .map(|(symbol, holding)| {
+                (
+                    holding.quantity * self.price(symbol),
+                    holding.quantity * holding.average_cost,
+                )
+            })
+            .fold((0.0, 0.0), |(value, cost), (v, c)| (value + v, cost + c));
+        let buying_power = ledger.cash - ledger.reserved_cash(&self.quotes);
+
+        AccountBalance {
+            cash: ledger.cash,
+            buying_power,
+            market_value,
+            total_value: ledger.cash + market_value,
+            unrealized_profit_loss: market_value - cost,
+            unrealized_profit_loss_percentage: percentage(market_value - cost, cost),
+            currency: ledger.account.currency.clone(),
+            settled_cash: Some(ledger.cash),
+            unsettled_cash: Some(0.0),
+            withdrawable_cash: Some(ledger.cash),
+            tradable_cash: Some(buying_power),
+            margin_buying_power: None,
+            option_buying_power: None,
+            day_trading_buying_power: None,
+        }
+    }
+
+    pub fn positions(&self, paper: bool) -> Vec<Position> {
+        let ledger = self.ledger(paper);
+        ledger
+            .holdings
+            .iter()
+            .map(|(symbol, holding)| ledger.position(symbol, holding, self.price(symbol)))
+            .collect()
+    }
+
+    pub fn trades(&self, paper: bool) -> Vec<TradeHistory> {
+        self.ledger(paper).trades.clone()
+    }
+
+    pub fn reset_paper(&mut self, cash: f64) -> AccountBalance {
+        self.paper.reset(cash);
+        self.balance(true)
+    }
+
+    pub fn fund_paper(&mut self, amount: f64) -> Result<AccountBalance, Rejection> {
+        if amount <= 0.0 {
+            return Err(Rejection::bad_request(
+                "INVALID_AMOUNT",
+                "Funding amount must be positive",
+            ));
+        }
+        self.paper.cash += amount;
+        Ok(self.balance(true))
+    }
+
+    // Orders
+
+    pub fn place_order(&mut self, paper: bool, request: OrderRequest) -> Result<Order, Rejection> {
+        // A resubmitted client order ID returns the original order
+        if let Some(client_order_id) = &request.client_order_id {
+            let ledger = self.ledger(paper);
+            if let Some(order_id) = ledger.client_order_ids.get(client_order_id) {
+                return self.order(paper, order_id);
+            }
+        }
+
+        if !self.quotes.contains_key(&request.symbol) {
+            return Err(Rejection::bad_request(
+                "SYMBOL_NOT_TRADABLE",
+                format!("{} is not tradable", request.symbol),
+            ));
+        }
+        if request.quantity <= 0.0 {
+            return Err(Rejection::bad_request(
+                "INVALID_QUANTITY",
+                "Quantity must be positive",
+            ));
+        }
+        if matches!(request.order_type, OrderType::Limit | OrderType::StopLimit)
+            && request.price.is_none()
+        {
+            return Err(Rejection::bad_request(
+                "INVALID_PRICE",
+                "Limit orders need a price",
+            ));
+        }
+        if matches!(request.order_type, OrderType::Stop | OrderType::StopLimit)
+            && request.stop_price.is_none()
+        {
+            return Err(Rejection::bad_request(
+                "INVALID_PRICE",
+                "Stop orders need a stop price",
+            ));
+        }
+
+        let now = Utc::now();
+        let order = Order {
+            id: self.next_id("ORDER"),
+            symbol: request.symbol,
+            quantity: request.quantity,
+            filled_quantity: 0.0,
+            price: request.price,
+            stop_price: request.stop_price,
+            status: OrderStatus::New,
+            side: request.side,
+            order_type: request.order_type,
+            time_in_force: request.time_in_force,
+            extended_hours: request.extended_hours,
+            created_at: now,
+            updated_at: now,
+            commission: 0.0,
+            rejected_reason: None,
+            average_fill_price: None,
+        };
+
+        let quotes = self.quotes.clone();
+        let ledger = self.ledger_mut(paper);
+        ledger.check_funds(&order, &quotes)?;
+        if let Some(client_order_id) = request.client_order_id {
+            ledger
+                .client_order_ids
+                .insert(client_order_id, order.id.clone());
+        }
+        let id = order.id.clone();
+        ledger.orders.push(order);
+        ledger.match_orders(&quotes);
+
+        self.order(paper, &id)
+    }
+
+    pub fn order(&self, paper: bool, order_id: &str) -> Result<Order, Rejection> {
+        self.ledger(paper)
+            .orders
+            .iter()
+            .find(|order| order.id == order_id)
+            .cloned()
+            .ok_or_else(|| order_not_found(order_id))
+    }
+
+    pub fn orders(&self, paper: bool) -> Vec<Order> {
+        self.ledger(paper).orders.clone()
+    }
+
+    pub fn modify_order(
+        &mut self,
+        paper: bool,
+        order_id: &str,
+        request: OrderRequest,
+    ) -> Result<Order, Rejection> {
+        let quotes = self.quotes.clone();
+        let ledger = self.ledger_mut(paper);
+        let index = open_order_index(ledger, order_id)?;
+
+        let mut modified = ledger.orders[index].clone();
+        modified.quantity = request.quantity;
+        modified.price = request.price.or(modified.price);
+        modified.stop_price = request.stop_price.or(modified.stop_price);
+        modified.time_in_force = request.time_in_force;
+        modified.updated_at = Utc::now();
+        if modified.quantity < modified.filled_quantity + EPSILON {
+            return Err(Rejection::bad_request(
+                "INVALID_QUANTITY",
+                "Quantity must exceed the filled quantity",
+            ));
+        }
+
+        // Check funds with the old order out of the way
+        let original = std::mem::replace(&mut ledger.orders[index], modified);
+        ledger.orders[index].status = OrderStatus::PendingReplace;
+        let check = ledger.check_funds(&ledger.orders[index], &quotes);
+        match check {
+            Ok(()) => {
+                ledger.orders[index].status = if original.filled_quantity > EPSILON {
+                    OrderStatus::PartiallyFilled
+                } else {
+                    OrderStatus::New
+                };
+                ledger.match_orders(&quotes);
+                self.order(paper, order_id)
+            }
+            Err(rejection) => {
+                ledger.orders[index] = original;
+                Err(rejection)
+            }
+        }
+    }
+
+    pub fn cancel_order(&mut self, paper: bool, order_id: &str) -> Result<Order, Rejection> {
+        let ledger = self.ledger_mut(paper);
+        let index = open_order_index(ledger, order_id)?;
+        let order = &mut ledger.orders[index];
+        order.status = OrderStatus::Canceled;
+        order.updated_at = Utc::now();
+        Ok(order.clone())
+    }
+
+    /// Fill an open order in either trading mode, returning the updated order.
+    pub fn fill_order(
+        &mut self,
+        order_id: &str,
+        quantity: Option<f64>,
+        price: Option<f64>,
+    ) -> Result<Order, Rejection> {
+        let paper = self.paper.orders.iter().any(|order| order.id == order_id);
+        let quotes = self.quotes.clone();
+        let ledger = self.ledger_mut(paper);
+        let index = open_order_index(ledger, order_id)?;
+
+        let order = &ledger.orders[index];
+        let quantity = quantity.unwrap_or_else(|| remaining(order));
+        let price = price.unwrap_or_else(|| order.price.unwrap_or(reference_price(order, &quotes)));
+        ledger.fill(index, quantity, price);
+
+        self.order(paper, order_id)
+    }
+
+    // Market data
+
+    pub fn set_price(&mut self, symbol: &str, price: f64) {
+        self.quotes.insert(symbol.to_string(), price);
+        let quotes = self.quotes.clone();
+        self.live.match_orders(&quotes);
+        self.paper.match_orders(&quotes);
+    }
+
+    fn price(&self, symbol: &str) -> f64 {
+        self.quotes.get(symbol).copied().unwrap_or_default()
+    }
+
+    pub fn quote(&self, symbol: &str) -> Result<Quote, Rejection> {
+        let price = *self.quotes.get(symbol).ok_or_else(|| {
+            Rejection::not_found("SYMBOL_NOT_FOUND", format!("Unknown symbol {}", symbol))
+        })?;
+
+        Ok(Quote {
+            symbol: symbol.to_string(),
+            last_price: price,
+            change: 0.0,
+            change_percent: 0.0,
+            volume: 1_000_000,
+            average_volume: 1_000_000,
+            bid_price: price - 0.01,
+            bid_size: 100,
+            ask_price: price + 0.01,
+            ask_size: 100,
+            high: price,
+            low: price,
+            open: price,
+            prev_close: price,
+            fifty_two_week_high: price,
+            fifty_two_week_low: price,
+            market_cap: None,
+            pe_ratio: None,
+            timestamp: Utc::now(),
+        })
+    }
+
+    pub fn quotes(&self, symbols: &[&str]) -> Vec<Quote> {
+        symbols
+            .iter()
+            .filter_map(|symbol| self.quote(symbol).ok())
+            .collect()
+    }
+
+    pub fn bars(&self, symbol: &str, count: usize) -> Result<Vec<Bar>, Rejection> {
+        let quote = self.quote(symbol)?;
+        let now = Utc::now();
+        Ok((0..count.min(1200))
+            .map(|days_ago| Bar {
+                symbol: symbol.to_string(),
+                open: quote.last_price,
+                high: quote.last_price,
+                low: quote.last_price,
+                close: quote.last_price,
+                volume: quote.volume,
+                timestamp: now - chrono::Duration::days(days_ago as i64),
+            })
+            .collect())
+    }
+
+    pub fn instruments(&self, symbols: &[&str]) -> Vec<Instrument> {
+        symbols
+            .iter()
+            .filter(|symbol| self.quotes.contains_key(**symbol))
+            .map(|symbol| Instrument {
+                id: instrument_id(symbol),
+                symbol: symbol.to_string(),
+                name: symbol.to_string(),
+                exchange: "NASDAQ".to_string(),
+                security_type: "STK".to_string(),
+                region: "US".to_string(),
+                currency: "USD".to_string(),
+                tradable: true,
+                shortable: false,
+                marginable: false,
+                fractional_tradable: true,
+            })
+            .collect()
+    }
+
+    /// Get the symbol of an instrument ID issued by the mock server.
+    pub fn symbol_of(&self, instrument_id: &str) -> Option<String> {
+        instrument_id
+            .strip_prefix("INST-")
+            .filter(|symbol| self.quotes.contains_key(*symbol))
+            .map(str::to_string)
+    }
+
+    // Watchlists
+
+    pub fn watchlists(&self) -> Vec<Watchlist> {
+        self.watchlists.values().cloned().collect()
+    }
+
+    pub fn watchlist(&self, id: &str) -> Result<Watchlist, Rejection> {
+        self.watchlists
+            .get(id)
+            .cloned()
+            .ok_or_else(|| watchlist_not_found(id))
+    }
+
+    pub fn create_watchlist(&mut self, name: String, symbols: Vec<String>) -> Watchlist {
+        let watchlist = Watchlist {
+            id: self.next_id("WATCHLIST"),
+            name,
+            symbols,
+        };
+        self.watchlists
+            .insert(watchlist.id.clone(), watchlist.clone());
+        watchlist
+    }
+
+    pub fn modify_watchlist(
+        &mut self,
+        id: &str,
+        name: Option<String>,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Watchlist, Rejection> {
+        let watchlist = self
+            .watchlists
+            .get_mut(id)
+            .ok_or_else(|| watchlist_not_found(id))?;
+        if let Some(name) = name {
+            watchlist.name = name;
+        }
+        for symbol in add {
+            if !watchlist.symbols.contains(&symbol) {
+                watchlist.symbols.push(symbol);
+            }
+        }
+        watchlist.symbols.retain(|symbol| !remove.contains(symbol));
+        Ok(watchlist.clone())
+    }
+
+    pub fn delete_watchlist(&mut self, id: &str) -> Result<(), Rejection> {
+        self.watchlists
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| watchlist_not_found(id))
+    }
+}
+
+fn is_buy(side: OrderSide) -> bool {
+    matches!(side, OrderSide::Buy | OrderSide::BuyToCover)
+}
+
+fn remaining(order: &Order) -> f64 {
+    (order.quantity - order.filled_quantity).max(0.0)
+}
+
+/// Price used to value an order: its limit price, else the last price.
+fn reference_price(order: &Order, quotes: &BTreeMap<String, f64>) -> f64 {
+    order
+        .price
+        .or_else(|| quotes.get(&order.symbol).copied())
+        .unwrap_or_default()
+}
+
+fn open_order_index(ledger: &Ledger, order_id: &str) -> Result<usize, Rejection> {
+    let index = ledger
+        .orders
+        .iter()
+        .position(|order| order.id == order_id)
+        .ok_or_else(|| order_not_found(order_id))?;
+    if !ledger.orders[index].status.is_open() {
+        return Err(Rejection::bad_request(
+            "ORDER_NOT_OPEN",
+            format!("Order {} is no longer open", order_id),
+        ));
+    }
+    Ok(index)
+}
+
+fn order_not_found(order_id: &str) -> Rejection {
+    Rejection::not_found("ORDER_NOT_FOUND", format!("Order {} not found", order_id))
+}
+
+fn watchlist_not_found(id: &str) -> Rejection {
+    Rejection::not_found("WATCHLIST_NOT_FOUND", format!("Watchlist {} not found", id))
+}
+
+fn instrument_id(symbol: &str) -> String {
+    format!("INST-{}", symbol)
+}
+
+fn percentage(value: f64, base: f64) -> f64 {
+    if base.abs() <= EPSILON {
+        0.0
+    } else {
+        value / base * 100.0
+    }
+}
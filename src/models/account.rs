@@ -1,3 +1,4 @@
+use crate::region::Region;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -323,7 +324,67 @@ impl BalanceParams {
     }
 
     /// Create new balance query parameters with default currency (HKD).
+    #[deprecated(note = "use `BalanceParams::for_region` to pick the region's currency")]
     pub fn new_with_default_currency(account_id: impl Into<String>) -> Self {
-        Self::new(account_id, "HKD")
+        Self::for_region(account_id, Region::Hk)
+    }
+
+    /// Create new balance query parameters using a region's default currency.
+    pub fn for_region(account_id: impl Into<String>, region: Region) -> Self {
+        Self::new(account_id, region.default_currency())
+    }
+}
+
+/// Request to reset a paper trading account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccountResetRequest {
+    /// Paper account ID
+    #[serde(rename = "account_id")]
+    pub account_id: String,
+
+    /// Starting cash balance after the reset
+    #[serde(rename = "initial_balance")]
+    pub initial_balance: f64,
+}
+
+impl PaperAccountResetRequest {
+    /// Create a new paper account reset request.
+    pub fn new(account_id: impl Into<String>, initial_balance: f64) -> Self {
+        Self {
+            account_id: account_id.into(),
+            initial_balance,
+        }
+    }
+}
+
+/// Request to add virtual funds to a paper trading account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperFundingRequest {
+    /// Paper account ID
+    #[serde(rename = "account_id")]
+    pub account_id: String,
+
+    /// Amount of virtual cash to add
+    pub amount: f64,
+
+    /// Currency of the amount
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+impl PaperFundingRequest {
+    /// Create a new paper funding request.
+    pub fn new(account_id: impl Into<String>, amount: f64) -> Self {
+        Self {
+            account_id: account_id.into(),
+            amount,
+            currency: None,
+        }
+    }
+
+    /// Set the currency of the amount.
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
     }
 }
@@ -91,6 +91,13 @@ pub enum OrderStatus {
     Expired,
 }
 
+impl OrderStatus {
+    /// Check if the order can still be filled, modified or canceled.
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::New | Self::PendingNew | Self::PartiallyFilled)
+    }
+}
+
 /// Side of an order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -392,6 +399,24 @@ pub struct OrderResponse {
     pub created_at: DateTime<Utc>,
 }
 
+impl From<Order> for OrderResponse {
+    fn from(order: Order) -> Self {
+        Self {
+            id: order.id,
+            status: order.status,
+            symbol: order.symbol,
+            quantity: order.quantity,
+            price: order.price,
+            stop_price: order.stop_price,
+            side: order.side,
+            order_type: order.order_type,
+            time_in_force: order.time_in_force,
+            extended_hours: order.extended_hours,
+            created_at: order.created_at,
+        }
+    }
+}
+
 /// Parameters for querying orders.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderQueryParams {
@@ -11,11 +11,11 @@ pub struct ApiResponse<T> {
     pub data: Option<T>,
 
     /// Error code
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "error_code", skip_serializing_if = "Option::is_none")]
     pub code: Option<String>,
 
     /// Error message
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "msg", skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 }
 
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Webull region the client is operating in.
+///
+/// Webull runs separate services per region, with different hosts, account
+/// currencies and endpoint coverage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Region {
+    /// Webull US
+    #[default]
+    Us,
+
+    /// Webull Hong Kong
+    Hk,
+
+    /// Webull Japan
+    Jp,
+}
+
+/// Region-specific functionality of the Webull API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Paper trading accounts
+    PaperTrading,
+
+    /// Option trading and option chains
+    Options,
+
+    /// End-of-day bars
+    EodBars,
+
+    /// Corporate actions such as splits
+    CorporateActions,
+}
+
+impl Capability {
+    /// Get a human-readable name for the capability.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PaperTrading => "paper trading",
+            Self::Options => "options",
+            Self::EodBars => "end-of-day bars",
+            Self::CorporateActions => "corporate actions",
+        }
+    }
+}
+
+impl Region {
+    /// All supported regions.
+    pub const ALL: [Region; 3] = [Region::Us, Region::Hk, Region::Jp];
+
+    /// Get the region code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Us => "US",
+            Self::Hk => "HK",
+            Self::Jp => "JP",
+        }
+    }
+
+    /// Get the default REST API base URL for the region.
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            Self::Us => "https://api.webull.com",
+            Self::Hk => "https://api.webull.hk",
+            Self::Jp => "https://api.webull.co.jp",
+        }
+    }
+
+    /// Get the default streaming (WebSocket) URL for the region.
+    pub fn streaming_url(&self) -> &'static str {
+        match self {
+            Self::Us => "wss://push.webull.com",
+            Self::Hk => "wss://push.webull.hk",
+            Self::Jp => "wss://push.webull.co.jp",
+        }
+    }
+
+    /// Get the default currency for account balances in the region.
+    pub fn default_currency(&self) -> &'static str {
+        match self {
+            Self::Us => "USD",
+            Self::Hk => "HKD",
+            Self::Jp => "JPY",
+        }
+    }
+
+    /// Check if the region supports a capability.
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::PaperTrading => matches!(self, Self::Us | Self::Hk),
+            Capability::Options => matches!(self, Self::Us | Self::Hk),
+            Capability::EodBars => matches!(self, Self::Jp),
+            Capability::CorporateActions => matches!(self, Self::Jp),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|region| region.code().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown region '{}' (expected US, HK or JP)", s))
+    }
+}
@@ -1,24 +1,73 @@
 use crate::auth::{AccessToken, AuthManager};
+use crate::config::StreamingConfig;
 use crate::error::{WebullError, WebullResult};
 use crate::streaming::events::{
     ConnectionState, ConnectionStatus, ErrorEvent, Event, EventType, HeartbeatEvent,
 };
-use crate::streaming::subscription::{SubscriptionRequest, UnsubscriptionRequest};
+use crate::streaming::subscription::{
+    SubscriptionRequest, SubscriptionType, UnsubscriptionRequest,
+};
+use crate::telemetry::{self, redact_url, Metrics, NoopMetrics};
 use crate::utils::serialization::{from_json, to_json};
 use futures_util::{SinkExt, StreamExt};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::header::AUTHORIZATION;
 use serde_json::json;
-use std::sync::{Arc, Mutex};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, Weak};
 use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::time::sleep;
 use tokio_tungstenite::{
-    connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream,
+    connect_async,
+    tungstenite::{client::IntoClientRequest, protocol::Message},
+    MaybeTlsStream, WebSocketStream,
 };
+use tracing::field::Empty;
+use tracing::Instrument;
 use url::Url;
 use uuid::Uuid;
 
+/// Connection state and subscriptions of a WebSocket client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamStatus {
+    /// Streaming base URL, with credentials redacted
+    pub url: String,
+
+    /// Connection state
+    pub state: ConnectionState,
+
+    /// Number of subscribed topics, counting each symbol of a quote
+    /// subscription separately
+    pub subscriptions: usize,
+}
+
+/// Watches a WebSocket client without keeping it alive.
+#[derive(Debug, Clone)]
+pub(crate) struct StreamMonitor {
+    /// Streaming base URL, with credentials redacted
+    url: String,
+
+    /// Connection state of the client
+    connection_state: Weak<Mutex<ConnectionState>>,
+
+    /// Subscribed topics of the client
+    subscriptions: Weak<Mutex<HashSet<String>>>,
+}
+
+impl StreamMonitor {
+    /// Get the status of the client, or `None` if it has been dropped.
+    pub(crate) fn status(&self) -> Option<StreamStatus> {
+        let state = *self.connection_state.upgrade()?.lock().unwrap();
+        let subscriptions = self.subscriptions.upgrade()?.lock().unwrap().len();
+        Some(StreamStatus {
+            url: self.url.clone(),
+            state,
+            subscriptions,
+        })
+    }
+}
+
 /// WebSocket client for streaming data from Webull.
 pub struct WebSocketClient {
     /// Base URL for WebSocket connections
@@ -30,6 +79,9 @@ pub struct WebSocketClient {
     /// Connection state
     connection_state: Arc<Mutex<ConnectionState>>,
 
+    /// Subscribed topics, e.g. `Quote:AAPL`
+    subscriptions: Arc<Mutex<HashSet<String>>>,
+
     /// Event sender
     event_sender: Option<Sender<Event>>,
 
@@ -47,6 +99,9 @@ pub struct WebSocketClient {
 
     /// Reconnect delay in seconds
     reconnect_delay: u64,
+
+    /// Metrics sink for connections and messages
+    metrics: Arc<dyn Metrics>,
 }
 
 impl WebSocketClient {
@@ -56,15 +111,56 @@ impl WebSocketClient {
             base_url,
             auth_manager,
             connection_state: Arc::new(Mutex::new(ConnectionState::Disconnected)),
+            subscriptions: Arc::new(Mutex::new(HashSet::new())),
             event_sender: None,
             last_heartbeat: Arc::new(Mutex::new(Instant::now())),
             heartbeat_interval: 30,
             reconnect_attempts: Arc::new(Mutex::new(0)),
             max_reconnect_attempts: 5,
             reconnect_delay: 5,
+            metrics: Arc::new(NoopMetrics),
         }
     }
 
+    /// Report connections and messages to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Apply streaming settings from the client configuration.
+    pub fn with_config(mut self, config: &StreamingConfig) -> Self {
+        self.heartbeat_interval = config.heartbeat_interval.as_secs().max(1);
+        self.max_reconnect_attempts = config.max_reconnect_attempts;
+        self.reconnect_delay = config.reconnect_delay.as_secs();
+        self
+    }
+
+    /// Get the connection state and the number of subscribed topics.
+    pub fn status(&self) -> StreamStatus {
+        StreamStatus {
+            url: self.redacted_url(),
+            state: *self.connection_state.lock().unwrap(),
+            subscriptions: self.subscriptions.lock().unwrap().len(),
+        }
+    }
+
+    /// Watch the client's status without keeping it alive.
+    pub(crate) fn monitor(&self) -> StreamMonitor {
+        StreamMonitor {
+            url: self.redacted_url(),
+            connection_state: Arc::downgrade(&self.connection_state),
+            subscriptions: Arc::downgrade(&self.subscriptions),
+        }
+    }
+
+    /// Get the base URL with credentials redacted.
+    fn redacted_url(&self) -> String {
+        Url::parse(&self.base_url)
+            .map(|url| redact_url(&url))
+            .unwrap_or_default()
+    }
+
     /// Connect to the WebSocket server.
     pub async fn connect(&mut self) -> WebullResult<Receiver<Event>> {
         // Create a channel for events
@@ -86,6 +182,8 @@ impl WebSocketClient {
         let reconnect_attempts = self.reconnect_attempts.clone();
         let max_reconnect_attempts = self.max_reconnect_attempts;
         let reconnect_delay = self.reconnect_delay;
+        let metrics = self.metrics.clone();
+        let stream_url = self.redacted_url();
 
         tokio::spawn(async move {
             loop {
@@ -114,10 +212,23 @@ impl WebSocketClient {
                 // Increment reconnect attempts
                 *reconnect_attempts.lock().unwrap() = attempts + 1;
 
+                let span = tracing::info_span!(
+                    "webull.stream",
+                    url = %stream_url,
+                    attempt = attempts + 1,
+                    outcome = Empty
+                );
+
                 // Get the authentication token
                 let token = match auth_manager.get_token().await {
                     Ok(token) => token,
                     Err(e) => {
+                        span.record("outcome", "auth_error");
+                        metrics.increment_counter(
+                            telemetry::STREAM_CONNECTIONS_TOTAL,
+                            &[("outcome", "auth_error")],
+                        );
+
                         // Send an error event
                         let event = Event {
                             event_type: EventType::Error,
@@ -137,8 +248,17 @@ impl WebSocketClient {
                 };
 
                 // Connect to the WebSocket server
-                match Self::connect_websocket(&base_url, &token).await {
+                match Self::connect_websocket(&base_url, &token)
+                    .instrument(span.clone())
+                    .await
+                {
                     Ok(ws_stream) => {
+                        span.record("outcome", "connected");
+                        metrics.increment_counter(
+                            telemetry::STREAM_CONNECTIONS_TOTAL,
+                            &[("outcome", "connected")],
+                        );
+
                         // Set the connection state to connected
                         *connection_state.lock().unwrap() = ConnectionState::Connected;
 
@@ -167,7 +287,9 @@ impl WebSocketClient {
                             tx.clone(),
                             last_heartbeat.clone(),
                             heartbeat_interval,
+                            metrics.clone(),
                         )
+                        .instrument(span.clone())
                         .await
                         {
                             // Send an error event
@@ -202,6 +324,12 @@ impl WebSocketClient {
                         let _ = tx.send(event).await;
                     }
                     Err(e) => {
+                        span.record("outcome", "failed");
+                        metrics.increment_counter(
+                            telemetry::STREAM_CONNECTIONS_TOTAL,
+                            &[("outcome", "failed")],
+                        );
+
                         // Send an error event
                         let event = Event {
                             event_type: EventType::Error,
@@ -248,6 +376,9 @@ impl WebSocketClient {
         // Reset reconnect attempts
         *self.reconnect_attempts.lock().unwrap() = self.max_reconnect_attempts + 1;
 
+        // Subscriptions do not survive the connection
+        self.subscriptions.lock().unwrap().clear();
+
         Ok(())
     }
 
@@ -284,6 +415,12 @@ impl WebSocketClient {
             })?;
         }
 
+        self.subscriptions.lock().unwrap().extend(topics(
+            &request.subscription_type,
+            request.symbols.as_deref(),
+            request.account_id.as_deref(),
+        ));
+
         Ok(())
     }
 
@@ -320,6 +457,15 @@ impl WebSocketClient {
             })?;
         }
 
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        for topic in topics(
+            &request.subscription_type,
+            request.symbols.as_deref(),
+            request.account_id.as_deref(),
+        ) {
+            subscriptions.remove(&topic);
+        }
+
         Ok(())
     }
 
@@ -328,20 +474,29 @@ impl WebSocketClient {
         base_url: &str,
         token: &AccessToken,
     ) -> WebullResult<WebSocketStream<MaybeTlsStream<TcpStream>>> {
-        // Create the WebSocket URL
-        let ws_url = format!("{}/ws", base_url.replace("http", "ws"));
-        let url = Url::parse(&ws_url)
+        // Create the WebSocket URL, switching only the scheme of an HTTP URL
+        let mut url = Url::parse(&format!("{}/ws", base_url.trim_end_matches('/')))
             .map_err(|e| WebullError::InvalidRequest(format!("Invalid WebSocket URL: {}", e)))?;
+        let scheme = match url.scheme() {
+            "http" => Some("ws"),
+            "https" => Some("wss"),
+            _ => None,
+        };
+        if let Some(scheme) = scheme {
+            // Both schemes are special, so switching between them cannot fail
+            let _ = url.set_scheme(scheme);
+        }
 
-        // Create the request headers
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", token.token)).unwrap(),
-        );
+        // Create the handshake request with the authorization header
+        let mut request = url.into_client_request().map_err(|e| {
+            WebullError::InvalidRequest(format!("Invalid WebSocket request: {}", e))
+        })?;
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, token.bearer_header()?);
 
         // Connect to the WebSocket server
-        let (ws_stream, _) = connect_async(url).await.map_err(|e| {
+        let (ws_stream, _) = connect_async(request).await.map_err(|e| {
             WebullError::InvalidRequest(format!("WebSocket connection error: {}", e))
         })?;
 
@@ -354,6 +509,7 @@ impl WebSocketClient {
         tx: Sender<Event>,
         last_heartbeat: Arc<Mutex<Instant>>,
         heartbeat_interval: u64,
+        metrics: Arc<dyn Metrics>,
     ) -> WebullResult<()> {
         // Start the heartbeat task
         let tx_clone = tx.clone();
@@ -406,6 +562,11 @@ impl WebSocketClient {
                     // Parse the message
                     match from_json::<Event>(&text) {
                         Ok(event) => {
+                            metrics.increment_counter(
+                                telemetry::STREAM_MESSAGES_TOTAL,
+                                &[("kind", "event")],
+                            );
+
                             // Send the event
                             if tx.send(event).await.is_err() {
                                 // Channel closed, exit the loop
@@ -413,6 +574,11 @@ impl WebSocketClient {
                             }
                         }
                         Err(e) => {
+                            metrics.increment_counter(
+                                telemetry::STREAM_MESSAGES_TOTAL,
+                                &[("kind", "parse_error")],
+                            );
+
                             // Send an error event
                             let event = Event {
                                 event_type: EventType::Error,
@@ -491,3 +657,19 @@ impl WebSocketClient {
         Ok(())
     }
 }
+
+/// Get the topics of a subscription, one per quote symbol or account.
+fn topics(
+    subscription_type: &SubscriptionType,
+    symbols: Option<&[String]>,
+    account_id: Option<&str>,
+) -> Vec<String> {
+    let keys: Vec<&str> = match (symbols, account_id) {
+        (Some(symbols), _) => symbols.iter().map(String::as_str).collect(),
+        (None, Some(account_id)) => vec![account_id],
+        (None, None) => vec![""],
+    };
+    keys.into_iter()
+        .map(|key| format!("{:?}:{}", subscription_type, key))
+        .collect()
+}
@@ -0,0 +1,271 @@
+//! HTTP transport.
+//!
+//! Every HTTP request the client makes, from the endpoint groups and from
+//! authentication calls, is sent through an [`HttpTransport`]. The default is
+//! [`ReqwestTransport`]; pass a preconfigured `reqwest::Client` with
+//! [`WebullClientBuilder::with_http_client`] for proxies, custom TLS roots or
+//! connection pooling, or implement the trait to send requests some other
+//! way, e.g. through a tower `Service` stack or an in-memory fake:
+//!
+//! ```
+//! use async_trait::async_trait;
+//! use reqwest::StatusCode;
+//! use webull_rs::middleware::HttpResponse;
+//! use webull_rs::transport::{HttpRequest, HttpTransport};
+//! use webull_rs::WebullResult;
+//!
+//! struct Offline;
+//!
+//! #[async_trait]
+//! impl HttpTransport for Offline {
+//!     async fn send(&self, request: HttpRequest) -> WebullResult<HttpResponse> {
+//!         Ok(HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, request.url.path()))
+//!     }
+//! }
+//!
+//! let client = webull_rs::WebullClient::builder()
+//!     .with_transport(Offline)
+//!     .build();
+//! ```
+//!
+//! [`WebullClientBuilder::with_http_client`]: crate::WebullClientBuilder::with_http_client
+
+use crate::error::{WebullError, WebullResult};
+use crate::middleware::HttpResponse;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
+use std::time::Duration;
+use url::Url;
+
+/// An HTTP request ready to be sent.
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// HTTP method
+    pub method: Method,
+
+    /// Full request URL, including the query string
+    pub url: Url,
+
+    /// Request headers
+    pub headers: HeaderMap,
+
+    /// Request body
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    /// Create a request without headers or body.
+    pub fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// Add a header.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Set the request body.
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+}
+
+/// Sends HTTP requests.
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    /// Send a request and read the whole response.
+    ///
+    /// Unsuccessful HTTP statuses are returned as responses; errors are only
+    /// for requests that got no response at all.
+    async fn send(&self, request: HttpRequest) -> WebullResult<HttpResponse>;
+}
+
+/// Transport backed by a `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// Create a transport that sends requests with a client.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    /// Create a transport with a new client that times out requests.
+    pub fn with_timeout(timeout: Duration) -> WebullResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(WebullError::NetworkError)?;
+        Ok(Self::new(client))
+    }
+
+    /// Get the underlying client.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
+
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(client: reqwest::Client) -> Self {
+        Self::new(client)
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> WebullResult<HttpResponse> {
+        let mut builder = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await.map_err(WebullError::NetworkError)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = if status.is_success() {
+            response.text().await.map_err(WebullError::NetworkError)?
+        } else {
+            // The status is what matters for a failed request
+            response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string())
+        };
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Transport fakes for unit tests.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use reqwest::StatusCode;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers a request, or leaves it to the default response.
+    type Handler = dyn Fn(&HttpRequest) -> Option<HttpResponse> + Send + Sync;
+
+    /// In-memory transport that records every request it is sent.
+    ///
+    /// Logins succeed and every other request gets an empty list, unless the
+    /// handler answers it first.
+    #[derive(Clone)]
+    pub(crate) struct FakeTransport {
+        /// Requests sent so far
+        sent: Arc<Mutex<Vec<HttpRequest>>>,
+
+        /// Custom responses
+        handler: Arc<Handler>,
+
+        /// Time taken to answer each request
+        latency: Duration,
+    }
+
+    impl FakeTransport {
+        /// Create a transport that gives every request the default response.
+        pub(crate) fn new() -> Self {
+            Self::with_handler(|_| None)
+        }
+
+        /// Create a transport that answers requests with a handler first.
+        pub(crate) fn with_handler(
+            handler: impl Fn(&HttpRequest) -> Option<HttpResponse> + Send + Sync + 'static,
+        ) -> Self {
+            Self {
+                sent: Arc::new(Mutex::new(Vec::new())),
+                handler: Arc::new(handler),
+                latency: Duration::ZERO,
+            }
+        }
+
+        /// Take a while to answer each request, so that concurrent requests
+        /// overlap.
+        pub(crate) fn with_latency(mut self, latency: Duration) -> Self {
+            self.latency = latency;
+            self
+        }
+
+        /// Get the requests sent so far.
+        pub(crate) fn sent(&self) -> Vec<HttpRequest> {
+            self.sent.lock().unwrap().clone()
+        }
+
+        /// Get the method and path of every request sent so far.
+        pub(crate) fn requests(&self) -> Vec<String> {
+            self.sent()
+                .iter()
+                .map(|request| format!("{} {}", request.method, request.url.path()))
+                .collect()
+        }
+    }
+
+    /// Get the default response to a request: a token for logins and an
+    /// empty list for everything else.
+    pub(crate) fn respond(request: &HttpRequest) -> HttpResponse {
+        let body = if request.url.path().starts_with("/api/passport") {
+            r#"{"access_token":"a","refresh_token":"r","expires_in":3600}"#
+        } else {
+            r#"{"success":true,"data":[]}"#
+        };
+        HttpResponse::new(StatusCode::OK, body)
+    }
+
+    #[async_trait]
+    impl HttpTransport for FakeTransport {
+        async fn send(&self, request: HttpRequest) -> WebullResult<HttpResponse> {
+            self.sent.lock().unwrap().push(request.clone());
+            if !self.latency.is_zero() {
+                tokio::time::sleep(self.latency).await;
+            }
+            Ok((self.handler)(&request).unwrap_or_else(|| respond(&request)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::FakeTransport;
+    use crate::WebullClient;
+
+    #[tokio::test]
+    async fn custom_transports_carry_all_requests() {
+        let fake = FakeTransport::new();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .build()
+            .unwrap();
+        client.login("user", "password").await.unwrap();
+        let accounts = client.accounts().get_accounts().await.unwrap();
+        assert!(accounts.is_empty());
+
+        let paper = client.paper_trading().unwrap();
+        paper.login("user", "password").await.unwrap();
+
+        assert_eq!(
+            fake.requests(),
+            [
+                "POST /api/passport/login/v5/account",
+                "GET /api/account/getSecAccountList",
+                "POST /api/passport/login/v5/account",
+            ]
+        );
+    }
+}
@@ -1,7 +1,14 @@
-use std::collections::HashMap;
+use crate::config::CacheConfig;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
 use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Cache entry.
 #[derive(Debug, Clone)]
@@ -14,27 +21,131 @@ struct CacheEntry<T> {
 
     /// Time-to-live for the entry
     ttl: Duration,
+
+    /// How long the entry may be served stale after its time-to-live
+    stale_for: Duration,
+
+    /// Whether a background refresh of the entry is in flight
+    revalidating: bool,
+
+    /// Tick of the cache's clock when the entry was last stored or looked up
+    last_used: u64,
 }
 
 impl<T> CacheEntry<T> {
     /// Create a new cache entry.
-    fn new(value: T, ttl: Duration) -> Self {
+    fn new(value: T, ttl: Duration, stale_for: Duration) -> Self {
         Self {
             value,
             created_at: Instant::now(),
             ttl,
+            stale_for,
+            revalidating: false,
+            last_used: 0,
         }
     }
 
-    /// Check if the entry is expired.
-    fn is_expired(&self) -> bool {
+    /// Check if the entry is past its time-to-live.
+    fn is_stale(&self) -> bool {
         self.created_at.elapsed() > self.ttl
     }
+
+    /// Check if the entry can no longer be served, even stale.
+    fn is_expired(&self) -> bool {
+        self.created_at.elapsed() > self.ttl + self.stale_for
+    }
 }
 
-/// Cache key.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct CacheKey {
+/// How responses from an endpoint are cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Never cache the response
+    Never,
+
+    /// Cache the response with the cache manager's default time-to-live
+    Default,
+
+    /// Cache the response for a fixed time-to-live
+    Ttl(Duration),
+
+    /// Cache the response for `ttl`, then keep serving it for up to `stale`
+    /// longer while it is refreshed in the background
+    StaleWhileRevalidate {
+        /// Time the response is fresh
+        ttl: Duration,
+
+        /// Time a stale response may still be served
+        stale: Duration,
+    },
+
+    /// Cache the response like [`CachePolicy::StaleWhileRevalidate`], and
+    /// also keep it in the [`DiskCache`], if there is one, so that it
+    /// survives restarts
+    Persistent {
+        /// Time the response is fresh
+        ttl: Duration,
+
+        /// Time a stale response may still be served
+        stale: Duration,
+    },
+}
+
+impl CachePolicy {
+    /// Real-time data such as quotes and snapshots.
+    pub const REALTIME: CachePolicy = CachePolicy::Ttl(Duration::from_secs(1));
+
+    /// Account data such as balances and positions.
+    pub const ACCOUNT: CachePolicy = CachePolicy::Ttl(Duration::from_secs(5));
+
+    /// Historical data such as the latest bars.
+    pub const HISTORICAL: CachePolicy = CachePolicy::Ttl(Duration::from_secs(300));
+
+    /// Data for a range that ended in the past, such as end-of-day bars up
+    /// to a past date, which never changes.
+    pub const ARCHIVE: CachePolicy = CachePolicy::Persistent {
+        ttl: Duration::from_secs(30 * 24 * 3600),
+        stale: Duration::ZERO,
+    };
+
+    /// Reference data such as instruments and calendars.
+    pub const REFERENCE: CachePolicy = CachePolicy::StaleWhileRevalidate {
+        ttl: Duration::from_secs(3600),
+        stale: Duration::from_secs(86400),
+    };
+
+    /// Instrument metadata, which rarely changes.
+    pub const INSTRUMENT: CachePolicy = CachePolicy::Persistent {
+        ttl: Duration::from_secs(3600),
+        stale: Duration::from_secs(86400),
+    };
+
+    /// Check if responses are cached under this policy.
+    pub fn is_cacheable(&self) -> bool {
+        !matches!(self, Self::Never)
+    }
+
+    /// Check if responses are also kept in the disk tier under this policy.
+    pub fn is_persistent(&self) -> bool {
+        matches!(self, Self::Persistent { .. })
+    }
+}
+
+/// Result of looking up a cached response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheLookup<T> {
+    /// The response is fresh
+    Fresh(T),
+
+    /// The response is stale and should be refreshed
+    Stale(T),
+
+    /// No usable response is cached
+    Miss,
+}
+
+/// Cache key identifying a request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
     /// Method (GET, POST, etc.)
     method: String,
 
@@ -50,7 +161,7 @@ struct CacheKey {
 
 impl CacheKey {
     /// Create a new cache key.
-    fn new(method: &str, url: &str, query: Option<&str>, body: Option<&str>) -> Self {
+    pub fn new(method: &str, url: &str, query: Option<&str>, body: Option<&str>) -> Self {
         Self {
             method: method.to_string(),
             url: url.to_string(),
@@ -58,31 +169,156 @@ impl CacheKey {
             body: body.map(|s| s.to_string()),
         }
     }
+
+    /// Get the method.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Get the URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the query parameters.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// Get the request body.
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Hash the key with FNV-1a, which unlike the standard library's hasher
+    /// is stable across Rust releases and so can name files on disk.
+    fn stable_hash(&self) -> u64 {
+        let parts = [
+            Some(self.method.as_str()),
+            Some(self.url.as_str()),
+            self.query.as_deref(),
+            self.body.as_deref(),
+        ];
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for part in parts {
+            // Tell a missing part apart from an empty one
+            let bytes = part.map(str::as_bytes);
+            for byte in bytes.unwrap_or(&[0xff]).iter().chain(&[0]) {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        hash
+    }
+}
+
+/// Cached entries, ordered by last use.
+struct Entries<T> {
+    /// Entries by key
+    map: HashMap<CacheKey, CacheEntry<T>>,
+
+    /// Keys by the tick of their last use, least recently used first
+    recency: BTreeMap<u64, CacheKey>,
+
+    /// Clock that orders entries by last use
+    ticks: u64,
+}
+
+impl<T> Entries<T> {
+    /// Create an empty set of entries.
+    fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            recency: BTreeMap::new(),
+            ticks: 0,
+        }
+    }
+
+    /// Get an entry, marking it as the most recently used.
+    fn touch(&mut self, key: &CacheKey) -> Option<&mut CacheEntry<T>> {
+        let entry = self.map.get_mut(key)?;
+        self.recency.remove(&entry.last_used);
+        self.ticks += 1;
+        entry.last_used = self.ticks;
+        self.recency.insert(self.ticks, key.clone());
+        Some(entry)
+    }
+
+    /// Insert an entry as the most recently used.
+    fn insert(&mut self, key: CacheKey, mut entry: CacheEntry<T>) {
+        self.ticks += 1;
+        entry.last_used = self.ticks;
+        self.recency.insert(self.ticks, key.clone());
+        if let Some(old) = self.map.insert(key, entry) {
+            self.recency.remove(&old.last_used);
+        }
+    }
+
+    /// Remove an entry.
+    fn remove(&mut self, key: &CacheKey) -> Option<CacheEntry<T>> {
+        let entry = self.map.remove(key)?;
+        self.recency.remove(&entry.last_used);
+        Some(entry)
+    }
+
+    /// Remove the least recently used entry.
+    fn pop_least_recent(&mut self) -> Option<CacheEntry<T>> {
+        let (_, key) = self.recency.pop_first()?;
+        self.map.remove(&key)
+    }
+
+    /// Keep only the entries a predicate accepts.
+    fn retain(&mut self, mut keep: impl FnMut(&CacheKey, &CacheEntry<T>) -> bool) {
+        let recency = &mut self.recency;
+        self.map.retain(|key, entry| {
+            let kept = keep(key, entry);
+            if !kept {
+                recency.remove(&entry.last_used);
+            }
+            kept
+        });
+    }
 }
 
 /// Response cache.
+///
+/// When the cache is full, the least recently used entries are evicted,
+/// where both storing and looking up an entry count as using it. Entries
+/// are kept in order of use, so evicting one takes logarithmic time.
 pub struct ResponseCache<T: Clone + Send + Sync> {
     /// Cached responses
-    cache: Mutex<HashMap<CacheKey, CacheEntry<T>>>,
+    cache: Mutex<Entries<T>>,
 
     /// Default time-to-live for cache entries
     default_ttl: Duration,
 
     /// Maximum number of entries in the cache
     max_entries: usize,
+
+    /// Number of lookups that found a fresh or stale entry
+    hits: AtomicU64,
+
+    /// Number of lookups that found no usable entry
+    misses: AtomicU64,
+
+    /// Number of entries removed to make room for others
+    evictions: AtomicU64,
 }
 
 impl<T: Clone + Send + Sync> ResponseCache<T> {
     /// Create a new response cache.
     pub fn new(default_ttl: Duration, max_entries: usize) -> Self {
         Self {
-            cache: Mutex::new(HashMap::new()),
+            cache: Mutex::new(Entries::new()),
             default_ttl,
             max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
 
-    /// Get a cached response.
+    /// Get a fresh cached response.
     pub fn get(
         &self,
         method: &str,
@@ -90,20 +326,81 @@ impl<T: Clone + Send + Sync> ResponseCache<T> {
         query: Option<&str>,
         body: Option<&str>,
     ) -> Option<T> {
+        match self.lookup(method, url, query, body) {
+            CacheLookup::Fresh(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Look up a cached response, including stale ones.
+    pub fn lookup(
+        &self,
+        method: &str,
+        url: &str,
+        query: Option<&str>,
+        body: Option<&str>,
+    ) -> CacheLookup<T> {
         let key = CacheKey::new(method, url, query, body);
         let mut cache = self.cache.lock().unwrap();
 
-        if let Some(entry) = cache.get(&key) {
-            if entry.is_expired() {
+        let result = match cache.touch(&key) {
+            Some(entry) if entry.is_expired() => {
                 // Remove expired entry
                 cache.remove(&key);
-                None
-            } else {
-                // Return cached value
-                Some(entry.value.clone())
+                CacheLookup::Miss
+            }
+            Some(entry) => {
+                if entry.is_stale() {
+                    CacheLookup::Stale(entry.value.clone())
+                } else {
+                    CacheLookup::Fresh(entry.value.clone())
+                }
             }
-        } else {
-            None
+            None => CacheLookup::Miss,
+        };
+
+        let counter = match result {
+            CacheLookup::Miss => &self.misses,
+            _ => &self.hits,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Mark a stale entry as being refreshed.
+    ///
+    /// Returns `true` if the caller should refresh the entry, or `false` if a
+    /// refresh is already in flight.
+    pub fn begin_revalidation(
+        &self,
+        method: &str,
+        url: &str,
+        query: Option<&str>,
+        body: Option<&str>,
+    ) -> bool {
+        let key = CacheKey::new(method, url, query, body);
+        let mut cache = self.cache.lock().unwrap();
+
+        match cache.map.get_mut(&key) {
+            Some(entry) if !entry.revalidating => {
+                entry.revalidating = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Clear the refresh marker after a failed refresh.
+    pub fn end_revalidation(
+        &self,
+        method: &str,
+        url: &str,
+        query: Option<&str>,
+        body: Option<&str>,
+    ) {
+        let key = CacheKey::new(method, url, query, body);
+        if let Some(entry) = self.cache.lock().unwrap().map.get_mut(&key) {
+            entry.revalidating = false;
         }
     }
 
@@ -119,140 +416,885 @@ impl<T: Clone + Send + Sync> ResponseCache<T> {
     ) {
         let key = CacheKey::new(method, url, query, body);
         let ttl = ttl.unwrap_or(self.default_ttl);
-        let entry = CacheEntry::new(value, ttl);
-
-        let mut cache = self.cache.lock().unwrap();
-
-        // Check if we need to evict entries
-        if cache.len() >= self.max_entries {
-            // Remove expired entries first
-            let expired_keys: Vec<_> = cache
-                .iter()
-                .filter(|(_, entry)| entry.is_expired())
-                .map(|(key, _)| key.clone())
-                .collect();
+        self.insert(key, CacheEntry::new(value, ttl, Duration::ZERO));
+    }
 
-            for key in expired_keys {
-                cache.remove(&key);
-            }
+    /// Store a response in the cache according to a cache policy.
+    pub fn set_with_policy(
+        &self,
+        method: &str,
+        url: &str,
+        query: Option<&str>,
+        body: Option<&str>,
+        value: T,
+        policy: CachePolicy,
+    ) {
+        let key = CacheKey::new(method, url, query, body);
+        self.set_aged(key, value, policy, Duration::ZERO);
+    }
 
-            // If we still need to evict entries, remove the oldest ones
-            if cache.len() >= self.max_entries {
-                // Get all entries
-                let entries: Vec<_> = cache.iter().collect();
+    /// Store a response fetched `age` ago, such as one restored from the
+    /// disk tier, according to a cache policy.
+    ///
+    /// The entry goes stale and expires when it would have had it been
+    /// stored when it was fetched.
+    pub fn set_aged(&self, key: CacheKey, value: T, policy: CachePolicy, age: Duration) {
+        let (ttl, stale_for) = match policy {
+            CachePolicy::Never => return,
+            CachePolicy::Default => (self.default_ttl, Duration::ZERO),
+            CachePolicy::Ttl(ttl) => (ttl, Duration::ZERO),
+            CachePolicy::StaleWhileRevalidate { ttl, stale }
+            | CachePolicy::Persistent { ttl, stale } => (ttl, stale),
+        };
+        let (ttl, stale_for) = match ttl.checked_sub(age) {
+            Some(ttl) => (ttl, stale_for),
+            None => (Duration::ZERO, stale_for.saturating_sub(age - ttl)),
+        };
 
-                // Sort by creation time
-                let mut sorted_entries: Vec<_> = entries.iter().collect();
-                sorted_entries.sort_by_key(|(_, entry)| entry.created_at);
+        self.insert(key, CacheEntry::new(value, ttl, stale_for));
+    }
 
-                // Calculate how many to remove
-                let to_remove = entries.len() - self.max_entries + 1;
+    /// Insert an entry, evicting the least recently used entries if the
+    /// cache is full.
+    fn insert(&self, key: CacheKey, entry: CacheEntry<T>) {
+        let mut cache = self.cache.lock().unwrap();
 
-                // Remove the oldest entries
-                let keys_to_remove: Vec<_> = sorted_entries
-                    .iter()
-                    .take(to_remove)
-                    .map(|(k, _)| (*k).clone())
-                    .collect();
-                for key in keys_to_remove {
-                    cache.remove(&key);
+        // Replacing an entry does not need room
+        if !cache.map.contains_key(&key) {
+            while cache.map.len() >= self.max_entries {
+                let Some(evicted) = cache.pop_least_recent() else {
+                    break;
+                };
+                // Dropping an expired entry makes room without losing anything
+                if !evicted.is_expired() {
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
                 }
             }
         }
 
-        // Add the new entry
         cache.insert(key, entry);
     }
 
+    /// Remove one cached response.
+    ///
+    /// Returns `true` if the response was cached.
+    pub fn remove(&self, method: &str, url: &str, query: Option<&str>, body: Option<&str>) -> bool {
+        let key = CacheKey::new(method, url, query, body);
+        self.cache.lock().unwrap().remove(&key).is_some()
+    }
+
     /// Clear the cache.
+    ///
+    /// The hit, miss and eviction counters are kept.
     pub fn clear(&self) {
+        *self.cache.lock().unwrap() = Entries::new();
+    }
+
+    /// Remove every entry whose URL starts with a path prefix.
+    pub fn invalidate_prefix(&self, prefix: &str) {
         let mut cache = self.cache.lock().unwrap();
-        cache.clear();
+        cache.retain(|key, _| !key.url.starts_with(prefix));
+    }
+
+    /// Get the keys of the cached entries, most recently used first.
+    pub fn keys(&self) -> Vec<CacheKey> {
+        let cache = self.cache.lock().unwrap();
+        cache.recency.values().rev().cloned().collect()
+    }
+
+    /// Get the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().map.len()
+    }
+
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of lookups that found a fresh or stale entry.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of lookups that found no usable entry.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of entries removed to make room for others.
+    pub fn evictions(&self) -> u64 {
+        self.evictions.load(Ordering::Relaxed)
     }
 
     /// Remove expired entries from the cache.
     pub fn cleanup(&self) {
         let mut cache = self.cache.lock().unwrap();
-        let expired_keys: Vec<_> = cache
-            .iter()
-            .filter(|(_, entry)| entry.is_expired())
-            .map(|(key, _)| key.clone())
+        cache.retain(|_, entry| !entry.is_expired());
+    }
+}
+
+/// Type-erased operations on a response cache.
+trait ErasedCache: Send + Sync {
+    /// Remove every entry whose URL starts with a path prefix.
+    fn invalidate_prefix(&self, prefix: &str);
+
+    /// Remove every entry.
+    fn clear(&self);
+
+    /// Get the keys of the cached entries, most recently used first.
+    fn keys(&self) -> Vec<CacheKey>;
+
+    /// Get the statistics of the cache.
+    fn stats(&self, name: &str) -> CacheStats;
+}
+
+impl<T: Clone + Send + Sync> ErasedCache for ResponseCache<T> {
+    fn invalidate_prefix(&self, prefix: &str) {
+        ResponseCache::invalidate_prefix(self, prefix);
+    }
+
+    fn clear(&self) {
+        ResponseCache::clear(self);
+    }
+
+    fn keys(&self) -> Vec<CacheKey> {
+        ResponseCache::keys(self)
+    }
+
+    fn stats(&self, name: &str) -> CacheStats {
+        CacheStats {
+            name: name.to_string(),
+            entries: self.len(),
+            hits: self.hits(),
+            misses: self.misses(),
+            evictions: self.evictions(),
+        }
+    }
+}
+
+/// Statistics of one response cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Name the cache was created with, e.g. the HTTP method, or `disk` for
+    /// the disk tier
+    pub name: String,
+
+    /// Number of cached entries, including stale ones
+    pub entries: usize,
+
+    /// Number of lookups that found a fresh or stale entry
+    pub hits: u64,
+
+    /// Number of lookups that found no usable entry
+    pub misses: u64,
+
+    /// Number of entries removed to make room for others, or on the disk
+    /// tier because they outlived its time-to-live
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Get the share of lookups that found an entry, or `None` before the
+    /// first lookup.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// Disk tier for responses cached with [`CachePolicy::Persistent`].
+///
+/// Each response is stored as a JSON file under a directory named after its
+/// URL path, so that restarts do not download years of past bars again.
+/// A stored response is only served while it is within its policy's fresh
+/// and stale window; files older than the tier's time-to-live are removed.
+/// Disk errors are logged and treated as misses; they never fail a request.
+#[derive(Debug)]
+pub struct DiskCache {
+    /// Directory the entries are stored in
+    dir: PathBuf,
+
+    /// How long entries are kept
+    ttl: Duration,
+
+    /// Number of lookups that found an entry
+    hits: AtomicU64,
+
+    /// Number of lookups that found no usable entry
+    misses: AtomicU64,
+
+    /// Number of entries removed because they outlived the time-to-live
+    evictions: AtomicU64,
+}
+
+/// An entry of the disk tier, as stored in its file.
+#[derive(Serialize, Deserialize)]
+struct DiskEntry<V> {
+    /// Key of the entry, to detect hash collisions
+    key: CacheKey,
+
+    /// When the entry was stored, in seconds since the Unix epoch
+    stored_at: u64,
+
+    /// Cached value
+    value: V,
+}
+
+impl DiskCache {
+    /// Create a disk tier storing entries under a directory for `ttl`.
+    ///
+    /// The directory is created when the first entry is stored.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the directory the entries are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get a stored value no older than `max_age`, together with its age.
+    ///
+    /// Entries older than the tier's time-to-live are removed; entries
+    /// older than `max_age` are only skipped.
+    pub fn get<V: DeserializeOwned>(
+        &self,
+        key: &CacheKey,
+        max_age: Duration,
+    ) -> Option<(V, Duration)> {
+        let path = self.path(key);
+        let entry = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<DiskEntry<V>>(&bytes).ok())
+            .filter(|entry| entry.key == *key);
+
+        let value = match entry {
+            Some(entry) if self.is_expired(entry.stored_at) => {
+                let _ = fs::remove_file(&path);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Some(entry) => {
+                let age = Duration::from_secs(unix_now().saturating_sub(entry.stored_at));
+                (age <= max_age).then_some((entry.value, age))
+            }
+            None => None,
+        };
+
+        let counter = match value {
+            Some(_) => &self.hits,
+            None => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Store a value.
+    pub fn set<V: Serialize>(&self, key: &CacheKey, value: &V) {
+        let entry = DiskEntry {
+            key: key.clone(),
+            stored_at: unix_now(),
+            value,
+        };
+        let path = self.path(key);
+        let result = serde_json::to_vec(&entry)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| write_atomically(&path, &bytes).map_err(|e| e.to_string()));
+        if let Err(error) = result {
+            log::warn!(
+                "Failed to store cached response in {}: {}",
+                path.display(),
+                error
+            );
+        }
+    }
+
+    /// Remove a stored value.
+    ///
+    /// Returns `true` if the value was stored.
+    pub fn remove(&self, key: &CacheKey) -> bool {
+        fs::remove_file(self.path(key)).is_ok()
+    }
+
+    /// Remove every entry whose URL starts with a path prefix.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        // Entries live in one directory per path segment, so a prefix
+        // selects the directories under its parent that start with its last,
+        // possibly partial, segment
+        let (parent, partial) = prefix.rsplit_once('/').unwrap_or(("", prefix));
+        let parent = self.dir.join(path_dir(parent));
+        let partial = sanitize_segment(partial);
+        let Ok(entries) = fs::read_dir(&parent) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let matches = name.to_string_lossy().starts_with(&partial);
+            let result = match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() && matches => fs::remove_dir_all(entry.path()),
+                // Entries for the parent URL itself match any prefix ending in `/`
+                Ok(file_type) if file_type.is_file() && partial.is_empty() => {
+                    fs::remove_file(entry.path())
+                }
+                _ => Ok(()),
+            };
+            if let Err(error) = result {
+                log::warn!(
+                    "Failed to invalidate cached responses in {}: {}",
+                    entry.path().display(),
+                    error
+                );
+            }
+        }
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) {
+        self.invalidate_prefix("");
+    }
+
+    /// Get the keys of the stored entries, sorted by URL.
+    pub fn keys(&self) -> Vec<CacheKey> {
+        let mut keys: Vec<_> = entry_files(&self.dir)
+            .into_iter()
+            .filter_map(|path| fs::read(path).ok())
+            .filter_map(|bytes| serde_json::from_slice::<DiskEntry<IgnoredAny>>(&bytes).ok())
+            .filter(|entry| !self.is_expired(entry.stored_at))
+            .map(|entry| entry.key)
             .collect();
+        keys.sort_by(|a, b| (&a.url, &a.query).cmp(&(&b.url, &b.query)));
+        keys
+    }
+
+    /// Get the statistics of the disk tier.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: "disk".to_string(),
+            entries: entry_files(&self.dir).len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Get the file of an entry.
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir
+            .join(path_dir(&key.url))
+            .join(format!("{:016x}.json", key.stable_hash()))
+    }
+
+    /// Check if an entry stored at a time has outlived the time-to-live.
+    fn is_expired(&self, stored_at: u64) -> bool {
+        unix_now().saturating_sub(stored_at) > self.ttl.as_secs()
+    }
+}
+
+/// Get the directory of the entries for a URL path, relative to the tier's
+/// directory.
+fn path_dir(url: &str) -> PathBuf {
+    url.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(sanitize_segment)
+        .collect()
+}
+
+/// Make a URL path segment safe to use as a directory name.
+fn sanitize_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
 
-        for key in expired_keys {
-            cache.remove(&key);
+/// Get every entry file under a directory.
+fn entry_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                files.push(path);
+            }
         }
     }
+    files
+}
+
+/// Write a file through a temporary file, so readers never see a partial one.
+///
+/// The temporary file is named after the process and a counter, so that
+/// concurrent writers of the same entry, even from other processes sharing
+/// the directory, never write to the same temporary file.
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::write(&temporary, bytes).and_then(|_| fs::rename(&temporary, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+/// Get the current time in seconds since the Unix epoch.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// A response cache, viewed both as its concrete type and type-erased.
+struct CacheSlot {
+    /// The cache, for downcasting to its concrete type
+    typed: Arc<dyn Any + Send + Sync>,
+
+    /// The cache, for type-independent operations
+    erased: Arc<dyn ErasedCache>,
 }
 
 /// Cache manager for API responses.
 pub struct CacheManager {
-    /// Response caches for different types
-    caches: Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+    /// Response caches by name and response type
+    caches: Mutex<HashMap<(String, TypeId), CacheSlot>>,
+
+    /// Whether responses are cached
+    enabled: bool,
+
+    /// Default time-to-live for new caches
+    default_ttl: Duration,
+
+    /// Maximum number of entries for new caches
+    max_entries: usize,
+
+    /// Disk tier for persistent responses, if configured
+    disk: Option<Arc<DiskCache>>,
 }
 
 impl CacheManager {
     /// Create a new cache manager.
     pub fn new() -> Self {
+        Self::from_config(&CacheConfig::default())
+    }
+
+    /// Create a new cache manager from cache settings.
+    pub fn from_config(config: &CacheConfig) -> Self {
+        Self {
+            caches: Mutex::new(HashMap::new()),
+            enabled: config.enabled,
+            default_ttl: config.default_ttl,
+            max_entries: config.max_entries,
+            disk: config
+                .disk_dir
+                .as_ref()
+                .filter(|_| config.enabled)
+                .map(|dir| Arc::new(DiskCache::new(dir, config.disk_ttl))),
+        }
+    }
+
+    /// Create an empty cache manager with the same settings that shares this
+    /// manager's disk tier.
+    ///
+    /// Only persistent responses, such as instruments and historical bars, are
+    /// stored on disk, and those do not depend on the login.
+    pub fn sibling(&self) -> Self {
         Self {
             caches: Mutex::new(HashMap::new()),
+            enabled: self.enabled,
+            default_ttl: self.default_ttl,
+            max_entries: self.max_entries,
+            disk: self.disk.clone(),
         }
     }
 
+    /// Check if responses should be cached.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Get the default time-to-live for cached responses.
+    pub fn default_ttl(&self) -> Duration {
+        self.default_ttl
+    }
+
     /// Get a cache for a specific type.
     pub fn get_cache<T: Clone + Send + Sync + 'static>(&self, name: &str) -> Arc<ResponseCache<T>> {
         let mut caches = self.caches.lock().unwrap();
+        let key = (name.to_string(), TypeId::of::<T>());
 
         // Check if the cache exists
-        if let Some(cache) = caches.get(name) {
-            // Try to downcast to the correct type
-            if let Some(typed_cache) = cache.clone().downcast_arc::<ResponseCache<T>>().ok() {
+        if let Some(slot) = caches.get(&key) {
+            if let Ok(typed_cache) = slot.typed.clone().downcast::<ResponseCache<T>>() {
                 return typed_cache;
             }
         }
 
         // Create a new cache
-        let cache = Arc::new(ResponseCache::<T> {
-            cache: Mutex::new(HashMap::new()),
-            default_ttl: Duration::from_secs(60),
-            max_entries: 1000,
-        });
+        let cache = Arc::new(ResponseCache::<T>::new(self.default_ttl, self.max_entries));
 
         // Store the cache
         caches.insert(
-            name.to_string(),
-            cache.clone() as Arc<dyn Any + Send + Sync>,
+            key,
+            CacheSlot {
+                typed: cache.clone(),
+                erased: cache.clone(),
+            },
         );
 
         cache
     }
 
-    /// Clear all caches.
+    /// Remove cached responses under a path prefix from every cache,
+    /// including the disk tier.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        let caches = self.caches.lock().unwrap();
+        for slot in caches.values() {
+            slot.erased.invalidate_prefix(prefix);
+        }
+        if let Some(disk) = &self.disk {
+            disk.invalidate_prefix(prefix);
+        }
+    }
+
+    /// Get the disk tier, if configured.
+    pub fn disk(&self) -> Option<&Arc<DiskCache>> {
+        self.disk.as_ref()
+    }
+
+    /// Get the statistics of every in-memory cache, sorted by name.
+    ///
+    /// The statistics of the disk tier are available from [`DiskCache::stats`].
+    pub fn stats(&self) -> Vec<CacheStats> {
+        let caches = self.caches.lock().unwrap();
+        let mut stats: Vec<_> = caches
+            .iter()
+            .map(|((name, _), slot)| slot.erased.stats(name))
+            .collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
+    /// Get the keys of the entries of the caches with a name, most recently
+    /// used first within each cache.
+    pub fn keys(&self, name: &str) -> Vec<CacheKey> {
+        let caches = self.caches.lock().unwrap();
+        caches
+            .iter()
+            .filter(|((cache_name, _), _)| cache_name == name)
+            .flat_map(|(_, slot)| slot.erased.keys())
+            .collect()
+    }
+
+    /// Clear the caches with a name.
+    pub fn invalidate(&self, name: &str) {
+        let caches = self.caches.lock().unwrap();
+        for ((cache_name, _), slot) in caches.iter() {
+            if cache_name == name {
+                slot.erased.clear();
+            }
+        }
+    }
+
+    /// Clear all in-memory caches.
+    ///
+    /// The disk tier only holds market data, which is the same for every
+    /// login, and is kept; clear it with [`DiskCache::clear`].
     pub fn clear_all(&self) {
-        let mut caches = self.caches.lock().unwrap();
-        caches.clear();
+        let caches = self.caches.lock().unwrap();
+        for slot in caches.values() {
+            slot.erased.clear();
+        }
     }
 }
 
-use std::any::{Any, TypeId};
-
-/// Extension trait for Arc<dyn Any + Send + Sync>.
-trait ArcAnyExt {
-    /// Downcast to a specific type.
-    fn downcast_arc<T: 'static>(self) -> Result<Arc<T>, Self>
-    where
-        Self: Sized;
+impl Default for CacheManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl ArcAnyExt for Arc<dyn Any + Send + Sync> {
-    fn downcast_arc<T: 'static>(self) -> Result<Arc<T>, Self> {
-        if (*self).type_id() == TypeId::of::<T>() {
-            // SAFETY: We just checked that the type is correct
-            let ptr = Arc::into_raw(self) as *const T;
-            // SAFETY: We're creating a new Arc from the raw pointer
-            Ok(unsafe { Arc::from_raw(ptr) })
-        } else {
-            Err(self)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies_and_prefix_invalidation() {
+        let manager = CacheManager::new();
+        let cache = manager.get_cache::<u32>("GET");
+
+        cache.set_with_policy(
+            "GET",
+            "/api/trade/orders",
+            None,
+            None,
+            1,
+            CachePolicy::Never,
+        );
+        assert_eq!(
+            cache.lookup("GET", "/api/trade/orders", None, None),
+            CacheLookup::Miss
+        );
+
+        let swr = CachePolicy::StaleWhileRevalidate {
+            ttl: Duration::ZERO,
+            stale: Duration::from_secs(60),
+        };
+        cache.set_with_policy("GET", "/api/quote/instruments", None, None, 2, swr);
+        assert_eq!(
+            cache.lookup("GET", "/api/quote/instruments", None, None),
+            CacheLookup::Stale(2)
+        );
+        assert!(cache.begin_revalidation("GET", "/api/quote/instruments", None, None));
+        assert!(!cache.begin_revalidation("GET", "/api/quote/instruments", None, None));
+
+        // Restored entries keep the age they had on disk
+        let persistent = CachePolicy::Persistent {
+            ttl: Duration::from_secs(10),
+            stale: Duration::from_secs(60),
+        };
+        let key = |url: &str| CacheKey::new("GET", url, None, None);
+        cache.set_aged(key("/old"), 4, persistent, Duration::from_secs(30));
+        assert_eq!(
+            cache.lookup("GET", "/old", None, None),
+            CacheLookup::Stale(4)
+        );
+        cache.set_aged(key("/older"), 5, persistent, Duration::from_secs(71));
+        assert_eq!(cache.lookup("GET", "/older", None, None), CacheLookup::Miss);
+
+        cache.set_with_policy(
+            "GET",
+            "/api/trade/active",
+            None,
+            None,
+            3,
+            CachePolicy::Default,
+        );
+        manager.invalidate_prefix("/api/trade");
+        assert_eq!(cache.get("GET", "/api/trade/active", None, None), None);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let cache = ResponseCache::<u32>::new(Duration::from_secs(60), 2);
+        cache.set("GET", "/a", None, None, 1, None);
+        cache.set("GET", "/b", None, None, 2, None);
+
+        // Using /a makes /b the least recently used
+        assert_eq!(cache.get("GET", "/a", None, None), Some(1));
+        cache.set("GET", "/c", None, None, 3, None);
+        assert_eq!(cache.get("GET", "/b", None, None), None);
+
+        let urls: Vec<_> = cache
+            .keys()
+            .iter()
+            .map(|key| key.url().to_string())
+            .collect();
+        assert_eq!(urls, ["/c", "/a"]);
+        assert_eq!((cache.hits(), cache.misses(), cache.evictions()), (1, 1, 1));
+
+        // Replacing an entry of a full cache evicts nothing
+        cache.set("GET", "/a", None, None, 4, None);
+        assert_eq!(cache.evictions(), 1);
+        assert_eq!(cache.len(), 2);
+
+        // Dropping an expired entry to make room is not an eviction
+        let cache = ResponseCache::<u32>::new(Duration::ZERO, 1);
+        cache.set("GET", "/a", None, None, 1, None);
+        std::thread::sleep(Duration::from_millis(1));
+        cache.set("GET", "/b", None, None, 2, None);
+        assert_eq!(cache.evictions(), 0);
+        assert_eq!(cache.keys(), [CacheKey::new("GET", "/b", None, None)]);
+    }
+
+    #[test]
+    fn caches_can_be_inspected_and_invalidated_by_name() {
+        let manager = CacheManager::new();
+        let get = manager.get_cache::<u32>("GET");
+        let post = manager.get_cache::<u32>("POST");
+        get.set("GET", "/api/quote/bars", Some("symbol=AAPL"), None, 1, None);
+        post.set("POST", "/api/quote/search", None, Some("{}"), 2, None);
+        get.get("GET", "/api/quote/bars", Some("symbol=AAPL"), None);
+        get.get("GET", "/api/quote/bars", None, None);
+
+        let stats = manager.stats();
+        assert_eq!(
+            stats[0],
+            CacheStats {
+                name: "GET".to_string(),
+                entries: 1,
+                hits: 1,
+                misses: 1,
+                evictions: 0,
+            }
+        );
+        assert_eq!(stats[0].hit_rate(), Some(0.5));
+        assert_eq!(stats[1].name, "POST");
+        assert_eq!(manager.keys("GET")[0].query(), Some("symbol=AAPL"));
+
+        manager.invalidate("POST");
+        assert!(post.is_empty());
+        assert_eq!(get.len(), 1);
+
+        // Clearing keeps the caches and their counters
+        manager.clear_all();
+        assert!(get.is_empty());
+        assert!(Arc::ptr_eq(&get, &manager.get_cache::<u32>("GET")));
+        assert_eq!(manager.stats()[0].hits, 1);
+    }
+
+    /// Create an empty directory for a disk tier.
+    fn disk_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("webull-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn disk_tier_keeps_entries_across_instances() {
+        let dir = disk_dir("tier");
+        let bars = CacheKey::new("GET", "/api/quote/history/bars", Some("symbol=AAPL"), None);
+        let instruments =
+            CacheKey::new("GET", "/api/quote/instruments", Some("symbols=AAPL"), None);
+
+        let disk = DiskCache::new(&dir, Duration::from_secs(3600));
+        disk.set(&bars, &"bars".to_string());
+        disk.set(&instruments, &"instruments".to_string());
+
+        // A new instance, as after a restart, sees the same entries
+        let max_age = Duration::from_secs(60);
+        let disk = DiskCache::new(&dir, Duration::from_secs(3600));
+        let (value, age) = disk.get::<String>(&bars, max_age).unwrap();
+        assert_eq!((value.as_str(), age.as_secs()), ("bars", 0));
+        assert_eq!(disk.keys(), [bars.clone(), instruments.clone()]);
+
+        disk.invalidate_prefix("/api/quote/hist");
+        assert_eq!(disk.get::<String>(&bars, max_age), None);
+        assert!(disk.get::<String>(&instruments, max_age).is_some());
+
+        let stats = disk.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 2, 1));
+
+        // Entries older than the caller accepts are skipped but kept
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(disk.get::<String>(&instruments, Duration::ZERO), None);
+        assert_eq!(disk.stats().entries, 1);
+
+        // Entries outliving the tier's time-to-live are evicted when looked up
+        let disk = DiskCache::new(&dir, Duration::ZERO);
+        assert_eq!(disk.get::<String>(&instruments, max_age), None);
+        assert_eq!(disk.stats().evictions, 1);
+        assert_eq!(disk.stats().entries, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concurrent_writers_never_leave_partial_entries() {
+        let dir = disk_dir("writers");
+        let disk = Arc::new(DiskCache::new(&dir, Duration::from_secs(3600)));
+        let key = CacheKey::new("GET", "/api/quote/eod/bars", Some("date=2020-01-31"), None);
+        disk.set(&key, &"a".repeat(100_000));
+
+        let writers: Vec<_> = ["b", "c", "d", "e"]
+            .into_iter()
+            .map(|letter| {
+                let (disk, key) = (disk.clone(), key.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        disk.set(&key, &letter.repeat(100_000));
+                    }
+                })
+            })
+            .collect();
+        for _ in 0..200 {
+            let (value, _) = disk.get::<String>(&key, Duration::from_secs(60)).unwrap();
+            assert_eq!(value.len(), 100_000);
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // Every temporary file was renamed into place
+        let files: Vec<_> = fs::read_dir(dir.join("api/quote/eod/bars"))
+            .unwrap()
+            .collect();
+        assert_eq!(files.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_past_ranges_survive_restarts() {
+        use crate::models::market::EodBarsParams;
+        use crate::region::Region;
+        use crate::transport::testing::FakeTransport;
+        use crate::WebullClient;
+
+        let dir = disk_dir("client");
+        let config = CacheConfig {
+            disk_dir: Some(dir.clone()),
+            ..CacheConfig::default()
+        };
+        let past = EodBarsParams::new("913256135", 30).date("2020-01-31");
+        let latest = EodBarsParams::new("913256135", 30);
+
+        // Bars up to a past date are downloaded once and then served from
+        // disk, even after logging in again clears the memory tier. The
+        // latest bars may still change, so every client downloads them after
+        // each login.
+        for expected_past_downloads in [1, 0] {
+            let fake = FakeTransport::new();
+            let client = WebullClient::builder()
+                .with_transport(fake.clone())
+                .with_region(Region::Jp)
+                .with_cache_config(config.clone())
+                .build()
+                .unwrap();
+            for _ in 0..2 {
+                client.login("user", "password").await.unwrap();
+                client.market_data().get_eod_bar(&past).await.unwrap();
+                client.market_data().get_eod_bar(&latest).await.unwrap();
+            }
+
+            let downloads = |date: bool| {
+                fake.sent()
+                    .iter()
+                    .filter(|request| request.url.path().ends_with("/eod/bars"))
+                    .filter(|request| {
+                        request.url.query().unwrap_or_default().contains("date=") == date
+                    })
+                    .count()
+            };
+            assert_eq!(downloads(true), expected_past_downloads);
+            assert_eq!(downloads(false), 2);
         }
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,340 @@
+use crate::config::CircuitBreakerConfig;
+use crate::error::{WebullError, WebullResult};
+use crate::telemetry::{self, Metrics, NoopMetrics};
+use crate::utils::rate_limit::RateCategory;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// State of a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Requests are sent and their failures counted
+    Closed,
+
+    /// Requests fail immediately
+    Open,
+
+    /// A limited number of trial requests are sent
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Get the state name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Circuit of one endpoint category.
+#[derive(Debug)]
+struct Circuit {
+    /// Current state
+    state: CircuitState,
+
+    /// Start of the current failure counting window
+    window_start: Instant,
+
+    /// Requests completed in the window
+    requests: u32,
+
+    /// Requests failed in the window
+    failures: u32,
+
+    /// When the circuit last opened
+    opened_at: Instant,
+
+    /// Trial requests let through since the circuit became half-open
+    trials: u32,
+
+    /// Trial requests that succeeded
+    trial_successes: u32,
+}
+
+impl Circuit {
+    fn new(now: Instant) -> Self {
+        Self {
+            state: CircuitState::Closed,
+            window_start: now,
+            requests: 0,
+            failures: 0,
+            opened_at: now,
+            trials: 0,
+            trial_successes: 0,
+        }
+    }
+}
+
+/// Circuit breaker with one circuit per [`RateCategory`].
+///
+/// See [`CircuitBreakerConfig`] for when circuits open and close.
+pub struct CircuitBreaker {
+    /// Settings
+    config: CircuitBreakerConfig,
+
+    /// Circuits by category
+    circuits: Mutex<HashMap<RateCategory, Circuit>>,
+
+    /// Metrics sink for state changes
+    metrics: Arc<dyn Metrics>,
+}
+
+impl CircuitBreaker {
+    /// Create a circuit breaker from circuit breaker settings.
+    pub fn from_config(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            config: config.clone(),
+            circuits: Mutex::new(HashMap::new()),
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// Report state changes to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Get the state of a category's circuit.
+    pub fn state(&self, category: RateCategory) -> CircuitState {
+        let mut circuits = self.circuits.lock().unwrap();
+        match circuits.get_mut(&category) {
+            Some(circuit) => {
+                self.half_open_if_due(category, circuit, Instant::now());
+                circuit.state
+            }
+            None => CircuitState::Closed,
+        }
+    }
+
+    /// Check if a request to an API path may be sent.
+    ///
+    /// Fails with [`WebullError::CircuitOpen`] while the path's circuit is
+    /// open, or half-open with all trial requests in flight. A request that
+    /// is let through must be reported with [`CircuitBreaker::record`], or
+    /// with [`CircuitBreaker::release`] if it was never sent.
+    pub fn acquire(&self, path: &str) -> WebullResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let category = RateCategory::from_path(path);
+        let now = Instant::now();
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits
+            .entry(category)
+            .or_insert_with(|| Circuit::new(now));
+        self.half_open_if_due(category, circuit, now);
+
+        match circuit.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::HalfOpen if circuit.trials < self.config.half_open_requests => {
+                circuit.trials += 1;
+                Ok(())
+            }
+            CircuitState::HalfOpen => Err(WebullError::CircuitOpen {
+                category: category.name(),
+                retry_in: Duration::ZERO,
+            }),
+            CircuitState::Open => Err(WebullError::CircuitOpen {
+                category: category.name(),
+                retry_in: (circuit.opened_at + self.config.open_duration)
+                    .saturating_duration_since(now),
+            }),
+        }
+    }
+
+    /// Report whether a request to an API path failed.
+    ///
+    /// Only network errors, timeouts and server errors count as failures.
+    pub fn record(&self, path: &str, failed: bool) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let category = RateCategory::from_path(path);
+        let now = Instant::now();
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits
+            .entry(category)
+            .or_insert_with(|| Circuit::new(now));
+
+        match circuit.state {
+            CircuitState::Closed => {
+                if now.saturating_duration_since(circuit.window_start) >= self.config.window {
+                    circuit.window_start = now;
+                    circuit.requests = 0;
+                    circuit.failures = 0;
+                }
+
+                circuit.requests += 1;
+                if failed {
+                    circuit.failures += 1;
+                }
+                if circuit.requests >= self.config.minimum_requests
+                    && circuit.failures * 100 >= self.config.failure_rate_percent * circuit.requests
+                {
+                    self.open(category, circuit, now);
+                }
+            }
+            CircuitState::HalfOpen if failed => self.open(category, circuit, now),
+            CircuitState::HalfOpen => {
+                circuit.trial_successes += 1;
+                if circuit.trial_successes >= self.config.half_open_requests {
+                    *circuit = Circuit::new(now);
+                    self.transition(category, CircuitState::Closed);
+                }
+            }
+            // A request sent before the circuit opened
+            CircuitState::Open => {}
+        }
+    }
+
+    /// Report that a request to an API path was let through but never sent.
+    ///
+    /// The circuit's state is left unchanged, and a trial request slot taken
+    /// by a half-open circuit is handed back.
+    pub fn release(&self, path: &str) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let category = RateCategory::from_path(path);
+        let mut circuits = self.circuits.lock().unwrap();
+        if let Some(circuit) = circuits.get_mut(&category) {
+            if circuit.state == CircuitState::HalfOpen {
+                circuit.trials = circuit.trials.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Close all circuits.
+    pub fn reset(&self) {
+        self.circuits.lock().unwrap().clear();
+    }
+
+    /// Open a circuit.
+    fn open(&self, category: RateCategory, circuit: &mut Circuit, now: Instant) {
+        circuit.state = CircuitState::Open;
+        circuit.opened_at = now;
+        self.transition(category, CircuitState::Open);
+    }
+
+    /// Move an open circuit to half-open once it has been open long enough.
+    fn half_open_if_due(&self, category: RateCategory, circuit: &mut Circuit, now: Instant) {
+        if circuit.state == CircuitState::Open
+            && now.saturating_duration_since(circuit.opened_at) >= self.config.open_duration
+        {
+            circuit.state = CircuitState::HalfOpen;
+            circuit.trials = 0;
+            circuit.trial_successes = 0;
+            self.transition(category, CircuitState::HalfOpen);
+        }
+    }
+
+    /// Log and report a state change.
+    fn transition(&self, category: RateCategory, state: CircuitState) {
+        log::warn!(
+            "Circuit for {} requests is now {}",
+            category.name(),
+            state.name()
+        );
+        self.metrics.increment_counter(
+            telemetry::CIRCUIT_BREAKER_TRANSITIONS_TOTAL,
+            &[("category", category.name()), ("state", state.name())],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::middleware::HttpResponse;
+    use crate::transport::testing::FakeTransport;
+    use crate::utils::retry::RetryPolicy;
+    use crate::WebullClient;
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn circuits_open_after_server_errors() {
+        // Logins work, everything else is unavailable
+        let fake = FakeTransport::with_handler(|request| {
+            (!request.url.path().starts_with("/api/passport"))
+                .then(|| HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""))
+        });
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .with_circuit_breaker_config(CircuitBreakerConfig {
+                minimum_requests: 2,
+                ..Default::default()
+            })
+            .with_retry_policy(RetryPolicy::none())
+            .without_cache()
+            .build()
+            .unwrap();
+
+        client.login("user", "password").await.unwrap();
+        for _ in 0..2 {
+            assert!(client.accounts().get_accounts().await.is_err());
+        }
+        let sent = fake.sent().len();
+        let error = client.accounts().get_accounts().await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::CircuitOpen);
+        assert_eq!(fake.sent().len(), sent);
+        assert_eq!(
+            client
+                .circuit_breaker()
+                .unwrap()
+                .state(RateCategory::Account),
+            CircuitState::Open
+        );
+    }
+
+    #[tokio::test]
+    async fn requests_that_are_never_sent_leave_circuits_alone() {
+        let fake = FakeTransport::with_handler(|request| {
+            (!request.url.path().starts_with("/api/passport"))
+                .then(|| HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""))
+        });
+        let client = WebullClient::builder()
+            .with_transport(fake)
+            .with_circuit_breaker_config(CircuitBreakerConfig {
+                minimum_requests: 2,
+                open_duration: Duration::ZERO,
+                half_open_requests: 1,
+                ..Default::default()
+            })
+            .with_retry_policy(RetryPolicy::none())
+            .without_cache()
+            .build()
+            .unwrap();
+        let circuit_breaker = client.circuit_breaker().unwrap();
+
+        client.login("user", "password").await.unwrap();
+        for _ in 0..2 {
+            assert!(client.accounts().get_accounts().await.is_err());
+        }
+        assert_eq!(
+            circuit_breaker.state(RateCategory::Account),
+            CircuitState::HalfOpen
+        );
+
+        // Without a token the auth layer fails every request before sending
+        // it, which neither closes the circuit nor uses up its trial slot
+        client.logout().await.unwrap();
+        for _ in 0..2 {
+            let error = client.accounts().get_accounts().await.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::Unauthorized);
+        }
+        assert_eq!(
+            circuit_breaker.state(RateCategory::Account),
+            CircuitState::HalfOpen
+        );
+    }
+}
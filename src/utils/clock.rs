@@ -0,0 +1,210 @@
+use crate::config::ClockSkewConfig;
+use crate::error::{WebullError, WebullResult};
+use crate::telemetry::{self, Metrics, NoopMetrics};
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::header::{HeaderMap, DATE};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Skews smaller than the one-second resolution of the `Date` header are
+/// treated as no skew.
+const DATE_RESOLUTION_MS: i64 = 1000;
+
+/// Clock for request timestamps, corrected for the measured skew between the
+/// local and server clocks.
+///
+/// See [`ClockSkewConfig`] for how the skew is measured and applied.
+pub struct Clock {
+    /// Settings
+    config: ClockSkewConfig,
+
+    /// Server time minus local time in milliseconds
+    skew_ms: AtomicI64,
+
+    /// Whether the skew has been measured
+    measured: AtomicBool,
+
+    /// Whether the skew is above the warning threshold
+    warned: AtomicBool,
+
+    /// Metrics sink for measurements
+    metrics: Arc<dyn Metrics>,
+}
+
+impl Clock {
+    /// Create a clock from clock skew settings.
+    pub fn from_config(config: &ClockSkewConfig) -> Self {
+        Self {
+            config: config.clone(),
+            skew_ms: AtomicI64::new(0),
+            measured: AtomicBool::new(false),
+            warned: AtomicBool::new(false),
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// Report measurements to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Get the measured skew, server time minus local time, if it has been
+    /// measured.
+    pub fn skew(&self) -> Option<TimeDelta> {
+        self.measured
+            .load(Ordering::Acquire)
+            .then(|| TimeDelta::milliseconds(self.skew_ms.load(Ordering::Acquire)))
+    }
+
+    /// Get the current time on the server clock, as far as it is known.
+    ///
+    /// This is the local time unless compensation is enabled.
+    pub fn now(&self) -> DateTime<Utc> {
+        match self.skew() {
+            Some(skew) if self.config.compensate => Utc::now() + skew,
+            _ => Utc::now(),
+        }
+    }
+
+    /// Generate a timestamp for a signed request, in milliseconds since the
+    /// Unix epoch.
+    ///
+    /// Fails with [`WebullError::ClockSkew`] if the measured skew is above the
+    /// configured maximum.
+    pub fn timestamp(&self) -> WebullResult<String> {
+        self.check()?;
+        Ok(self.now().timestamp_millis().to_string())
+    }
+
+    /// Check that the measured skew is not above the configured maximum.
+    pub fn check(&self) -> WebullResult<()> {
+        match (self.skew(), self.config.max_skew) {
+            (Some(skew), Some(max))
+                if skew.num_milliseconds().unsigned_abs() > max.as_millis() as u64 =>
+            {
+                Err(WebullError::ClockSkew {
+                    skew_ms: skew.num_milliseconds(),
+                    max,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Measure the skew from the `Date` header of a response.
+    ///
+    /// `sent_at` and `received_at` are the local times the request was sent
+    /// and the response received; the server is assumed to have answered
+    /// halfway between them. Returns the skew, or `None` if the response has
+    /// no valid `Date` header.
+    pub fn observe(
+        &self,
+        headers: &HeaderMap,
+        sent_at: DateTime<Utc>,
+        received_at: DateTime<Utc>,
+    ) -> Option<TimeDelta> {
+        let date = headers
+            .get(DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok())?;
+
+        // The header is truncated to the second, so the server time is on
+        // average half a second later
+        let server_time =
+            date.with_timezone(&Utc) + TimeDelta::milliseconds(DATE_RESOLUTION_MS / 2);
+        let local_time = sent_at + (received_at - sent_at) / 2;
+        self.set_skew(server_time - local_time);
+        self.skew()
+    }
+
+    /// Record a measured skew, server time minus local time.
+    pub fn set_skew(&self, skew: TimeDelta) {
+        let skew_ms = match skew.num_milliseconds() {
+            skew_ms if skew_ms.abs() < DATE_RESOLUTION_MS => 0,
+            skew_ms => skew_ms,
+        };
+        self.skew_ms.store(skew_ms, Ordering::Release);
+        self.measured.store(true, Ordering::Release);
+        self.metrics.record_histogram(
+            telemetry::CLOCK_SKEW_SECONDS,
+            skew_ms.unsigned_abs() as f64 / 1000.0,
+            &[],
+        );
+
+        // Warn once each time the skew goes above the threshold
+        let above = skew_ms.unsigned_abs() > self.config.warn_threshold.as_millis() as u64;
+        if above && !self.warned.swap(true, Ordering::AcqRel) {
+            log::warn!(
+                "Local clock is {} ms off the server clock; {}",
+                skew_ms,
+                if self.config.compensate {
+                    "request timestamps are being corrected"
+                } else {
+                    "signed requests may be rejected"
+                }
+            );
+        } else if !above {
+            self.warned.store(false, Ordering::Release);
+        }
+    }
+
+    /// Forget the measured skew.
+    pub fn reset(&self) {
+        self.measured.store(false, Ordering::Release);
+        self.skew_ms.store(0, Ordering::Release);
+        self.warned.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::transport::testing::{self, FakeTransport};
+    use crate::WebullClient;
+
+    /// Create a transport for a server whose clock is an hour ahead.
+    fn server_ahead() -> FakeTransport {
+        FakeTransport::with_handler(|request| {
+            let date = (Utc::now() + TimeDelta::hours(1)).to_rfc2822();
+            let mut response = testing::respond(request);
+            response.headers.insert(DATE, date.parse().unwrap());
+            Some(response)
+        })
+    }
+
+    #[tokio::test]
+    async fn timestamps_follow_the_server_clock() {
+        let fake = server_ahead();
+        let client = WebullClient::builder()
+            .with_transport(fake.clone())
+            .build()
+            .unwrap();
+        assert!(client.clock().skew().is_none());
+        let skew = client.sync_clock().await.unwrap();
+        assert!((skew.num_seconds() - 3600).abs() <= 2);
+
+        client.login("user", "password").await.unwrap();
+        let login = fake.sent().pop().unwrap();
+        let sent: i64 = login.headers["timestamp"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let offset = sent - Utc::now().timestamp_millis();
+        assert!((offset / 1000 - 3600).abs() <= 2);
+
+        let client = WebullClient::builder()
+            .with_transport(server_ahead())
+            .with_clock_skew_config(ClockSkewConfig {
+                max_skew: Some(std::time::Duration::from_secs(30)),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        client.sync_clock().await.unwrap();
+        let error = client.login("user", "password").await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ClockSkew);
+    }
+}
@@ -0,0 +1,663 @@
+use crate::auth::Credentials;
+use crate::error::{WebullError, WebullResult};
+use crate::utils::credentials::CredentialStore;
+use crate::utils::secret::SecretString;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Default prefix for credential environment variables.
+pub const DEFAULT_ENV_PREFIX: &str = "WEBULL_";
+
+/// Environment variable selecting the profile to read from the credentials file.
+///
+/// This is separate from `WEBULL_PROFILE`, which selects a configuration
+/// profile in [`crate::WebullConfig::from_env`].
+pub const PROFILE_ENV_VAR: &str = "WEBULL_CREDENTIALS_PROFILE";
+
+/// Name of the profile used when none is selected.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Default time a credential helper command may take before it is killed.
+pub const DEFAULT_HELPER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Credential material resolved from one or more providers.
+///
+/// Every field is optional: a provider only fills in what it knows about, and
+/// a [`CredentialProviderChain`] merges the results of several providers.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedCredentials {
+    /// Username for authentication
+    pub username: Option<String>,
+
+    /// Password for authentication
+    pub password: Option<SecretString>,
+
+    /// API key for authentication
+    pub api_key: Option<String>,
+
+    /// API secret for authentication
+    pub api_secret: Option<SecretString>,
+}
+
+impl ResolvedCredentials {
+    /// Fill in any missing fields from another set of credentials.
+    ///
+    /// Fields that are already set are kept, so the first provider to supply a
+    /// value wins.
+    pub fn merge(mut self, other: ResolvedCredentials) -> Self {
+        self.username = self.username.or(other.username);
+        self.password = self.password.or(other.password);
+        self.api_key = self.api_key.or(other.api_key);
+        self.api_secret = self.api_secret.or(other.api_secret);
+        self
+    }
+
+    /// Check if no field has been resolved.
+    pub fn is_empty(&self) -> bool {
+        self.username.is_none()
+            && self.password.is_none()
+            && self.api_key.is_none()
+            && self.api_secret.is_none()
+    }
+
+    /// Check if every field has been resolved.
+    pub fn is_complete(&self) -> bool {
+        self.username.is_some()
+            && self.password.is_some()
+            && self.api_key.is_some()
+            && self.api_secret.is_some()
+    }
+
+    /// Get the username and password as login credentials, if both were resolved.
+    pub fn credentials(&self) -> Option<Credentials> {
+        match (&self.username, &self.password) {
+            (Some(username), Some(password)) => {
+                Some(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Set a field from a `key=value` pair, ignoring unknown keys.
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "username" => self.username = Some(value.to_string()),
+            "password" => self.password = Some(value.into()),
+            "api_key" => self.api_key = Some(value.to_string()),
+            "api_secret" => self.api_secret = Some(value.into()),
+            _ => {}
+        }
+    }
+}
+
+/// A source of credentials.
+pub trait CredentialProvider: Send + Sync {
+    /// Name of the provider, used in error messages.
+    fn name(&self) -> &str;
+
+    /// Resolve whatever credentials this provider knows about.
+    ///
+    /// A provider that has nothing to offer should return an empty
+    /// [`ResolvedCredentials`] rather than an error, so that the chain can move
+    /// on to the next provider.
+    fn provide(&self) -> WebullResult<ResolvedCredentials>;
+}
+
+/// Provider reading credentials from environment variables.
+///
+/// With the default prefix the variables are `WEBULL_USERNAME`, `WEBULL_PASSWORD`,
+/// `WEBULL_API_KEY` and `WEBULL_API_SECRET`.
+#[derive(Debug, Clone)]
+pub struct EnvCredentialProvider {
+    /// Prefix for the environment variable names
+    prefix: String,
+}
+
+impl EnvCredentialProvider {
+    /// Create a new provider using the default `WEBULL_` prefix.
+    pub fn new() -> Self {
+        Self::with_prefix(DEFAULT_ENV_PREFIX)
+    }
+
+    /// Create a new provider using a custom prefix.
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    /// Read a non-empty environment variable.
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(format!("{}{}", self.prefix, name))
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+}
+
+impl Default for EnvCredentialProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialProvider for EnvCredentialProvider {
+    fn name(&self) -> &str {
+        "environment"
+    }
+
+    fn provide(&self) -> WebullResult<ResolvedCredentials> {
+        Ok(ResolvedCredentials {
+            username: self.var("USERNAME"),
+            password: self.var("PASSWORD").map(Into::into),
+            api_key: self.var("API_KEY"),
+            api_secret: self.var("API_SECRET").map(Into::into),
+        })
+    }
+}
+
+/// Credentials stored in a profile of a credentials file.
+#[derive(Debug, Default, Deserialize)]
+struct ProfileCredentials {
+    username: Option<String>,
+    password: Option<SecretString>,
+    api_key: Option<String>,
+    api_secret: Option<SecretString>,
+}
+
+/// Layout of a credentials file.
+#[derive(Debug, Default, Deserialize)]
+struct ProfileFile {
+    #[serde(default)]
+    profiles: HashMap<String, ProfileCredentials>,
+}
+
+/// Provider reading credentials from a named profile in a TOML or JSON file.
+///
+/// Profiles live under a `profiles` table, for example:
+///
+/// ```toml
+/// [profiles.paper-us]
+/// username = "trader@example.com"
+/// password = "..."
+/// ```
+///
+/// Files ending in `.json` are parsed as JSON; everything else is parsed as TOML.
+/// A missing file or profile resolves to no credentials.
+#[derive(Debug, Clone)]
+pub struct ProfileCredentialProvider {
+    /// Path to the credentials file
+    path: PathBuf,
+
+    /// Profile to read
+    profile: String,
+}
+
+impl ProfileCredentialProvider {
+    /// Create a new provider for a file and profile.
+    pub fn new(path: impl Into<PathBuf>, profile: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            profile: profile.into(),
+        }
+    }
+
+    /// Create a provider for `~/.webull/credentials`, using the profile named by
+    /// `WEBULL_CREDENTIALS_PROFILE` or `default`.
+    pub fn from_default_location() -> Option<Self> {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        let path = Path::new(&home).join(".webull").join("credentials");
+        let profile =
+            std::env::var(PROFILE_ENV_VAR).unwrap_or_else(|_| DEFAULT_PROFILE.to_string());
+        Some(Self::new(path, profile))
+    }
+
+    /// Get the profile name.
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Parse the credentials file.
+    fn load(&self) -> WebullResult<Option<ProfileFile>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(&self.path).map_err(|e| {
+            WebullError::InvalidRequest(format!(
+                "Failed to read credentials file {}: {}",
+                self.path.display(),
+                e
+            ))
+        })?;
+
+        let is_json = self
+            .path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+
+        let file = if is_json {
+            serde_json::from_str(&contents).map_err(WebullError::SerializationError)?
+        } else {
+            toml::from_str(&contents).map_err(|e| {
+                WebullError::InvalidRequest(format!(
+                    "Invalid credentials file {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            })?
+        };
+
+        Ok(Some(file))
+    }
+}
+
+impl CredentialProvider for ProfileCredentialProvider {
+    fn name(&self) -> &str {
+        "profile"
+    }
+
+    fn provide(&self) -> WebullResult<ResolvedCredentials> {
+        let profile = self
+            .load()?
+            .and_then(|mut file| file.profiles.remove(&self.profile))
+            .unwrap_or_default();
+
+        Ok(ResolvedCredentials {
+            username: profile.username,
+            password: profile.password,
+            api_key: profile.api_key,
+            api_secret: profile.api_secret,
+        })
+    }
+}
+
+/// Provider running an external helper command, in the style of git credential helpers.
+///
+/// The command is run through the shell with `get` appended as its last argument.
+/// It receives `key=value` lines on stdin describing the request (`protocol`,
+/// `host`, and `username` when known), terminated by a blank line. It must
+/// answer with `key=value` lines on stdout; recognised keys are `username`,
+/// `password`, `api_key` and `api_secret`. Unknown keys are ignored, and a
+/// non-zero exit status is reported as an error.
+///
+/// A helper that has not exited within the timeout (30 seconds by default) is
+/// killed and reported as an error.
+#[derive(Debug, Clone)]
+pub struct CommandCredentialProvider {
+    /// Helper command line
+    command: String,
+
+    /// Maximum time the helper may run
+    timeout: Duration,
+
+    /// Host the credentials are requested for
+    host: Option<String>,
+
+    /// Username hint passed to the helper
+    username: Option<String>,
+}
+
+impl CommandCredentialProvider {
+    /// Create a new provider for a helper command.
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            timeout: DEFAULT_HELPER_TIMEOUT,
+            host: None,
+            username: None,
+        }
+    }
+
+    /// Set the maximum time the helper may run.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the host passed to the helper.
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Set the username hint passed to the helper.
+    pub fn with_username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Build the request written to the helper's stdin.
+    fn request(&self) -> String {
+        let mut request = String::from("protocol=https\n");
+        if let Some(host) = &self.host {
+            request.push_str(&format!("host={}\n", host));
+        }
+        if let Some(username) = &self.username {
+            request.push_str(&format!("username={}\n", username));
+        }
+        request.push('\n');
+        request
+    }
+
+    /// Create the shell command for the helper.
+    fn shell_command(&self) -> Command {
+        let command_line = format!("{} get", self.command);
+        if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(command_line);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(command_line);
+            command
+        }
+    }
+}
+
+impl CommandCredentialProvider {
+    /// Wait for the helper to exit, killing it once the timeout has passed.
+    fn wait(&self, child: &mut Child) -> WebullResult<ExitStatus> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let status = child.try_wait().map_err(|e| {
+                WebullError::InvalidRequest(format!(
+                    "Failed to run credential helper '{}': {}",
+                    self.command, e
+                ))
+            })?;
+            if let Some(status) = status {
+                return Ok(status);
+            }
+
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(WebullError::InvalidRequest(format!(
+                    "Credential helper '{}' timed out after {:?}",
+                    self.command, self.timeout
+                )));
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Read a pipe to the end on a background thread.
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+/// Collect the output of a pipe reader thread.
+fn join_output(handle: JoinHandle<Vec<u8>>) -> Vec<u8> {
+    handle.join().unwrap_or_default()
+}
+
+impl CredentialProvider for CommandCredentialProvider {
+    fn name(&self) -> &str {
+        &self.command
+    }
+
+    fn provide(&self) -> WebullResult<ResolvedCredentials> {
+        let mut child = self
+            .shell_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                WebullError::InvalidRequest(format!(
+                    "Failed to run credential helper '{}': {}",
+                    self.command, e
+                ))
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // The helper may exit without reading its input, so a broken pipe is not an error
+            let _ = stdin.write_all(self.request().as_bytes());
+        }
+
+        // Drain the pipes on their own threads so a chatty helper cannot block
+        // on a full pipe while we wait for it to exit
+        let stdout = child.stdout.take().map(read_to_end);
+        let stderr = child.stderr.take().map(read_to_end);
+
+        let status = self.wait(&mut child)?;
+        let stdout = stdout.map(join_output).unwrap_or_default();
+        let stderr = stderr.map(join_output).unwrap_or_default();
+
+        if !status.success() {
+            return Err(WebullError::InvalidRequest(format!(
+                "Credential helper '{}' failed ({}): {}",
+                self.command,
+                status,
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+
+        let mut stdout = String::from_utf8(stdout).map_err(|e| {
+            WebullError::InvalidRequest(format!(
+                "Credential helper '{}' returned invalid UTF-8: {}",
+                self.command, e
+            ))
+        })?;
+
+        let mut resolved = ResolvedCredentials::default();
+        for line in stdout.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                resolved.set(key.trim(), value);
+            }
+        }
+
+        zeroize::Zeroize::zeroize(&mut stdout);
+
+        Ok(resolved)
+    }
+}
+
+/// Provider reading the username and password from a [`CredentialStore`].
+pub struct StoreCredentialProvider {
+    /// Credential store
+    store: Arc<Box<dyn CredentialStore>>,
+}
+
+impl StoreCredentialProvider {
+    /// Create a new provider for a credential store.
+    pub fn new(store: Arc<Box<dyn CredentialStore>>) -> Self {
+        Self { store }
+    }
+}
+
+impl CredentialProvider for StoreCredentialProvider {
+    fn name(&self) -> &str {
+        "credential store"
+    }
+
+    fn provide(&self) -> WebullResult<ResolvedCredentials> {
+        Ok(match self.store.get_credentials()? {
+            Some(credentials) => ResolvedCredentials {
+                username: Some(credentials.username.clone()),
+                password: Some(credentials.password.clone()),
+                ..Default::default()
+            },
+            None => ResolvedCredentials::default(),
+        })
+    }
+}
+
+/// An ordered chain of credential providers.
+///
+/// Providers are queried in order and their results merged, so the first
+/// provider to supply a field wins. Resolution stops early once every field
+/// has been resolved.
+#[derive(Default)]
+pub struct CredentialProviderChain {
+    /// Providers in resolution order
+    providers: Vec<Box<dyn CredentialProvider>>,
+}
+
+impl CredentialProviderChain {
+    /// Create an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the default chain: environment variables, then the default
+    /// credentials file profile.
+    pub fn default_chain() -> Self {
+        let chain = Self::new().with_provider(EnvCredentialProvider::new());
+        match ProfileCredentialProvider::from_default_location() {
+            Some(provider) => chain.with_provider(provider),
+            None => chain,
+        }
+    }
+
+    /// Append a provider to the chain.
+    pub fn with_provider(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Append a provider to the chain.
+    pub fn push(&mut self, provider: impl CredentialProvider + 'static) {
+        self.providers.push(Box::new(provider));
+    }
+
+    /// Get the number of providers in the chain.
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Check if the chain has no providers.
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Resolve credentials from every provider in order.
+    pub fn resolve(&self) -> WebullResult<ResolvedCredentials> {
+        let mut resolved = ResolvedCredentials::default();
+
+        for provider in &self.providers {
+            if resolved.is_complete() {
+                break;
+            }
+
+            let provided = provider.provide().map_err(|e| {
+                WebullError::InvalidRequest(format!(
+                    "Credential provider '{}' failed: {}",
+                    provider.name(),
+                    e
+                ))
+            })?;
+
+            resolved = resolved.merge(provided);
+        }
+
+        Ok(resolved)
+    }
+}
+
+impl CredentialProvider for CredentialProviderChain {
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    fn provide(&self) -> WebullResult<ResolvedCredentials> {
+        self.resolve()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticProvider(ResolvedCredentials);
+
+    impl CredentialProvider for StaticProvider {
+        fn name(&self) -> &str {
+            "static"
+        }
+
+        fn provide(&self) -> WebullResult<ResolvedCredentials> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn chain_prefers_earlier_providers() {
+        let chain = CredentialProviderChain::new()
+            .with_provider(StaticProvider(ResolvedCredentials {
+                username: Some("first".to_string()),
+                ..Default::default()
+            }))
+            .with_provider(StaticProvider(ResolvedCredentials {
+                username: Some("second".to_string()),
+                password: Some("secret".into()),
+                ..Default::default()
+            }));
+
+        let resolved = chain.resolve().unwrap();
+        assert_eq!(resolved.username.as_deref(), Some("first"));
+        assert_eq!(resolved.password.unwrap().expose_secret(), "secret");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_provider_parses_helper_output() {
+        let provider = CommandCredentialProvider::new(
+            "cat >/dev/null; printf 'username=alice\\npassword=p=w\\nignored=x\\n'; true",
+        );
+
+        let resolved = provider.provide().unwrap();
+        assert_eq!(resolved.username.as_deref(), Some("alice"));
+        assert_eq!(resolved.password.unwrap().expose_secret(), "p=w");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn chain_merges_profile_and_helper() {
+        let path = std::env::temp_dir().join(format!("webull-creds-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            "[profiles.paper-us]\nusername = \"bob\"\napi_key = \"key\"\n",
+        )
+        .unwrap();
+
+        let chain = CredentialProviderChain::new()
+            .with_provider(EnvCredentialProvider::with_prefix("WEBULL_TEST_UNSET_"))
+            .with_provider(ProfileCredentialProvider::new(&path, "paper-us"))
+            .with_provider(CommandCredentialProvider::new(
+                "cat >/dev/null; printf 'api_key=other\\npassword=pw\\n'",
+            ));
+
+        let resolved = chain.resolve().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resolved.username.as_deref(), Some("bob"));
+        assert_eq!(resolved.api_key.as_deref(), Some("key"));
+        assert_eq!(resolved.password.unwrap().expose_secret(), "pw");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_provider_kills_helper_after_timeout() {
+        let provider = CommandCredentialProvider::new("cat >/dev/null; exec sleep 10;")
+            .with_timeout(Duration::from_millis(100));
+
+        let started = Instant::now();
+        let error = provider.provide().unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
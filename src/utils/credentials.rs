@@ -220,20 +220,20 @@ impl EncryptedCredentialStore {
 
         // Parse the stored credentials
         let stored: StoredCredentials =
-            serde_json::from_str(&contents).map_err(|e| WebullError::SerializationError(e))?;
+            serde_json::from_str(&contents).map_err(WebullError::SerializationError)?;
 
         // Decrypt the username and password
         let username = self.decrypt(&stored.encrypted_username, &stored.iv, &stored.salt)?;
         let password = self.decrypt(&stored.encrypted_password, &stored.iv, &stored.salt)?;
 
-        Ok(Some(Credentials { username, password }))
+        Ok(Some(Credentials::new(username, password)))
     }
 
     /// Save credentials to disk.
     fn save_credentials(&self, credentials: &Credentials) -> WebullResult<()> {
         // Encrypt the username and password
         let (encrypted_username, iv, salt) = self.encrypt(&credentials.username)?;
-        let (encrypted_password, _, _) = self.encrypt(&credentials.password)?;
+        let (encrypted_password, _, _) = self.encrypt(credentials.password.expose_secret())?;
 
         // Create the stored credentials
         let stored = StoredCredentials {
@@ -244,8 +244,7 @@ impl EncryptedCredentialStore {
         };
 
         // Serialize to JSON
-        let json =
-            serde_json::to_string(&stored).map_err(|e| WebullError::SerializationError(e))?;
+        let json = serde_json::to_string(&stored).map_err(WebullError::SerializationError)?;
 
         // Write to file
         std::fs::write(&self.credentials_path, json).map_err(|e| {
@@ -270,7 +269,7 @@ impl EncryptedCredentialStore {
 
         // Parse the stored token
         let stored: StoredToken =
-            serde_json::from_str(&contents).map_err(|e| WebullError::SerializationError(e))?;
+            serde_json::from_str(&contents).map_err(WebullError::SerializationError)?;
 
         // Decrypt the token
         let token = self.decrypt(&stored.encrypted_token, &stored.iv, &stored.salt)?;
@@ -287,20 +286,20 @@ impl EncryptedCredentialStore {
             .ok_or_else(|| WebullError::InvalidRequest("Invalid timestamp".to_string()))?;
 
         Ok(Some(AccessToken {
-            token,
+            token: token.into(),
             expires_at,
-            refresh_token,
+            refresh_token: refresh_token.map(Into::into),
         }))
     }
 
     /// Save token to disk.
     fn save_token(&self, token: &AccessToken) -> WebullResult<()> {
         // Encrypt the token
-        let (encrypted_token, iv, salt) = self.encrypt(&token.token)?;
+        let (encrypted_token, iv, salt) = self.encrypt(token.token.expose_secret())?;
 
         // Encrypt the refresh token if present
         let encrypted_refresh_token = if let Some(refresh_token) = &token.refresh_token {
-            Some(self.encrypt(refresh_token)?.0)
+            Some(self.encrypt(refresh_token.expose_secret())?.0)
         } else {
             None
         };
@@ -315,8 +314,7 @@ impl EncryptedCredentialStore {
         };
 
         // Serialize to JSON
-        let json =
-            serde_json::to_string(&stored).map_err(|e| WebullError::SerializationError(e))?;
+        let json = serde_json::to_string(&stored).map_err(WebullError::SerializationError)?;
 
         // Write to file
         std::fs::write(&self.token_path, json).map_err(|e| {
@@ -8,7 +8,7 @@ use sha2::Sha256;
 pub fn generate_device_id() -> String {
     let mut rng = thread_rng();
     let random_bytes: [u8; 16] = rng.gen();
-    encode(&random_bytes)
+    encode(random_bytes)
 }
 
 /// Generate an HMAC-SHA256 signature.
@@ -0,0 +1,187 @@
+use crate::error::{WebullError, WebullResult};
+use crate::middleware::HttpResponse;
+use crate::telemetry::{self, Metrics, NoopMetrics};
+use reqwest::StatusCode;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use url::Url;
+
+/// Ordered list of base URLs that requests fail over across.
+///
+/// Requests go to the first host that has not failed recently. A host that
+/// cannot be reached, or answers with a 502, 503 or 504, is skipped for a
+/// cooldown and the request is sent to the next host. Requests that are not
+/// idempotent only move on when the connection could not be made, so they are
+/// never sent twice. When every host has failed recently, all of them are
+/// tried in order.
+pub struct Failover {
+    /// Base URLs, primary first
+    hosts: Vec<Url>,
+
+    /// How long a failed host is skipped
+    cooldown: Duration,
+
+    /// When each host may be used again, if it failed recently
+    failed_until: Mutex<Vec<Option<Instant>>>,
+
+    /// Metrics sink for failovers
+    metrics: Arc<dyn Metrics>,
+}
+
+impl Failover {
+    /// Create a failover list from a primary base URL and its fallbacks.
+    pub fn new(base_url: &str, fallback_urls: &[String], cooldown: Duration) -> WebullResult<Self> {
+        let hosts = std::iter::once(base_url)
+            .chain(fallback_urls.iter().map(String::as_str))
+            .map(|url| {
+                Url::parse(url)
+                    .map_err(|e| WebullError::InvalidRequest(format!("Invalid URL {}: {}", url, e)))
+            })
+            .collect::<WebullResult<Vec<_>>>()?;
+
+        Ok(Self {
+            failed_until: Mutex::new(vec![None; hosts.len()]),
+            hosts,
+            cooldown,
+            metrics: Arc::new(NoopMetrics),
+        })
+    }
+
+    /// Report failovers to a metrics sink.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Get the base URLs, primary first.
+    pub fn hosts(&self) -> &[Url] {
+        &self.hosts
+    }
+
+    /// Get the base URL requests are currently sent to.
+    pub fn active_host(&self) -> &Url {
+        &self.hosts[self.order()[0]]
+    }
+
+    /// Send a request, failing over to the next host if its host fails.
+    ///
+    /// `url` is the request URL on the primary host; `send` is called with
+    /// the URL on each host tried.
+    pub async fn send<F, Fut>(
+        &self,
+        url: &Url,
+        idempotent: bool,
+        mut send: F,
+    ) -> WebullResult<HttpResponse>
+    where
+        F: FnMut(Url) -> Fut,
+        Fut: Future<Output = WebullResult<HttpResponse>>,
+    {
+        let order = self.order();
+        let last = order.len() - 1;
+
+        for (position, index) in order.into_iter().enumerate() {
+            let result = send(self.rebase(url, index)).await;
+            let host_failed = match &result {
+                Ok(response) => matches!(
+                    response.status,
+                    StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(WebullError::NetworkError(_)) => true,
+                Err(_) => false,
+            };
+            if !host_failed {
+                self.failed_until.lock().unwrap()[index] = None;
+                return result;
+            }
+
+            // Only requests that cannot have reached the server are safe to resend
+            let resendable = idempotent
+                || matches!(&result, Err(WebullError::NetworkError(e)) if e.is_connect());
+            if self.hosts.len() == 1 || position == last || !resendable {
+                if self.hosts.len() > 1 {
+                    self.mark_failed(index);
+                }
+                return result;
+            }
+
+            log::debug!(
+                "Failing over from {} after a failed request",
+                self.hosts[index]
+            );
+            self.mark_failed(index);
+        }
+
+        unreachable!("there is always at least one host")
+    }
+
+    /// Get the host indexes in the order to try them: recently failed hosts
+    /// go last.
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let failed_until = self.failed_until.lock().unwrap();
+        let (healthy, failed): (Vec<usize>, Vec<usize>) = (0..self.hosts.len())
+            .partition(|&index| failed_until[index].is_none_or(|until| until <= now));
+        healthy.into_iter().chain(failed).collect()
+    }
+
+    /// Skip a host for the cooldown.
+    fn mark_failed(&self, index: usize) {
+        self.failed_until.lock().unwrap()[index] = Some(Instant::now() + self.cooldown);
+        self.metrics.increment_counter(
+            telemetry::FAILOVERS_TOTAL,
+            &[("host", self.hosts[index].as_str())],
+        );
+    }
+
+    /// Move a URL on the primary host to another host.
+    fn rebase(&self, url: &Url, index: usize) -> Url {
+        if index == 0 {
+            return url.clone();
+        }
+
+        let primary_path = self.hosts[0].path().trim_end_matches('/');
+        let path = url.path().strip_prefix(primary_path).unwrap_or(url.path());
+        let host = &self.hosts[index];
+        let mut rebased = host.clone();
+        rebased.set_path(&format!("{}{}", host.path().trim_end_matches('/'), path));
+        rebased.set_query(url.query());
+        rebased
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::middleware::HttpResponse;
+    use crate::transport::testing::FakeTransport;
+    use crate::utils::retry::RetryPolicy;
+    use crate::WebullClient;
+    use reqwest::StatusCode;
+
+    #[tokio::test]
+    async fn requests_fail_over_to_fallback_hosts() {
+        // The primary host only serves logins
+        let fake = FakeTransport::with_handler(|request| {
+            let primary = request.url.host_str() == Some("primary.test");
+            (primary && !request.url.path().starts_with("/api/passport"))
+                .then(|| HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE, ""))
+        });
+        let client = WebullClient::builder()
+            .with_transport(fake)
+            .with_custom_url("http://primary.test")
+            .with_fallback_urls(["http://backup.test"])
+            .with_retry_policy(RetryPolicy::none())
+            .without_cache()
+            .build()
+            .unwrap();
+
+        client.login("user", "password").await.unwrap();
+        assert!(client.accounts().get_accounts().await.unwrap().is_empty());
+        let failover = client.failover().unwrap();
+        assert_eq!(failover.active_host().host_str(), Some("backup.test"));
+    }
+}
@@ -1,8 +1,14 @@
 // Re-export utility modules
 pub mod cache;
+pub mod circuit_breaker;
+pub mod clock;
+pub mod credential_provider;
 pub mod credentials;
 pub mod crypto;
+pub mod failover;
 pub mod rate_limit;
+pub mod retry;
+pub mod secret;
 pub mod serialization;
 
 // This module contains utility functions for the Webull API client
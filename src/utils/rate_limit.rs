@@ -1,78 +1,542 @@
+use crate::config::RateLimitConfig;
+use reqwest::header::HeaderMap;
+use reqwest::Method;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
-use tokio::time::sleep;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::{sleep, Instant};
+
+/// Category of API endpoints that share a rate limit budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateCategory {
+    /// Order placement, cancellation and queries
+    Trade,
+
+    /// Quotes, bars, instruments and other market data
+    Quote,
+
+    /// Account, balance and position data
+    Account,
+
+    /// Login, token refresh and logout
+    Auth,
+
+    /// Everything else; only the global cap applies
+    Other,
+}
+
+impl RateCategory {
+    /// All rate limit categories.
+    pub const ALL: [RateCategory; 5] = [
+        RateCategory::Trade,
+        RateCategory::Quote,
+        RateCategory::Account,
+        RateCategory::Auth,
+        RateCategory::Other,
+    ];
+
+    /// Get the category of an API path.
+    pub fn from_path(path: &str) -> Self {
+        let path = path.trim_start_matches('/').trim_start_matches("api/");
+        let path = path.strip_prefix("paper/").unwrap_or(path);
+
+        match path.split('/').next().unwrap_or_default() {
+            "trade" => Self::Trade,
+            "quote" | "options" | "securities" | "stock" => Self::Quote,
+            "account" | "asset" | "position" => Self::Account,
+            "passport" | "auth" => Self::Auth,
+            _ => Self::Other,
+        }
+    }
+
+    /// Get the default priority of requests in the category sent with a
+    /// method.
+    ///
+    /// Only requests that change orders are high priority; order queries are
+    /// not, so polling open orders cannot starve quotes and account data.
+    pub fn default_priority(&self, method: &Method) -> Priority {
+        match self {
+            Self::Trade if !matches!(*method, Method::GET | Method::HEAD) => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
+
+    /// Get the category name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Trade => "trade",
+            Self::Quote => "quote",
+            Self::Account => "account",
+            Self::Auth => "auth",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Priority of a request waiting for rate limit budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Served in FIFO order within its category
+    Normal,
+
+    /// Served before normal requests competing for the same budget, e.g. order
+    /// placement and cancellation
+    High,
+}
+
+/// Token bucket refilled continuously up to its capacity.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Maximum number of tokens
+    capacity: f64,
+
+    /// Available tokens
+    tokens: f64,
+
+    /// Tokens added per second
+    refill_per_sec: f64,
+
+    /// When the tokens were last refilled
+    updated_at: Instant,
+
+    /// No tokens are handed out before this instant
+    paused_until: Option<Instant>,
+}
+
+impl TokenBucket {
+    /// Create a full bucket allowing a number of requests per minute.
+    fn per_minute(requests_per_minute: u32) -> Self {
+        let capacity = requests_per_minute.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            updated_at: Instant::now(),
+            paused_until: None,
+        }
+    }
+
+    /// Add the tokens earned since the last refill.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    /// Get how long to wait until a token is available.
+    fn wait_time(&mut self, now: Instant) -> Duration {
+        if let Some(until) = self.paused_until {
+            if until > now {
+                return until - now;
+            }
+            self.paused_until = None;
+        }
+
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+        }
+    }
+
+    /// Change the number of requests allowed per minute.
+    fn set_limit(&mut self, requests_per_minute: u32, now: Instant) {
+        self.refill(now);
+        self.capacity = requests_per_minute.max(1) as f64;
+        self.refill_per_sec = self.capacity / 60.0;
+        self.tokens = self.tokens.min(self.capacity);
+    }
+
+    /// Get the budget left in the bucket.
+    fn headroom(&mut self, now: Instant) -> Headroom {
+        self.refill(now);
+        Headroom {
+            available: self.tokens.max(0.0) as u32,
+            limit: self.capacity as u32,
+            paused_for: self
+                .paused_until
+                .map(|until| until.saturating_duration_since(now))
+                .filter(|duration| !duration.is_zero()),
+        }
+    }
+
+    /// Stop handing out tokens for a while.
+    fn pause(&mut self, until: Instant) {
+        self.tokens = 0.0;
+        self.paused_until = Some(
+            self.paused_until
+                .map_or(until, |current| current.max(until)),
+        );
+    }
+}
+
+/// Rate limit budget left in a token bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Headroom {
+    /// Number of requests that may be sent right away
+    pub available: u32,
+
+    /// Number of requests allowed per minute
+    pub limit: u32,
+
+    /// How much longer the budget is paused, e.g. after a 429 response
+    pub paused_for: Option<Duration>,
+}
+
+impl Headroom {
+    /// Check if a request would have to wait for budget.
+    pub fn is_exhausted(&self) -> bool {
+        self.available == 0 || self.paused_for.is_some()
+    }
+}
+
+/// Global and per-category token buckets.
+#[derive(Debug)]
+struct Buckets {
+    /// Cap across all categories
+    global: TokenBucket,
+
+    /// Budget per category
+    categories: HashMap<RateCategory, TokenBucket>,
+}
+
+/// Time to wait before a request may be sent.
+#[derive(Debug, Clone, Copy)]
+struct Shortage {
+    /// How long until every bucket the request needs has a token
+    wait: Duration,
+
+    /// Whether the global bucket is out of tokens
+    global: bool,
+}
+
+impl Buckets {
+    /// Take a token from the category and the global bucket, or find out how
+    /// long to wait until both have one.
+    fn try_acquire(&mut self, category: RateCategory) -> Option<Shortage> {
+        let now = Instant::now();
+        let global = self.global.wait_time(now);
+        let mut wait = global;
+        if let Some(bucket) = self.categories.get_mut(&category) {
+            wait = wait.max(bucket.wait_time(now));
+        }
+
+        if !wait.is_zero() {
+            return Some(Shortage {
+                wait,
+                global: !global.is_zero(),
+            });
+        }
+
+        self.global.tokens -= 1.0;
+        if let Some(bucket) = self.categories.get_mut(&category) {
+            bucket.tokens -= 1.0;
+        }
+        None
+    }
+}
+
+/// High-priority requests that normal requests have to let go first.
+#[derive(Debug, Default)]
+struct PriorityWaiting {
+    /// High-priority requests waiting or being admitted, per category
+    categories: HashMap<RateCategory, usize>,
+
+    /// High-priority requests waiting for a global token
+    global: usize,
+}
+
+impl PriorityWaiting {
+    /// Check if a normal request in a category competes for a bucket that a
+    /// high-priority request is waiting on.
+    fn blocks(&self, category: RateCategory) -> bool {
+        self.global > 0
+            || self
+                .categories
+                .get(&category)
+                .is_some_and(|count| *count > 0)
+    }
+
+    /// Get the counter of a claim.
+    fn count(&mut self, claim: Claim) -> &mut usize {
+        match claim {
+            Claim::Category(category) => self.categories.entry(category).or_default(),
+            Claim::Global => &mut self.global,
+        }
+    }
+}
+
+/// Budget a high-priority request is waiting on.
+#[derive(Debug, Clone, Copy)]
+enum Claim {
+    /// The budget of a category
+    Category(RateCategory),
+
+    /// The global budget
+    Global,
+}
+
+/// Holds a high-priority claim on a budget until dropped.
+struct PriorityGuard<'a> {
+    /// Rate limiter the request is waiting on
+    limiter: &'a RateLimiter,
+
+    /// Budget the request is waiting on
+    claim: Claim,
+}
+
+impl<'a> PriorityGuard<'a> {
+    /// Claim a budget for a high-priority request.
+    fn new(limiter: &'a RateLimiter, claim: Claim) -> Self {
+        *limiter.priority_waiting.lock().unwrap().count(claim) += 1;
+        Self { limiter, claim }
+    }
+}
+
+impl Drop for PriorityGuard<'_> {
+    fn drop(&mut self) {
+        *self
+            .limiter
+            .priority_waiting
+            .lock()
+            .unwrap()
+            .count(self.claim) -= 1;
+        self.limiter.priority_done.notify_waiters();
+    }
+}
 
 /// Rate limiter for API requests.
+///
+/// Requests take a token from the bucket of their [`RateCategory`] and from a
+/// global bucket. Waiting requests are served in FIFO order within their
+/// category and priority. A [`Priority::High`] request is served before
+/// normal requests in its category, and before every normal request while the
+/// global bucket is empty, but an exhausted trade budget does not hold up
+/// quote or account requests.
 pub struct RateLimiter {
-    /// Maximum number of requests per minute
+    /// Maximum number of requests per minute across all categories
     requests_per_minute: u32,
 
-    /// Request timestamps
-    timestamps: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
+    /// Token buckets
+    buckets: Mutex<Buckets>,
+
+    /// FIFO queues of normal-priority requests, per category
+    lanes: HashMap<RateCategory, tokio::sync::Mutex<()>>,
+
+    /// FIFO queues of high-priority requests, per category
+    priority_lanes: HashMap<RateCategory, tokio::sync::Mutex<()>>,
+
+    /// High-priority requests waiting or being admitted
+    priority_waiting: Mutex<PriorityWaiting>,
+
+    /// Notified when a high-priority request stops waiting on a budget
+    priority_done: Notify,
 
     /// Backoff strategy
     backoff_strategy: BackoffStrategy,
 }
 
 impl RateLimiter {
-    /// Create a new rate limiter.
+    /// Create a new rate limiter with a global cap and no category budgets.
     pub fn new(requests_per_minute: u32) -> Self {
+        Self::with_buckets(requests_per_minute, HashMap::new())
+    }
+
+    /// Create a new rate limiter from rate limiting settings.
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        let categories = [
+            (RateCategory::Trade, config.trade_per_minute),
+            (RateCategory::Quote, config.quote_per_minute),
+            (RateCategory::Account, config.account_per_minute),
+            (RateCategory::Auth, config.auth_per_minute),
+        ]
+        .into_iter()
+        .map(|(category, limit)| (category, TokenBucket::per_minute(limit)))
+        .collect();
+
+        Self::with_buckets(config.requests_per_minute, categories)
+    }
+
+    /// Create a new rate limiter from its buckets.
+    fn with_buckets(
+        requests_per_minute: u32,
+        categories: HashMap<RateCategory, TokenBucket>,
+    ) -> Self {
         Self {
             requests_per_minute,
-            timestamps: Arc::new(Mutex::new(HashMap::new())),
+            buckets: Mutex::new(Buckets {
+                global: TokenBucket::per_minute(requests_per_minute),
+                categories,
+            }),
+            lanes: RateCategory::ALL
+                .into_iter()
+                .map(|category| (category, tokio::sync::Mutex::new(())))
+                .collect(),
+            priority_lanes: RateCategory::ALL
+                .into_iter()
+                .map(|category| (category, tokio::sync::Mutex::new(())))
+                .collect(),
+            priority_waiting: Mutex::new(PriorityWaiting::default()),
+            priority_done: Notify::new(),
             backoff_strategy: BackoffStrategy::default(),
         }
     }
 
+    /// Get the maximum number of requests per minute across all categories.
+    pub fn requests_per_minute(&self) -> u32 {
+        self.requests_per_minute
+    }
+
+    /// Get the number of requests per minute currently allowed for a category.
+    ///
+    /// Returns `None` if only the global cap applies to the category.
+    pub fn category_limit(&self, category: RateCategory) -> Option<u32> {
+        let buckets = self.buckets.lock().unwrap();
+        buckets
+            .categories
+            .get(&category)
+            .map(|bucket| bucket.capacity as u32)
+    }
+
+    /// Get the budget left under the global cap.
+    pub fn global_headroom(&self) -> Headroom {
+        self.buckets.lock().unwrap().global.headroom(Instant::now())
+    }
+
+    /// Get the budget left for a category.
+    ///
+    /// Returns `None` if only the global cap applies to the category.
+    pub fn headroom(&self, category: RateCategory) -> Option<Headroom> {
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets
+            .categories
+            .get_mut(&category)
+            .map(|bucket| bucket.headroom(Instant::now()))
+    }
+
     /// Set the backoff strategy.
     pub fn with_backoff_strategy(mut self, strategy: BackoffStrategy) -> Self {
         self.backoff_strategy = strategy;
         self
     }
 
-    /// Wait for rate limit to allow a request.
+    /// Wait for rate limit to allow a normal-priority request to an API path.
     pub async fn wait(&self, endpoint: &str) {
-        // Get the current time
-        let now = Instant::now();
+        self.acquire(RateCategory::from_path(endpoint), Priority::Normal)
+            .await;
+    }
+
+    /// Wait until a request in a category may be sent.
+    pub async fn acquire(&self, category: RateCategory, priority: Priority) {
+        let _guard;
+        let _turn = match priority {
+            Priority::High => {
+                _guard = PriorityGuard::new(self, Claim::Category(category));
+                self.priority_lanes[&category].lock().await
+            }
+            Priority::Normal => self.lanes[&category].lock().await,
+        };
+
+        loop {
+            if priority == Priority::Normal {
+                self.yield_to_priority(category).await;
+            }
+
+            let shortage = self.buckets.lock().unwrap().try_acquire(category);
+            match shortage {
+                None => return,
+                // Normal requests in other categories compete for global
+                // tokens only, so they only wait while the global bucket is
+                // what holds this request up
+                Some(shortage) if priority == Priority::High && shortage.global => {
+                    let _global = PriorityGuard::new(self, Claim::Global);
+                    sleep(shortage.wait).await;
+                }
+                Some(shortage) => sleep(shortage.wait).await,
+            }
+        }
+    }
 
-        // Check if we need to wait
-        let wait_time = {
-            // Get the timestamps for this endpoint
-            let mut timestamps = self.timestamps.lock().unwrap();
-            let endpoint_timestamps = timestamps
-                .entry(endpoint.to_string())
-                .or_insert_with(Vec::new);
-
-            // Remove timestamps older than 1 minute
-            endpoint_timestamps.retain(|t| now.duration_since(*t) < Duration::from_secs(60));
-
-            // Check if we've exceeded the rate limit
-            if endpoint_timestamps.len() >= self.requests_per_minute as usize {
-                // Calculate how long to wait
-                let oldest = endpoint_timestamps[0];
-                Some(Duration::from_secs(60) - now.duration_since(oldest))
-            } else {
-                // Add the current timestamp
-                endpoint_timestamps.push(now);
-                None
+    /// Wait until no high-priority request is waiting on a budget that a
+    /// request in a category needs.
+    async fn yield_to_priority(&self, category: RateCategory) {
+        loop {
+            let done = self.priority_done.notified();
+            if !self.priority_waiting.lock().unwrap().blocks(category) {
+                return;
             }
+            done.await;
+        }
+    }
+
+    /// Adapt the budget of an API path's category to rate limit response headers.
+    ///
+    /// Understands `X-RateLimit-Limit` (requests per minute),
+    /// `X-RateLimit-Remaining` and `X-RateLimit-Reset` (seconds until the
+    /// budget resets).
+    pub fn update_from_headers(&self, endpoint: &str, headers: &HeaderMap) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<f64>().ok())
+        };
+
+        let limit = header("x-ratelimit-limit");
+        let remaining = header("x-ratelimit-remaining");
+        let reset = header("x-ratelimit-reset");
+        if limit.is_none() && remaining.is_none() {
+            return;
+        }
+
+        let now = Instant::now();
+        let category = RateCategory::from_path(endpoint);
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = match buckets.categories.get_mut(&category) {
+            Some(bucket) => bucket,
+            None => &mut buckets.global,
         };
 
-        // Wait if necessary
-        if let Some(duration) = wait_time {
-            // Wait for the rate limit to reset
-            sleep(duration).await;
+        if let Some(limit) = limit.filter(|limit| *limit >= 1.0) {
+            bucket.set_limit(limit as u32, now);
+        }
+
+        if let Some(remaining) = remaining {
+            bucket.refill(now);
+            bucket.tokens = bucket.tokens.min(remaining.max(0.0));
+
+            if remaining < 1.0 {
+                if let Some(reset) = reset.filter(|reset| *reset > 0.0) {
+                    bucket.pause(now + Duration::from_secs_f64(reset));
+                }
+            }
+        }
+    }
 
-            // Add the current timestamp
-            let mut timestamps = self.timestamps.lock().unwrap();
-            let endpoint_timestamps = timestamps
-                .entry(endpoint.to_string())
-                .or_insert_with(Vec::new);
-            endpoint_timestamps.push(Instant::now());
+    /// Stop sending requests in an API path's category for a while, e.g. after
+    /// a 429 response with a `Retry-After` header.
+    pub fn pause(&self, endpoint: &str, duration: Duration) {
+        let category = RateCategory::from_path(endpoint);
+        let until = Instant::now() + duration;
+        let mut buckets = self.buckets.lock().unwrap();
+        match buckets.categories.get_mut(&category) {
+            Some(bucket) => bucket.pause(until),
+            None => buckets.global.pause(until),
         }
     }
 
+    /// Check if the budget of an API path's category is paused, e.g. after a
+    /// 429 with `Retry-After`.
+    pub fn is_paused(&self, endpoint: &str) -> bool {
+        let category = RateCategory::from_path(endpoint);
+        let now = Instant::now();
+        let buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.categories.get(&category).unwrap_or(&buckets.global);
+        bucket.paused_until.is_some_and(|until| until > now)
+    }
+
     /// Handle a rate limit error.
     pub async fn handle_rate_limit_error(&self, attempt: u32) -> Duration {
         self.backoff_strategy.get_backoff_duration(attempt)
@@ -134,3 +598,105 @@ impl Default for BackoffStrategy {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn categories_share_budgets_and_adapt_to_headers() {
+        assert_eq!(
+            RateCategory::from_path("/api/quote/tickerRealTimes/AAPL"),
+            RateCategory::Quote
+        );
+        assert_eq!(
+            RateCategory::from_path("/api/paper/trade/order"),
+            RateCategory::Trade
+        );
+        assert_eq!(
+            RateCategory::from_path("/api/wlas/watchlist"),
+            RateCategory::Other
+        );
+        assert_eq!(
+            RateCategory::Trade.default_priority(&Method::DELETE),
+            Priority::High
+        );
+        assert_eq!(
+            RateCategory::Trade.default_priority(&Method::GET),
+            Priority::Normal
+        );
+
+        let limiter = RateLimiter::from_config(&RateLimitConfig::default());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("30"));
+        limiter.update_from_headers("/api/quote/snapshot", &headers);
+
+        assert_eq!(limiter.category_limit(RateCategory::Quote), Some(30));
+        assert_eq!(limiter.category_limit(RateCategory::Trade), Some(60));
+        assert_eq!(limiter.category_limit(RateCategory::Other), None);
+
+        limiter.pause("/api/quote/snapshot", Duration::from_secs(5));
+        let quote = limiter.headroom(RateCategory::Quote).unwrap();
+        assert!(quote.is_exhausted());
+        assert_eq!(quote.limit, 30);
+        assert_eq!(limiter.global_headroom().available, 60);
+        assert_eq!(limiter.headroom(RateCategory::Other), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn priority_requests_skip_the_queue() {
+        let limiter = std::sync::Arc::new(RateLimiter::new(1));
+        limiter.acquire(RateCategory::Quote, Priority::Normal).await;
+
+        let order = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let normal = {
+            let (limiter, order) = (limiter.clone(), order.clone());
+            tokio::spawn(async move {
+                limiter.acquire(RateCategory::Quote, Priority::Normal).await;
+                order.lock().unwrap().push("quote");
+            })
+        };
+        sleep(Duration::from_millis(10)).await;
+        let high = {
+            let (limiter, order) = (limiter.clone(), order.clone());
+            tokio::spawn(async move {
+                limiter.acquire(RateCategory::Trade, Priority::High).await;
+                order.lock().unwrap().push("trade");
+            })
+        };
+
+        high.await.unwrap();
+        normal.await.unwrap();
+        assert_eq!(*order.lock().unwrap(), vec!["trade", "quote"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn exhausted_trade_budget_does_not_hold_up_quotes() {
+        let config = RateLimitConfig {
+            trade_per_minute: 1,
+            ..RateLimitConfig::default()
+        };
+        let limiter = std::sync::Arc::new(RateLimiter::from_config(&config));
+        limiter.acquire(RateCategory::Trade, Priority::High).await;
+
+        // The next trade waits almost a minute for its bucket to refill
+        let trade = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move {
+                limiter.acquire(RateCategory::Trade, Priority::High).await;
+                Instant::now()
+            })
+        };
+        sleep(Duration::from_millis(10)).await;
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire(RateCategory::Quote, Priority::Normal).await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        let admitted = trade.await.unwrap();
+        assert!(admitted - start >= Duration::from_secs(59));
+    }
+}
@@ -0,0 +1,93 @@
+use crate::error::WebullError;
+use crate::utils::rate_limit::BackoffStrategy;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Policy for retrying failed requests.
+///
+/// Only safe or idempotent requests are retried: GET, PUT and DELETE requests,
+/// read-only POSTs, and order submissions that carry a client order ID.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+
+    /// Backoff between attempts
+    pub backoff: BackoffStrategy,
+
+    /// Whether to retry requests rejected with 429 Too Many Requests
+    pub retry_rate_limited: bool,
+
+    /// Whether to retry requests that failed with a 5xx status
+    pub retry_server_errors: bool,
+
+    /// Whether to retry requests that failed to connect or timed out
+    pub retry_network_errors: bool,
+}
+
+impl RetryPolicy {
+    /// Create a retry policy with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the backoff between attempts.
+    pub fn with_backoff(mut self, backoff: BackoffStrategy) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Check if a failed attempt should be retried.
+    ///
+    /// `status` is the HTTP status of the response, if one was received.
+    pub fn should_retry(&self, status: Option<StatusCode>, error: &WebullError) -> bool {
+        match status {
+            Some(StatusCode::TOO_MANY_REQUESTS) => self.retry_rate_limited,
+            Some(status) if status.is_server_error() => self.retry_server_errors,
+            Some(_) => false,
+            None => match error {
+                WebullError::NetworkError(e) => {
+                    self.retry_network_errors && (e.is_timeout() || e.is_connect())
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// Get the delay before the next attempt.
+    ///
+    /// `attempt` is the number of attempts made so far.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.get_backoff_duration(attempt.saturating_sub(1))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: BackoffStrategy::Exponential {
+                initial: Duration::from_millis(250),
+                multiplier: 2.0,
+                max: Duration::from_secs(10),
+            },
+            retry_rate_limited: true,
+            retry_server_errors: true,
+            retry_network_errors: true,
+        }
+    }
+}
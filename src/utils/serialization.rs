@@ -7,7 +7,7 @@ pub fn to_json<T>(value: &T) -> WebullResult<String>
 where
     T: Serialize,
 {
-    serde_json::to_string(value).map_err(|e| WebullError::SerializationError(e))
+    serde_json::to_string(value).map_err(WebullError::SerializationError)
 }
 
 /// Convert a JSON string to a struct.
@@ -15,7 +15,7 @@ pub fn from_json<T>(json: &str) -> WebullResult<T>
 where
     T: for<'de> Deserialize<'de>,
 {
-    serde_json::from_str(json).map_err(|e| WebullError::SerializationError(e))
+    serde_json::from_str(json).map_err(WebullError::SerializationError)
 }
 
 /// Convert a struct to a JSON value.
@@ -23,7 +23,7 @@ pub fn to_json_value<T>(value: &T) -> WebullResult<Value>
 where
     T: Serialize,
 {
-    serde_json::to_value(value).map_err(|e| WebullError::SerializationError(e))
+    serde_json::to_value(value).map_err(WebullError::SerializationError)
 }
 
 /// Convert a JSON value to a struct.
@@ -31,7 +31,7 @@ pub fn from_json_value<T>(value: Value) -> WebullResult<T>
 where
     T: for<'de> Deserialize<'de>,
 {
-    serde_json::from_value(value).map_err(|e| WebullError::SerializationError(e))
+    serde_json::from_value(value).map_err(WebullError::SerializationError)
 }
 
 /// Build a JSON object with the given parameters.
@@ -44,3 +44,53 @@ pub fn build_json_object(params: &[(&str, Value)]) -> Value {
 
     obj
 }
+
+/// Serialize parameters into a canonical URL query string.
+///
+/// Keys are sorted so that equal parameters always produce the same string.
+/// `None` fields are skipped, arrays are joined with commas and nested
+/// objects are sent as JSON. Returns `None` if there are no parameters.
+pub fn to_query_string<T>(params: &T) -> WebullResult<Option<String>>
+where
+    T: Serialize,
+{
+    let fields = match to_json_value(params)? {
+        Value::Null => return Ok(None),
+        Value::Object(fields) => fields,
+        _ => {
+            return Err(WebullError::InvalidRequest(
+                "Query parameters must serialize to a map".to_string(),
+            ))
+        }
+    };
+
+    let mut pairs: Vec<(String, String)> = fields
+        .into_iter()
+        .filter_map(|(key, value)| query_value(&value).map(|value| (key, value)))
+        .collect();
+    if pairs.is_empty() {
+        return Ok(None);
+    }
+    pairs.sort();
+
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish();
+    Ok(Some(query))
+}
+
+/// Format a JSON value as a query parameter value.
+fn query_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(query_value)
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        other => Some(other.to_string()),
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "blocking")]
+
+use webull_rs::blocking::WebullClient;
+use webull_rs::models::order::{OrderRequest, OrderSide, OrderStatus};
+
+#[test]
+fn blocking_client_runs_requests_without_a_runtime() {
+    let _login = mockito::mock("POST", "/api/passport/login/v5/account")
+        .with_body(r#"{"access_token":"a","refresh_token":"r","expires_in":3600}"#)
+        .create();
+    let _accounts = mockito::mock("GET", "/api/account/getSecAccountList")
+        .with_body(
+            r#"{"success":true,"data":[{"id":"1","account_number":"1","account_type":"MARGIN","status":"ACTIVE","created_at":"2024-01-02T00:00:00Z","currency":"USD","paper_trading":false}]}"#,
+        )
+        .create();
+    let _order = mockito::mock("POST", "/api/trade/order")
+        .with_body(
+            r#"{"success":true,"data":{"id":"9","symbol":"AAPL","quantity":1.0,"status":"NEW","side":"BUY","order_type":"MARKET","time_in_force":"DAY","extended_hours":false,"created_at":"2024-01-02T00:00:00Z"}}"#,
+        )
+        .create();
+
+    let client = WebullClient::builder()
+        .with_custom_url(mockito::server_url())
+        .without_cache()
+        .build_blocking()
+        .unwrap();
+    client.login("user", "password").unwrap();
+
+    // Clones share the login and can be used from other threads
+    let threads: Vec<_> = (0..3)
+        .map(|_| {
+            let client = client.clone();
+            std::thread::spawn(move || client.accounts().get_accounts())
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap().unwrap().len(), 1);
+    }
+    assert_eq!(client.accounts().get_accounts().unwrap()[0].id, "1");
+
+    let order = OrderRequest::market()
+        .symbol("AAPL")
+        .quantity(1.0)
+        .side(OrderSide::Buy);
+    let placed = client.orders().place_order(&order).unwrap();
+    assert_eq!(placed.status, OrderStatus::New);
+}
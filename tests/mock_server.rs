@@ -0,0 +1,231 @@
+#![cfg(feature = "mock-server")]
+
+use reqwest::{Method, StatusCode};
+use webull_rs::mock_server::{self, MockServer, ScriptedResponse};
+use webull_rs::models::order::{OrderRequest, OrderSide, OrderStatus};
+use webull_rs::utils::rate_limit::BackoffStrategy;
+use webull_rs::utils::retry::RetryPolicy;
+use webull_rs::{ErrorKind, WebullClient, WebullError};
+
+async fn logged_in_client(server: &MockServer) -> WebullClient {
+    let client = server
+        .client_builder()
+        .without_cache()
+        .with_retry_policy(
+            RetryPolicy::new().with_backoff(BackoffStrategy::Constant(std::time::Duration::ZERO)),
+        )
+        .build()
+        .unwrap();
+    client
+        .login(mock_server::USERNAME, mock_server::PASSWORD)
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn orders_fill_and_update_the_account() {
+    let server = MockServer::start().await.unwrap();
+    let client = logged_in_client(&server).await;
+    let account_id = mock_server::LIVE_ACCOUNT_ID;
+
+    // A limit order below the market rests until the price drops
+    let buy = OrderRequest::limit()
+        .symbol("AAPL")
+        .quantity(10.0)
+        .price(180.0)
+        .side(OrderSide::Buy);
+    let placed = client.orders().place_order(&buy).await.unwrap();
+    assert_eq!(placed.status, OrderStatus::New);
+
+    server.set_price("AAPL", 179.0);
+    let order = client.orders().get_order(&placed.id).await.unwrap();
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.average_fill_price, Some(179.0));
+
+    let positions = client.accounts().get_positions(account_id).await.unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].quantity, 10.0);
+
+    let balance = client
+        .accounts()
+        .get_account_balance(account_id)
+        .await
+        .unwrap();
+    assert_eq!(balance.cash, 100_000.0 - 1_790.0);
+
+    // Market orders fill immediately; selling more than is held is rejected
+    let sell = OrderRequest::market()
+        .symbol("AAPL")
+        .quantity(4.0)
+        .side(OrderSide::Sell);
+    let sold = client.orders().place_order(&sell).await.unwrap();
+    assert_eq!(sold.status, OrderStatus::Filled);
+
+    let oversell = client
+        .orders()
+        .place_order(&sell.clone().quantity(100.0))
+        .await
+        .unwrap_err();
+    assert!(oversell.to_string().contains("INSUFFICIENT_POSITION"));
+
+    let too_expensive = OrderRequest::market()
+        .symbol("MSFT")
+        .quantity(1_000.0)
+        .side(OrderSide::Buy);
+    let error = client
+        .orders()
+        .place_order(&too_expensive)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InsufficientBuyingPower);
+
+    // Open orders can be canceled, but only once
+    let resting = client
+        .orders()
+        .place_order(&buy.clone().price(100.0))
+        .await
+        .unwrap();
+    client.orders().cancel_order(&resting.id).await.unwrap();
+    let canceled = client.orders().get_order(&resting.id).await.unwrap();
+    assert_eq!(canceled.status, OrderStatus::Canceled);
+    assert!(client.orders().cancel_order(&resting.id).await.is_err());
+
+    let history = client
+        .accounts()
+        .get_trade_history(account_id)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+}
+
+#[tokio::test]
+async fn scripted_errors_are_retried() {
+    let server = MockServer::start().await.unwrap();
+    let client = logged_in_client(&server).await;
+
+    server.script("/api/quote", ScriptedResponse::rate_limited(0));
+    server.script_times(
+        Some(Method::GET),
+        "/api/quote",
+        1,
+        ScriptedResponse::server_error(),
+    );
+
+    let quote = client.market_data().get_quote("MSFT").await.unwrap();
+    assert_eq!(quote.last_price, 420.0);
+    assert_eq!(server.request_count(Method::GET, "/api/quote"), 3);
+
+    let statuses: Vec<StatusCode> = server
+        .requests()
+        .iter()
+        .filter(|request| request.path.starts_with("/api/quote"))
+        .map(|request| request.status)
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::OK
+        ]
+    );
+}
+
+#[tokio::test]
+async fn requests_need_a_valid_token() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client_builder().build().unwrap();
+
+    let error = client.accounts().get_accounts().await.unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Unauthorized);
+
+    assert!(client.login(mock_server::USERNAME, "wrong").await.is_err());
+    client
+        .login(mock_server::USERNAME, mock_server::PASSWORD)
+        .await
+        .unwrap();
+    client.refresh_token().await.unwrap();
+
+    let accounts = client.accounts().get_accounts().await.unwrap();
+    assert_eq!(accounts[0].id, mock_server::LIVE_ACCOUNT_ID);
+}
+
+#[tokio::test]
+async fn account_calls_check_the_trading_mode() {
+    let server = MockServer::start().await.unwrap();
+    let client = logged_in_client(&server).await;
+    let paper = client.paper_trading().unwrap();
+    paper
+        .login(mock_server::USERNAME, mock_server::PASSWORD)
+        .await
+        .unwrap();
+
+    // A paper client must not trade or read the live account
+    let error = paper
+        .orders()
+        .place_option_order(mock_server::LIVE_ACCOUNT_ID, &[])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, WebullError::TradingModeMismatch(_)));
+    let error = paper
+        .accounts()
+        .get_positions(mock_server::LIVE_ACCOUNT_ID)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, WebullError::TradingModeMismatch(_)));
+    assert_eq!(server.request_count(Method::POST, "/api/paper/trade"), 0);
+    assert_eq!(
+        server.request_count(Method::GET, "/api/paper/account/getPositions"),
+        0
+    );
+
+    let positions = paper
+        .accounts()
+        .get_positions(mock_server::PAPER_ACCOUNT_ID)
+        .await
+        .unwrap();
+    assert!(positions.is_empty());
+}
+
+#[tokio::test]
+async fn writes_and_logins_invalidate_cached_responses() {
+    let server = MockServer::start().await.unwrap();
+    let client = server.client_builder().build().unwrap();
+    client
+        .login(mock_server::USERNAME, mock_server::PASSWORD)
+        .await
+        .unwrap();
+    let account_id = mock_server::LIVE_ACCOUNT_ID;
+
+    assert!(client
+        .accounts()
+        .get_positions(account_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // A filled order shows up in the cached positions straight away
+    let buy = OrderRequest::market()
+        .symbol("AAPL")
+        .quantity(5.0)
+        .side(OrderSide::Buy);
+    client.orders().place_order(&buy).await.unwrap();
+    let positions = client.accounts().get_positions(account_id).await.unwrap();
+    assert_eq!(positions[0].quantity, 5.0);
+
+    // Responses cached for one login are not served to the next
+    client.accounts().get_accounts().await.unwrap();
+    client.accounts().get_accounts().await.unwrap();
+    let fetches = server.request_count(Method::GET, "/api/account/getSecAccountList");
+    client.logout().await.unwrap();
+    client
+        .login(mock_server::USERNAME, mock_server::PASSWORD)
+        .await
+        .unwrap();
+    client.accounts().get_accounts().await.unwrap();
+    assert_eq!(
+        server.request_count(Method::GET, "/api/account/getSecAccountList"),
+        fetches + 1
+    );
+}